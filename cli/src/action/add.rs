@@ -1,13 +1,17 @@
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Plaintext, Secret, Store};
+use prs_lib::{
+    crypto::{prelude::*, ContextPool},
+    template::Template,
+    Plaintext, Secret, SecretBody, Store,
+};
 use thiserror::Error;
 
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 use crate::util::tomb;
 use crate::{
     cmd::matcher::{add::AddMatcher, MainMatcher, Matcher},
-    util::{cli, edit, error, stdin, sync},
+    util::{cli, edit, error, pass, stdin, sync},
 };
 
 /// Add secret action.
@@ -56,11 +60,38 @@ impl<'a> Add<'a> {
 
         let mut plaintext = Plaintext::empty();
 
+        // Pre-fill from template
+        if let Some(name) = matcher_add.template() {
+            let template = Template::load(&store, name).map_err(Err::Template)?;
+            let mut body = SecretBody::parse(&plaintext).map_err(Err::Template)?;
+            template.apply(&mut body).map_err(Err::Template)?;
+            plaintext = body.to_plaintext();
+        }
+
+        let attributes = matcher_add.attributes();
         if matcher_add.stdin() {
             plaintext = stdin::read_plaintext(!matcher_main.quiet())?;
+        } else if !attributes.is_empty() || matcher_add.generate().is_some() {
+            // Assemble secret from arguments
+            let mut body = SecretBody::parse(&plaintext).map_err(Err::Attribute)?;
+            if let Some(len) = matcher_add.generate() {
+                body.set_password(pass::generate_password(len).unsecure_to_str()?);
+            }
+            for (property, value) in attributes {
+                body.set_property(property, value).map_err(Err::Attribute)?;
+            }
+            plaintext = body.to_plaintext();
         } else if !matcher_add.empty() {
-            if let Some(changed) = edit::edit(&plaintext).map_err(Err::Edit)? {
-                plaintext = changed;
+            match edit::edit(&plaintext).map_err(Err::Edit)? {
+                Some(changed) => plaintext = changed,
+                // Do not save an untouched template, user did not enter anything
+                None if matcher_add.template().is_some() => {
+                    if !matcher_main.quiet() {
+                        eprintln!("Template is unchanged, addition cancelled");
+                    }
+                    error::quit();
+                },
+                None => {},
             }
         }
 
@@ -85,9 +116,9 @@ impl<'a> Add<'a> {
         }
 
         // Encrypt and write changed plaintext
-        // TODO: select proper recipients (use from current file?)
-        let recipients = store.recipients()?;
-        crate::crypto::context(&matcher_main)?
+        let recipients = store.recipients_for(&path)?;
+        let mut contexts = ContextPool::empty();
+        crate::crypto::pool_context(&mut contexts, &matcher_main, store.proto_for(&path))?
             .encrypt_file(&recipients, plaintext, &path)
             .map_err(Err::Write)?;
 
@@ -96,6 +127,9 @@ impl<'a> Add<'a> {
             sync.finalize(format!("Add secret to {}", secret.name))?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -120,9 +154,15 @@ pub(crate) enum Err {
     #[error("failed to normalize destination path")]
     NormalizePath(#[source] anyhow::Error),
 
+    #[error("failed to use secret template")]
+    Template(#[source] anyhow::Error),
+
     #[error("failed to edit secret in editor")]
     Edit(#[source] anyhow::Error),
 
+    #[error("failed to add property to secret")]
+    Attribute(#[source] anyhow::Error),
+
     #[error("failed to write changed secret")]
     Write(#[source] anyhow::Error),
 }
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::agent::Client;
+use thiserror::Error;
+
+use crate::cmd::matcher::{MainMatcher, Matcher};
+
+/// An agent flush action.
+pub(crate) struct Flush<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Flush<'a> {
+    /// Construct a new flush action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the flush action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+
+        // Nothing is cached if the agent is not running
+        let mut client = match Client::connect_default() {
+            Some(client) => client,
+            None => {
+                if matcher_main.verbose() {
+                    eprintln!("Agent is not running, nothing to flush");
+                }
+                return Ok(());
+            },
+        };
+
+        client.flush().map_err(Err::Flush)?;
+
+        if !matcher_main.quiet() {
+            eprintln!("Agent flushed");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to flush agent")]
+    Flush(#[source] anyhow::Error),
+}
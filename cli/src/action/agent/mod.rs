@@ -0,0 +1,46 @@
+pub(crate) mod flush;
+pub(crate) mod start;
+pub(crate) mod status;
+pub(crate) mod stop;
+
+use anyhow::Result;
+use clap::ArgMatches;
+
+use crate::cmd::matcher::{agent::AgentMatcher, Matcher};
+
+/// Agent management action.
+pub(crate) struct Agent<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Agent<'a> {
+    /// Construct a new agent action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the agent action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_agent = AgentMatcher::with(self.cmd_matches).unwrap();
+
+        if matcher_agent.cmd_start().is_some() {
+            return start::Start::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_agent.cmd_stop().is_some() {
+            return stop::Stop::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_agent.cmd_flush().is_some() {
+            return flush::Flush::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_agent.cmd_status().is_some() {
+            return status::Status::new(self.cmd_matches).invoke();
+        }
+
+        // Unreachable, clap will print help for missing sub command instead
+        unreachable!()
+    }
+}
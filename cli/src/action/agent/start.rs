@@ -0,0 +1,117 @@
+use std::{
+    env,
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::agent::{self, Client, Server};
+use thiserror::Error;
+
+use crate::{
+    cmd::matcher::{agent::start::StartMatcher, MainMatcher, Matcher},
+    util::error,
+};
+
+/// Time to wait for a background agent to start listening.
+const DAEMON_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An agent start action.
+pub(crate) struct Start<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Start<'a> {
+    /// Construct a new start action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the start action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_start = StartMatcher::with(self.cmd_matches).unwrap();
+
+        if matcher_start.daemon() {
+            return spawn_daemon(matcher_start.ttl(), &matcher_main);
+        }
+
+        let socket = agent::socket_path();
+        let mut server = Server::bind(socket.clone(), matcher_start.ttl()).map_err(Err::Start)?;
+
+        // Flush cache on session lock and system suspend
+        if let Err(err) = server.flush_on_lock() {
+            if !matcher_main.quiet() {
+                error::print_error(
+                    &err.context(
+                        "failed to flush agent on session lock and system suspend, ignoring",
+                    ),
+                );
+            }
+        }
+
+        if !matcher_main.quiet() {
+            eprintln!("Agent listening on {}", socket.display());
+        }
+
+        server.serve().map_err(Err::Serve)?;
+
+        if matcher_main.verbose() {
+            eprintln!("Agent stopped");
+        }
+
+        Ok(())
+    }
+}
+
+/// Start the agent in a background process, and wait for it to listen.
+fn spawn_daemon(ttl: Duration, matcher_main: &MainMatcher) -> Result<()> {
+    if Client::connect_default().is_some() {
+        return Err(Err::Start(prs_lib::agent::Err::Running.into()).into());
+    }
+
+    // Spawn in own process group, to not be interrupted along with this process
+    let current_exe = env::current_exe().map_err(Err::Daemon)?;
+    let mut process = Command::new(current_exe)
+        .args(["agent", "start", "--quiet", "--ttl"])
+        .arg(ttl.as_secs().to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(Err::Daemon)?;
+
+    let deadline = Instant::now() + DAEMON_TIMEOUT;
+    while Client::connect_default().is_none() {
+        if process.try_wait().map_err(Err::Daemon)?.is_some() || Instant::now() > deadline {
+            return Err(Err::DaemonListen.into());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    if !matcher_main.quiet() {
+        eprintln!("Agent started in background");
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to start agent")]
+    Start(#[source] anyhow::Error),
+
+    #[error("failed to serve agent")]
+    Serve(#[source] anyhow::Error),
+
+    #[error("failed to spawn agent in background")]
+    Daemon(#[source] std::io::Error),
+
+    #[error("failed to start agent in background, it did not start listening")]
+    DaemonListen,
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::agent::{self, Client};
+use thiserror::Error;
+
+use crate::{
+    cmd::matcher::{MainMatcher, Matcher},
+    util::time,
+};
+
+/// An agent status action.
+pub(crate) struct Status<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Status<'a> {
+    /// Construct a new status action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the status action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+
+        let socket = agent::socket_path();
+        let status = match Client::connect(&socket) {
+            Ok(mut client) => client.status().map_err(Err::Status)?,
+            Err(_) => {
+                println!("Agent: no");
+                if matcher_main.verbose() {
+                    println!("Socket: {}", socket.display());
+                }
+                return Ok(());
+            },
+        };
+
+        println!("Agent: yes");
+        println!("PID: {}", status.pid);
+        println!("Socket: {}", socket.display());
+        println!("Cached secrets: {}", status.entries);
+        println!(
+            "Cache time: {}",
+            time::format_duration(status.ttl.as_secs() as u32)
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to query agent status")]
+    Status(#[source] anyhow::Error),
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::agent::Client;
+use thiserror::Error;
+
+use crate::{
+    cmd::matcher::{agent::stop::StopMatcher, MainMatcher, Matcher},
+    util::error::{self, ErrorHints},
+};
+
+/// An agent stop action.
+pub(crate) struct Stop<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Stop<'a> {
+    /// Construct a new stop action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the stop action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_stop = StopMatcher::with(self.cmd_matches).unwrap();
+
+        let mut client = match Client::connect_default() {
+            Some(client) => client,
+            None if matcher_stop.do_try() => return Ok(()),
+            None => error::quit_error_msg("agent is not running", ErrorHints::default()),
+        };
+
+        client.stop().map_err(Err::Stop)?;
+
+        if !matcher_main.quiet() {
+            eprintln!("Agent stopped");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to stop agent")]
+    Stop(#[source] anyhow::Error),
+}
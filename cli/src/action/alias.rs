@@ -89,6 +89,9 @@ impl<'a> Alias<'a> {
             ))?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -140,7 +143,7 @@ where
 ///
 /// This function determines what path to provide when creating a symlink at
 /// `dst`, which links to `src`.
-fn secret_link_path(store: &Store, src: &Secret, dst: &Path) -> Result<PathBuf, Err> {
+pub(crate) fn secret_link_path(store: &Store, src: &Secret, dst: &Path) -> Result<PathBuf, Err> {
     let target = src
         .relative_path(&store.root)
         .map_err(|_| Err::UnknownRoot)?;
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, path::PathBuf, time::SystemTime};
+
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::{
+    crypto::ContextPool,
+    health::{Auditor, Report},
+    store::SecretIterConfig,
+    Secret, SecretBody, Store,
+};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{audit::AuditMatcher, MainMatcher, Matcher},
+    util::error,
+};
+
+/// Audit password health action.
+pub(crate) struct Audit<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Audit<'a> {
+    /// Construct a new audit action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the audit action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_audit = AuditMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_audit.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Audit secrets, not aliases as they share the password of their target
+        let config = SecretIterConfig {
+            find_files:         true,
+            find_symlink_files: false,
+        };
+        let mut secrets: Vec<Secret> = store
+            .secret_iter_config(config)
+            .filter_name(matcher_audit.query())
+            .collect();
+        secrets.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let policy = matcher_audit.policy();
+        let changed = if policy.max_age.is_some() {
+            last_changed(&store, &matcher_main)
+        } else {
+            HashMap::new()
+        };
+
+        let mut auditor = Auditor::new(policy);
+        let mut contexts = ContextPool::empty();
+        for secret in &secrets {
+            let body = crate::crypto::decrypt_file(&mut contexts, &matcher_main, secret)
+                .and_then(|plaintext| SecretBody::parse(&plaintext));
+            match body {
+                Ok(body) => {
+                    let changed = secret
+                        .relative_path(&store.root)
+                        .ok()
+                        .and_then(|path| changed.get(path))
+                        .copied();
+                    auditor.check(&secret.name, &body, changed);
+                },
+                Err(err) => auditor.unreadable(&secret.name, &err),
+            }
+        }
+        let report = auditor.finish();
+
+        if matcher_audit.json() {
+            println!("{}", report.to_json().map_err(|err| Err::Json(err.into()))?);
+        } else {
+            print_report(&report);
+        }
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if !matcher_main.quiet() {
+            if report.findings.is_empty() {
+                eprintln!("No problems found in {} secrets", report.secrets);
+            } else {
+                eprintln!(
+                    "Found problems in {} of {} secrets",
+                    report.findings.len(),
+                    report.secrets,
+                );
+            }
+        }
+
+        if !report.findings.is_empty() {
+            error::exit(1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the time each secret was last changed from git history.
+///
+/// Returns an empty map if the store does not use git, or if its history cannot
+/// be read, in which case ages are not checked.
+fn last_changed(store: &Store, matcher_main: &MainMatcher) -> HashMap<PathBuf, SystemTime> {
+    if !store.sync().is_init() {
+        if matcher_main.verbose() {
+            eprintln!("Password store does not use git, not checking password age");
+        }
+        return HashMap::new();
+    }
+
+    match store.history().last_changed() {
+        Ok(changed) => changed,
+        Err(err) => {
+            error::print_warning(format!(
+                "failed to read password store history, not checking password age: {err:#}"
+            ));
+            HashMap::new()
+        },
+    }
+}
+
+/// Print the given report in human readable form.
+fn print_report(report: &Report) {
+    for finding in &report.findings {
+        println!("{}:", finding.name);
+        for issue in &finding.issues {
+            println!("    {issue}");
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to serialize report")]
+    Json(#[source] anyhow::Error),
+}
@@ -58,7 +58,7 @@ impl<'a> Clone<'a> {
                 eprintln!("Store cloned");
             } else {
                 let bin = util::bin_name();
-                let config = crate::crypto::config(&matcher_main);
+                let config = crate::crypto::config(&matcher_main, store.proto());
                 let system_has_secret = crypto::util::has_private_key(&config).unwrap_or(true);
 
                 if system_has_secret {
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Store};
+use prs_lib::{
+    crypto::ContextPool,
+    SecretBody, Store,
+};
 use thiserror::Error;
 
 use crate::cmd::matcher::{copy::CopyMatcher, MainMatcher, Matcher};
@@ -38,19 +41,22 @@ impl<'a> Copy<'a> {
         tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
 
         let secret =
-            select::store_select_secret(&store, matcher_copy.query()).ok_or(Err::NoneSelected)?;
+            select::store_select_secret_indexed(&store, &matcher_main, matcher_copy.query())
+                .ok_or(Err::NoneSelected)?;
 
         secret::print_name(matcher_copy.query(), &secret, &store, matcher_main.quiet());
 
-        let mut plaintext = crate::crypto::context(&matcher_main)?
-            .decrypt_file(&secret.path)
+        let mut contexts = ContextPool::empty();
+        let mut plaintext = crate::crypto::decrypt_file(&mut contexts, &matcher_main, &secret)
             .map_err(Err::Read)?;
 
         // Trim plaintext to property or first line
         if let Some(property) = matcher_copy.property() {
-            plaintext = plaintext.property(property).map_err(Err::Property)?;
+            plaintext = SecretBody::parse(&plaintext)?
+                .property(property)
+                .ok_or_else(|| Err::Property(property.into()))?;
         } else if !matcher_copy.all() {
-            plaintext = plaintext.first_line()?;
+            plaintext = SecretBody::parse(&plaintext)?.password().into();
         }
 
         clipboard::plaintext_copy(
@@ -84,6 +90,6 @@ pub(crate) enum Err {
     #[error("failed to read secret")]
     Read(#[source] anyhow::Error),
 
-    #[error("failed to select property from secret")]
-    Property(#[source] anyhow::Error),
+    #[error("property '{}' does not exist in secret", _0)]
+    Property(String),
 }
@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use anyhow::Result;
+use clap::ArgMatches;
+use colored::Colorize;
+use prs_lib::{crypto::ContextPool, history::Revision, Plaintext, Secret, Store};
+use similar::{ChangeTag, TextDiff};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{diff::DiffMatcher, MainMatcher, Matcher},
+    util::{secret, select},
+};
+
+/// Number of unchanged lines to show around changes.
+const CONTEXT_LINES: usize = 3;
+
+/// Secret diff action.
+pub(crate) struct Diff<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Diff<'a> {
+    /// Construct a new diff action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the diff action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_diff = DiffMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_diff.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        let secret = select::store_select_secret_history(&store, matcher_diff.query())
+            .ok_or(Err::NoneSelected)?;
+
+        secret::print_name(matcher_diff.query(), &secret, &store, matcher_main.quiet());
+
+        let mut contexts = ContextPool::empty();
+        let mut diff = Differ {
+            store:        &store,
+            secret:       &secret,
+            contexts:     &mut contexts,
+            matcher_main: &matcher_main,
+        };
+        let ((old_label, old), (new_label, new)) = match (matcher_diff.rev1(), matcher_diff.rev2())
+        {
+            (Some(rev1), Some(rev2)) => (diff.at(rev1)?, diff.at(rev2)?),
+            (Some(rev1), None) => (diff.at(rev1)?, diff.current()?),
+            (None, _) => diff.previous()?,
+        };
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if old == new {
+            if !matcher_main.quiet() {
+                eprintln!("No changes");
+            }
+            return Ok(());
+        }
+
+        print_diff(
+            old.unsecure_to_str().map_err(|err| Err::Utf8(err.into()))?,
+            new.unsecure_to_str().map_err(|err| Err::Utf8(err.into()))?,
+            &format!("{}@{}", secret.name, old_label),
+            &format!("{}@{}", secret.name, new_label),
+        );
+
+        Ok(())
+    }
+}
+
+/// Helper to decrypt versions of a secret to compare.
+struct Differ<'a> {
+    store:        &'a Store,
+    secret:       &'a Secret,
+    contexts:     &'a mut ContextPool,
+    matcher_main: &'a MainMatcher<'a>,
+}
+
+impl Differ<'_> {
+    /// Decrypt the secret as it was in the given revision.
+    fn at(&mut self, rev: &str) -> Result<(String, Plaintext)> {
+        let path = self.path()?;
+        let revision = self
+            .store
+            .history()
+            .revision_at(path, rev)
+            .map_err(Err::Read)?;
+        let plaintext = self.decrypt(&revision)?;
+        Ok((revision.short_commit().into(), plaintext))
+    }
+
+    /// Decrypt the current secret, empty if it has been removed.
+    fn current(&mut self) -> Result<(String, Plaintext)> {
+        if !self.secret.path.is_file() {
+            return Ok(("removed".into(), Plaintext::empty()));
+        }
+        let plaintext = crate::crypto::decrypt_file(self.contexts, self.matcher_main, self.secret)
+            .map_err(Err::Read)?;
+        Ok(("current".into(), plaintext))
+    }
+
+    /// Decrypt the previous and current version of the secret.
+    ///
+    /// The previous version is the last committed version that differs from
+    /// the current secret.
+    fn previous(&mut self) -> Result<((String, Plaintext), (String, Plaintext))> {
+        let path = self.path()?;
+        let log = self.store.history().log(path).map_err(Err::Read)?;
+        let mut revisions = log.iter().filter(|revision| !revision.removed);
+
+        let current = self.current()?;
+        let mut previous = revisions.next().ok_or(Err::NoPrevious)?;
+        let mut plaintext = self.decrypt(previous)?;
+        if plaintext == current.1 {
+            previous = revisions.next().ok_or(Err::NoPrevious)?;
+            plaintext = self.decrypt(previous)?;
+        }
+
+        Ok(((previous.short_commit().into(), plaintext), current))
+    }
+
+    /// Decrypt the secret in the given revision.
+    fn decrypt(&mut self, revision: &Revision) -> Result<Plaintext> {
+        Ok(
+            crate::crypto::decrypt_revision(self.contexts, self.matcher_main, self.store, revision)
+                .map_err(Err::Read)?,
+        )
+    }
+
+    /// Get the secret path relative to the store root.
+    fn path(&self) -> Result<&Path> {
+        Ok(self
+            .secret
+            .relative_path(&self.store.root)
+            .map_err(|err| Err::Read(err.into()))?)
+    }
+}
+
+/// Print a colored unified diff between the old and new text.
+fn print_diff(old: &str, new: &str, old_label: &str, new_label: &str) {
+    let diff = TextDiff::from_lines(old, new);
+
+    println!("{}", format!("--- {old_label}").bold());
+    println!("{}", format!("+++ {new_label}").bold());
+    for hunk in diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .iter_hunks()
+    {
+        println!("{}", hunk.header().to_string().cyan());
+        for change in hunk.iter_changes() {
+            let line = change.value().trim_end_matches('\n');
+            match change.tag() {
+                ChangeTag::Delete => println!("{}", format!("-{line}").red()),
+                ChangeTag::Insert => println!("{}", format!("+{line}").green()),
+                ChangeTag::Equal => println!(" {line}"),
+            }
+            if change.missing_newline() {
+                println!("\\ No newline at end of file");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("no secret selected")]
+    NoneSelected,
+
+    #[error("failed to read secret")]
+    Read(#[source] anyhow::Error),
+
+    #[error("secret has no previous revision to compare with")]
+    NoPrevious,
+
+    #[error("failed to diff secret, it is not valid UTF-8")]
+    Utf8(#[source] anyhow::Error),
+}
@@ -2,7 +2,11 @@ use std::fs;
 
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{Secret, Store};
+use prs_lib::{
+    crypto::{prelude::*, ContextPool, Proto},
+    transaction::Transaction,
+    Secret, Store,
+};
 use thiserror::Error;
 
 use crate::cmd::matcher::{duplicate::DuplicateMatcher, MainMatcher, Matcher};
@@ -70,16 +74,30 @@ impl<'a> Duplicate<'a> {
         }
 
         // Copy secret
-        fs::copy(&secret.path, path).map_err(Err::Copy)?;
+        let mut tx = store.transaction();
+        let proto = secret.proto();
+        tx.write(&path, &fs::read(&secret.path).map_err(Err::Copy)?)?;
+
+        // Re-encrypt if copied to directory with other recipients or protocol
+        if store.recipients_dir(&secret.path) != store.recipients_dir(&path)
+            || proto != new_secret.proto()
+        {
+            recrypt_for_dest(&mut tx, proto, &new_secret, &matcher_main).map_err(Err::Recrypt)?;
+        }
 
-        // Finalize sync
-        if !matcher_duplicate.no_sync() {
-            sync.finalize(format!(
-                "Duplicate from {} to {}",
-                secret.name, new_secret.name
-            ))?;
+        // Commit changes, finalize sync
+        if matcher_duplicate.no_sync() {
+            tx.commit()?;
+        } else {
+            tx.commit_sync(
+                &sync,
+                format!("Duplicate from {} to {}", secret.name, new_secret.name),
+            )?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -92,6 +110,35 @@ impl<'a> Duplicate<'a> {
     }
 }
 
+/// Re-encrypt a secret for the recipients at its location.
+///
+/// Used when a secret is copied or moved into a directory with other
+/// recipients, or to a path with the suffix of another protocol. The secret is
+/// decrypted with the given protocol it was encrypted with, and encrypted with
+/// the protocol of its new path. Aliases are skipped, as they point to a
+/// secret elsewhere.
+pub(crate) fn recrypt_for_dest(
+    tx: &mut Transaction,
+    proto: Proto,
+    secret: &Secret,
+    matcher_main: &MainMatcher,
+) -> Result<()> {
+    if secret.path.symlink_metadata()?.file_type().is_symlink() {
+        return Ok(());
+    }
+
+    let mut contexts = ContextPool::empty();
+    let recipients = tx.store().recipients_for(&secret.path)?;
+    let plaintext = crate::crypto::pool_context(&mut contexts, matcher_main, proto)?
+        .decrypt_file(&secret.path)?;
+    tx.encrypt(
+        crate::crypto::pool_context(&mut contexts, matcher_main, secret.proto())?,
+        &recipients,
+        plaintext,
+        &secret.path,
+    )
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Err {
     #[error("failed to access password store")]
@@ -109,4 +156,7 @@ pub(crate) enum Err {
 
     #[error("failed to copy secret file")]
     Copy(#[source] std::io::Error),
+
+    #[error("failed to re-encrypt secret for recipients at destination")]
+    Recrypt(#[source] anyhow::Error),
 }
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Store};
+use prs_lib::{
+    crypto::{prelude::*, ContextPool},
+    Plaintext, SecretBody, Store,
+};
 use thiserror::Error;
 
 #[cfg(all(feature = "tomb", target_os = "linux"))]
@@ -51,21 +54,33 @@ impl<'a> Edit<'a> {
 
         secret::print_name(matcher_edit.query(), &secret, &store, matcher_main.quiet());
 
-        let mut context = crate::crypto::context(&matcher_main)?;
-        let mut plaintext = context.decrypt_file(&secret.path).map_err(Err::Read)?;
+        let mut contexts = ContextPool::empty();
+        let context = crate::crypto::pool_context(&mut contexts, &matcher_main, secret.proto())?;
+        let original = context.decrypt_file(&secret.path).map_err(Err::Read)?;
 
-        if matcher_edit.stdin() {
-            plaintext = stdin::read_plaintext(!matcher_main.quiet())?;
-        } else {
-            plaintext = if let Some(changed) = edit::edit(&plaintext).map_err(Err::Edit)? {
-                changed
-            } else {
+        let mut message = format!("Edit secret {}", secret.name);
+        let plaintext = if matcher_edit.stdin() {
+            stdin::read_plaintext(!matcher_main.quiet())?
+        } else if matcher_edit.edit_fields() {
+            let (plaintext, description) = edit_fields(&original, &matcher_edit, &matcher_main)?;
+            if plaintext == original {
                 if !matcher_main.quiet() {
                     eprintln!("Secret is unchanged");
                 }
                 error::quit();
-            };
-        }
+            }
+            if !description.is_empty() {
+                message = format!("{message}: {description}");
+            }
+            plaintext
+        } else if let Some(changed) = edit::edit(&original).map_err(Err::Edit)? {
+            changed
+        } else {
+            if !matcher_main.quiet() {
+                eprintln!("Secret is unchanged");
+            }
+            error::quit();
+        };
 
         // Confirm if empty secret should be stored
         if !matcher_main.force()
@@ -79,29 +94,116 @@ impl<'a> Edit<'a> {
         }
 
         // Encrypt and write changed plaintext
-        // TODO: select proper recipients (use from current file?)
-        let recipients = store.recipients()?;
+        let changes = changes(&original, &plaintext);
+        let recipients = store.recipients_for(&secret.path)?;
         context
             .encrypt_file(&recipients, plaintext, &secret.path)
             .map_err(Err::Write)?;
 
         // Finalize sync
         if !matcher_edit.no_sync() {
-            sync.finalize(format!("Edit secret {}", secret.name))?;
+            sync.finalize(message)?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
 
         if !matcher_main.quiet() {
             eprintln!("Secret updated");
+            if matcher_main.verbose() && !changes.is_empty() {
+                eprintln!("Changes: {}", changes.join(", "));
+            }
         }
 
         Ok(())
     }
 }
 
+/// Edit fields of the secret as given on the command line.
+///
+/// Returns the edited plaintext, and a description of the edits for the
+/// commit message.
+fn edit_fields(
+    original: &Plaintext,
+    matcher_edit: &EditMatcher,
+    matcher_main: &MainMatcher,
+) -> Result<(Plaintext, String)> {
+    let mut body = SecretBody::parse(original).map_err(Err::Read)?;
+    let mut description = vec![];
+
+    if matcher_edit.password_stdin() {
+        let password = stdin::read_plaintext(!matcher_main.quiet())?.first_line()?;
+        if password.is_empty() {
+            return Err(Err::NoPassword.into());
+        }
+        body.set_password(password.unsecure_to_str()?);
+        description.push("change password".to_owned());
+    }
+
+    let sets = matcher_edit.set();
+    for (property, value) in &sets {
+        body.set_property(property, value).map_err(Err::Property)?;
+    }
+    if !sets.is_empty() {
+        let keys: Vec<&str> = sets.iter().map(|(property, _)| *property).collect();
+        description.push(format!("set {}", keys.join(", ")));
+    }
+
+    let mut unset = vec![];
+    for property in matcher_edit.unset() {
+        if body.remove_property(property).map_err(Err::Property)? {
+            unset.push(property);
+        } else if !matcher_main.quiet() {
+            error::print_warning(format!("property '{property}' does not exist in secret"));
+        }
+    }
+    if !unset.is_empty() {
+        description.push(format!("unset {}", unset.join(", ")));
+    }
+
+    Ok((body.to_plaintext(), description.join("; ")))
+}
+
+/// Describe the changes between the original and edited secret.
+///
+/// Compares the password and fields of both secret bodies. Returns no changes
+/// if either is not valid UTF-8.
+fn changes(original: &Plaintext, edited: &Plaintext) -> Vec<String> {
+    let (original, edited) = match (SecretBody::parse(original), SecretBody::parse(edited)) {
+        (Ok(original), Ok(edited)) => (original, edited),
+        _ => return vec![],
+    };
+
+    let mut changes = vec![];
+    if original.password() != edited.password() {
+        changes.push("password changed".into());
+    }
+
+    // Compare values of all keys, repeated keys are compared as a whole
+    let mut keys: Vec<&str> = vec![];
+    for field in original.fields().chain(edited.fields()) {
+        if !keys.iter().any(|key| field.is(key)) {
+            keys.push(field.key());
+        }
+    }
+    for key in keys {
+        let old: Vec<&str> = original.get_all(key).collect();
+        let new: Vec<&str> = edited.get_all(key).collect();
+        if old.is_empty() {
+            changes.push(format!("field '{key}' added"));
+        } else if new.is_empty() {
+            changes.push(format!("field '{key}' removed"));
+        } else if old != new {
+            changes.push(format!("field '{key}' changed"));
+        }
+    }
+    changes
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Err {
     #[error("failed to access password store")]
@@ -120,6 +222,12 @@ pub(crate) enum Err {
     #[error("failed to edit secret in editor")]
     Edit(#[source] anyhow::Error),
 
+    #[error("no password read from stdin")]
+    NoPassword,
+
+    #[error("failed to edit property in secret")]
+    Property(#[source] anyhow::Error),
+
     #[error("failed to write changed secret")]
     Write(#[source] anyhow::Error),
 }
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::{find, index::Index, Store};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{find::FindMatcher, MainMatcher, Matcher},
+    util::{self, error},
+};
+
+/// Find secrets action.
+pub(crate) struct Find<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Find<'a> {
+    /// Construct a new find action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the find action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_find = FindMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_find.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Fields are only known through the index, decrypt all secrets without it
+        let query = matcher_find.query();
+        let index = if query.uses_fields() {
+            if !Index::is_enabled(&store) && matcher_main.verbose() {
+                eprintln!(
+                    "Decrypting all secrets, build a search index to speed this up: prs \
+                     housekeeping index"
+                );
+            }
+            Some(util::index::load_or_scan(&store, &matcher_main).map_err(Err::Index)?)
+        } else {
+            None
+        };
+
+        let found = find::find(&store, index.as_ref(), &query);
+        let limit = if matcher_find.first() { 1 } else { found.len() };
+        for found in found.iter().take(limit) {
+            println!("{}", found.secret.name);
+        }
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if found.is_empty() {
+            if !matcher_main.quiet() {
+                eprintln!("No secrets found");
+            }
+            error::exit(1);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to load search index")]
+    Index(#[source] anyhow::Error),
+}
@@ -3,7 +3,11 @@ use std::path::PathBuf;
 use anyhow::Result;
 use chbs::{config::BasicConfig, prelude::*};
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Plaintext, Secret, Store};
+use prs_lib::{
+    crypto::{prelude::*, ContextPool},
+    template::{Generator, Template},
+    Plaintext, Secret, SecretBody, Store,
+};
 use thiserror::Error;
 
 #[cfg(feature = "clipboard")]
@@ -11,7 +15,10 @@ use crate::util::clipboard;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 use crate::util::tomb;
 use crate::{
-    cmd::matcher::{generate::GenerateMatcher, MainMatcher, Matcher},
+    cmd::matcher::{
+        generate::{GenerateMatcher, PASSPHRASE_LENGTH, PASSWORD_LENGTH},
+        MainMatcher, Matcher,
+    },
     util::{cli, edit, error, pass, secret, select, stdin, sync},
 };
 
@@ -80,8 +87,24 @@ impl<'a> Generate<'a> {
         };
 
         // Generate secure password/passphrase plaintext
-        let mut context = crate::crypto::context(&matcher_main)?;
-        let mut plaintext = generate_password(&matcher_generate);
+        let proto = dest
+            .as_ref()
+            .map_or_else(|| store.proto(), |dest| store.proto_for(&dest.0));
+        let mut contexts = ContextPool::empty();
+        let context = crate::crypto::pool_context(&mut contexts, &matcher_main, proto)?;
+        let template = matcher_generate
+            .template()
+            .map(|name| Template::load(&store, name))
+            .transpose()
+            .map_err(Err::Template)?;
+
+        // Template password generator is used unless explicitly given
+        let mut plaintext = match template.as_ref().and_then(|t| t.password.generate) {
+            Some(generator) if !matcher_generate.explicit() => {
+                generate(generator, template.as_ref().and_then(|t| t.password.length))
+            },
+            _ => generate_password(&matcher_generate),
+        };
 
         // If destination already exists, merge
         if let Some(dest) = &dest {
@@ -97,18 +120,27 @@ impl<'a> Generate<'a> {
                 }
             }
 
-            // Append existing secret except first line to new secret
+            // Replace password in existing secret, keeping all other lines
             if exists {
-                let existing = context
+                let mut body = context
                     .decrypt_file(&dest.0)
-                    .and_then(|p| p.except_first_line())
+                    .and_then(|p| SecretBody::parse(&p))
                     .map_err(Err::Read)?;
-                if !existing.is_empty() {
-                    plaintext.append(&existing, true);
-                }
+                body.set_password(plaintext.unsecure_to_str()?);
+                plaintext = body.to_plaintext();
             }
         }
 
+        // Fill template fields, generate empty fields with a generator
+        if let Some(template) = &template {
+            let mut body = SecretBody::parse(&plaintext).map_err(Err::Template)?;
+            template.apply(&mut body).map_err(Err::Template)?;
+            template
+                .generate_fields(&mut body, generate)
+                .map_err(Err::Template)?;
+            plaintext = body.to_plaintext();
+        }
+
         // Append from stdin
         if matcher_generate.stdin() {
             let extra = stdin::read_plaintext(!matcher_main.quiet())?;
@@ -136,8 +168,7 @@ impl<'a> Generate<'a> {
 
         // Encrypt and write changed plaintext if we need to store
         if let Some(dest) = &dest {
-            // TODO: select proper recipients (use from current file?)
-            let recipients = store.recipients()?;
+            let recipients = store.recipients_for(&dest.0)?;
             context
                 .encrypt_file(&recipients, plaintext.clone(), &dest.0)
                 .map_err(Err::Write)?;
@@ -167,6 +198,9 @@ impl<'a> Generate<'a> {
             }
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -202,6 +236,24 @@ fn generate_password(matcher_generate: &GenerateMatcher) -> Plaintext {
     }
 }
 
+/// Generate a value with a template generator.
+///
+/// Uses the default length of the generator if no length is given.
+#[allow(clippy::field_reassign_with_default)]
+fn generate(generator: Generator, length: Option<u16>) -> Plaintext {
+    match generator {
+        Generator::Password => {
+            let min = pass::PASSWORD_MIN_LENGTH as u16;
+            pass::generate_password(length.unwrap_or(PASSWORD_LENGTH).max(min))
+        },
+        Generator::Passphrase => {
+            let mut config = BasicConfig::default();
+            config.words = length.unwrap_or(PASSPHRASE_LENGTH) as usize;
+            config.to_scheme().generate().into()
+        },
+    }
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Err {
     #[error("failed to access password store")]
@@ -214,6 +266,9 @@ pub(crate) enum Err {
     #[error("failed to normalize destination path")]
     NormalizePath(#[source] anyhow::Error),
 
+    #[error("failed to use secret template")]
+    Template(#[source] anyhow::Error),
+
     #[error("failed to edit secret in editor")]
     Edit(#[source] anyhow::Error),
 
@@ -45,6 +45,9 @@ impl<'a> Git<'a> {
 
         let result = git(&store, matcher_git.command(), matcher_main.verbose());
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
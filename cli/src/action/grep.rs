@@ -1,17 +1,33 @@
-use grep_searcher::{BinaryDetection, SearcherBuilder};
+use std::{
+    collections::BTreeMap,
+    io,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use grep_printer::{
+    ColorSpecs, JSONBuilder, Standard, StandardBuilder, Summary, SummaryBuilder, SummaryKind, JSON,
+};
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{BinaryDetection, Searcher, SearcherBuilder};
 use termcolor::{BufferedStandardStream, ColorChoice, StandardStream};
 
 use anyhow::Result;
 use clap::ArgMatches;
 use colored::{Color, Colorize};
-use prs_lib::{crypto::prelude::*, Store};
-use std::io;
+use prs_lib::{crypto::ContextPool, Plaintext, Secret, SecretBody, Store};
 use thiserror::Error;
 
-#[cfg(all(feature = "tomb", target_os = "linux"))]
-use crate::cmd::matcher::{grep::GrepMatcher, MainMatcher, Matcher};
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 use crate::util::tomb;
+use crate::{
+    cmd::matcher::{grep::GrepMatcher, MainMatcher, Matcher},
+    util::error,
+};
 
 /// List secrets action.
 pub(crate) struct Grep<'a> {
@@ -42,60 +58,53 @@ impl<'a> Grep<'a> {
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
 
+        // Line numbers are meaningless when searching names or fields
+        let line_number = !matcher_grep.names() && matcher_grep.field().is_none();
         let mut searcher = SearcherBuilder::new()
             .binary_detection(BinaryDetection::quit(b'\x00'))
+            .invert_match(matcher_grep.invert_match())
+            .line_number(line_number)
             .build();
-        let matcher_regex = grep_regex::RegexMatcher::new_line_matcher(matcher_grep.search())?;
-        let mut printer = grep_printer::StandardBuilder::new()
-            .color_specs(grep_printer::ColorSpecs::new(&[
-                "path:fg:magenta".parse()?,
-                "line:fg:green".parse()?,
-                "column:fg:yellow".parse()?,
-                "match:fg:red".parse()?,
-                "match:style:bold".parse()?,
-            ]))
-            .column(true)
-            .heading(true)
-            .build({
-                if atty::is(atty::Stream::Stdout) {
-                    self::StandardStreamKind::LineBuffered(StandardStream::stdout(
-                        ColorChoice::Auto,
-                    ))
-                } else {
-                    self::StandardStreamKind::BlockBuffered(BufferedStandardStream::stdout(
-                        ColorChoice::Never,
-                    ))
-                }
-            });
+        let matcher_regex = RegexMatcherBuilder::new()
+            .case_insensitive(matcher_grep.ignore_case())
+            .line_terminator(Some(b'\n'))
+            .build(matcher_grep.search())?;
+        let mut printer = Printer::new(&matcher_grep, line_number)?;
 
-        for sec in store.secret_iter() {
-            let path = sec.path;
-            let path_str = path.display().to_string();
-
-            let sep = path_str.rfind('/').ok_or(Err::NoSubfolder)? + 1;
-            let subfolder = &path_str[store.root.display().to_string().len() + 1..sep];
-            let file = &path_str[sep..path_str.rfind(".gpg").unwrap()];
-
-            let plaintext = crate::crypto::context(&matcher_main)?
-                .decrypt_file(&path)
-                .map_err(Err::Read)?;
-
-            let path_color = format!(
-                "{}{}",
-                subfolder.bold().underline(),
-                file.color(Color::TrueColor {
-                    r: 160,
-                    g: 100,
-                    b: 105,
-                })
-                .underline(),
-            );
-
-            searcher.search_slice(
+        let mut secrets: Vec<Secret> = store.secret_iter().collect();
+        secrets.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let result = search_parallel(&secrets, &matcher_main, &matcher_grep, |secret, content| {
+            // Search the name on its own line before the content, if selected
+            let haystack = match (matcher_grep.names(), content) {
+                (true, content) => {
+                    let mut haystack = Plaintext::from(format!("{}\n", secret.name));
+                    if let Some(content) = content {
+                        haystack.append(&content, false);
+                    }
+                    haystack
+                },
+                (false, Some(content)) => content,
+                (false, None) => return Ok(()),
+            };
+
+            let path = match printer {
+                Printer::Standard(_) if atty::is(atty::Stream::Stdout) =>
+                    path_color(&store, secret)?,
+                _ => secret.name.clone(),
+            };
+            printer.search(
+                &mut searcher,
                 &matcher_regex,
-                plaintext.unsecure_ref(),
-                printer.sink_with_path(&matcher_regex, &path_color),
-            )?;
+                &path,
+                haystack.unsecure_ref(),
+            )
+        });
+
+        // Stop quietly if output is closed early, such as when piped into head
+        match result {
+            Err(err) if is_broken_pipe(&err) => {},
+            result => result?,
         }
 
         // Finalize tomb
@@ -106,6 +115,232 @@ impl<'a> Grep<'a> {
     }
 }
 
+/// Decrypt the given secrets on a pool of workers, and search them.
+///
+/// Each worker uses its own crypto contexts. A single worker is used if
+/// passphrases may be prompted for on the TTY, to not mix up prompts. The
+/// `search` function is called on this thread for each secret in order, with
+/// its content to search. The content is `None` if the secret could not be
+/// decrypted and errors are skipped, other errors stop the search.
+fn search_parallel<F>(
+    secrets: &[Secret],
+    matcher_main: &MainMatcher,
+    matcher_grep: &GrepMatcher,
+    mut search: F,
+) -> Result<()>
+where
+    F: FnMut(&Secret, Option<Plaintext>) -> Result<()>,
+{
+    let workers = if matcher_main.gpg_tty() {
+        1
+    } else {
+        matcher_grep
+            .jobs()
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+    }
+    .clamp(1, secrets.len().max(1));
+    let field = matcher_grep.field();
+
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (tx, rx) = mpsc::sync_channel(workers);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let (tx, next, stop) = (tx.clone(), &next, &stop);
+            scope.spawn(move || {
+                let mut contexts = ContextPool::empty();
+                while !stop.load(Ordering::Relaxed) {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let secret = match secrets.get(i) {
+                        Some(secret) => secret,
+                        None => break,
+                    };
+                    let content = decrypt(&mut contexts, matcher_main, secret, field);
+                    if tx.send((i, content)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        // Search results in order, stop workers on error
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        for (i, content) in rx {
+            pending.insert(i, content);
+            while let Some(content) = pending.remove(&expected) {
+                let secret = &secrets[expected];
+                expected += 1;
+
+                let content = match content {
+                    Ok(content) => Some(content),
+                    Err(err) if matcher_grep.skip_errors() => {
+                        if !matcher_main.quiet() {
+                            error::print_error(&err.context(format!(
+                                "failed to read secret, skipping: {}",
+                                secret.name
+                            )));
+                        }
+                        None
+                    },
+                    Err(err) => {
+                        stop.store(true, Ordering::Relaxed);
+                        return Err(Err::Read(secret.name.clone(), err).into());
+                    },
+                };
+                if let Err(err) = search(secret, content) {
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Decrypt a secret, and select the value of the given field to search.
+fn decrypt(
+    contexts: &mut ContextPool,
+    matcher_main: &MainMatcher,
+    secret: &Secret,
+    field: Option<&str>,
+) -> Result<Plaintext> {
+    let plaintext = crate::crypto::decrypt_file(contexts, matcher_main, secret)?;
+    let field = match field {
+        Some(field) => field,
+        None => return Ok(plaintext),
+    };
+
+    // Search password as field, and all values of fields with the given key
+    let body = SecretBody::parse(&plaintext).map_err(Err::Parse)?;
+    let values: Vec<&str> = field
+        .trim()
+        .eq_ignore_ascii_case("password")
+        .then(|| body.password())
+        .into_iter()
+        .chain(body.get_all(field))
+        .collect();
+
+    // Allocate once, growing would leave unzeroed copies behind
+    let mut data = Vec::with_capacity(values.iter().map(|value| value.len() + 1).sum());
+    for value in values {
+        data.extend_from_slice(value.as_bytes());
+        data.push(b'\n');
+    }
+    Ok(Plaintext::from(data))
+}
+
+/// Check whether the given error is caused by writing to a closed pipe.
+fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+}
+
+/// Format the path of a secret for the standard printer.
+fn path_color(store: &Store, secret: &Secret) -> Result<String> {
+    let path_str = secret.path.display().to_string();
+
+    let sep = path_str.rfind('/').ok_or(Err::NoSubfolder)? + 1;
+    let subfolder = &path_str[store.root.display().to_string().len() + 1..sep];
+    let file = secret.name.rsplit('/').next().unwrap_or(&secret.name);
+
+    Ok(format!(
+        "{}{}",
+        subfolder.bold().underline(),
+        file.color(Color::TrueColor {
+            r: 160,
+            g: 100,
+            b: 105,
+        })
+        .underline(),
+    ))
+}
+
+/// Printer for search results.
+enum Printer {
+    /// Print matching lines.
+    Standard(Standard<StandardStreamKind>),
+
+    /// Print secret names with matches, or match counts.
+    Summary(Summary<StandardStreamKind>),
+
+    /// Print matches as JSON lines.
+    Json(JSON<io::Stdout>),
+}
+
+impl Printer {
+    /// Construct the printer selected by the user.
+    ///
+    /// Columns of matches are only shown if `column` is set.
+    fn new(matcher_grep: &GrepMatcher, column: bool) -> Result<Self> {
+        let color_specs = ColorSpecs::new(&[
+            "path:fg:magenta".parse()?,
+            "line:fg:green".parse()?,
+            "column:fg:yellow".parse()?,
+            "match:fg:red".parse()?,
+            "match:style:bold".parse()?,
+        ]);
+
+        if matcher_grep.json() {
+            return Ok(Self::Json(JSONBuilder::new().build(io::stdout())));
+        }
+
+        let kind = if matcher_grep.files_with_matches() {
+            SummaryKind::PathWithMatch
+        } else if matcher_grep.count() {
+            SummaryKind::Count
+        } else {
+            return Ok(Self::Standard(
+                StandardBuilder::new()
+                    .color_specs(color_specs)
+                    .column(column)
+                    .heading(true)
+                    .build(stdout()),
+            ));
+        };
+        Ok(Self::Summary(
+            SummaryBuilder::new()
+                .kind(kind)
+                .color_specs(color_specs)
+                .build(stdout()),
+        ))
+    }
+
+    /// Search the given haystack, and print results for the given path.
+    fn search(
+        &mut self,
+        searcher: &mut Searcher,
+        matcher: &RegexMatcher,
+        path: &str,
+        haystack: &[u8],
+    ) -> Result<()> {
+        match self {
+            Self::Standard(printer) => {
+                searcher.search_slice(matcher, haystack, printer.sink_with_path(matcher, path))?;
+            },
+            Self::Summary(printer) => {
+                searcher.search_slice(matcher, haystack, printer.sink_with_path(matcher, path))?;
+            },
+            Self::Json(printer) => {
+                searcher.search_slice(matcher, haystack, printer.sink_with_path(matcher, path))?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Get a stream to stdout, colored and line buffered if it is a TTY.
+fn stdout() -> StandardStreamKind {
+    if atty::is(atty::Stream::Stdout) {
+        StandardStreamKind::LineBuffered(StandardStream::stdout(ColorChoice::Auto))
+    } else {
+        StandardStreamKind::BlockBuffered(BufferedStandardStream::stdout(ColorChoice::Never))
+    }
+}
+
 // Taken from `grep_cli` to implement only the necessary requirements for
 // `grep_searcher` instead of having to depend on `grep_cli` for this simple
 // feature
@@ -167,8 +402,11 @@ pub(crate) enum Err {
     #[error("failed to prepare password store tomb for usage")]
     Tomb(#[source] anyhow::Error),
 
-    #[error("failed to read secret")]
-    Read(#[source] anyhow::Error),
+    #[error("failed to read secret: {0}")]
+    Read(String, #[source] anyhow::Error),
+
+    #[error("failed to parse secret body")]
+    Parse(#[source] anyhow::Error),
 
     #[error("failed no subfolder")]
     NoSubfolder,
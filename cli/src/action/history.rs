@@ -0,0 +1,109 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::Store;
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{history::HistoryMatcher, MainMatcher, Matcher},
+    util::{secret, select, time},
+};
+
+/// Secret history action.
+pub(crate) struct History<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> History<'a> {
+    /// Construct a new history action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the history action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_history = HistoryMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_history.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        let secret = select::store_select_secret_history(&store, matcher_history.query())
+            .ok_or(Err::NoneSelected)?;
+
+        secret::print_name(
+            matcher_history.query(),
+            &secret,
+            &store,
+            matcher_main.quiet(),
+        );
+
+        let path = secret
+            .relative_path(&store.root)
+            .map_err(|err| Err::Log(err.into()))?;
+        let revisions = store.history().log(path).map_err(Err::Log)?;
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if revisions.is_empty() {
+            if !matcher_main.quiet() {
+                eprintln!("Secret has no history");
+            }
+            return Ok(());
+        }
+
+        for revision in revisions {
+            if matcher_main.quiet() {
+                println!("{}", revision.commit);
+                continue;
+            }
+
+            let note = if revision.removed {
+                " (removed)".into()
+            } else if revision.path != path {
+                format!(" (as {})", revision.name())
+            } else {
+                String::new()
+            };
+            println!(
+                "{} {} {}: {}{}",
+                revision.short_commit(),
+                time::format_elapsed(revision.time),
+                revision.author,
+                revision.summary,
+                note,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("no secret selected")]
+    NoneSelected,
+
+    #[error("failed to list secret history")]
+    Log(#[source] anyhow::Error),
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::ArgMatches;
+use thiserror::Error;
+
+use prs_lib::{
+    crypto::{self, prelude::*, Context, ContextPool, Key, Proto, WILDCARD_KEY_ID},
+    Recipients, Secret, Store,
+};
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{
+        housekeeping::{audit::AuditMatcher, HousekeepingMatcher},
+        MainMatcher, Matcher,
+    },
+    util::{self, error, style},
+};
+
+/// A housekeeping audit action.
+pub(crate) struct Audit<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Audit<'a> {
+    /// Construct a new audit action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the audit action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_housekeeping = HousekeepingMatcher::with(self.cmd_matches).unwrap();
+        let matcher_audit = AuditMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_housekeeping.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Import new keys, required to resolve key IDs of recipients
+        crypto::store::import_missing_keys_from_store(&store).map_err(Err::ImportRecipients)?;
+
+        let secrets = store.secrets(matcher_audit.query());
+        let (drifted, skipped) = audit(&store, &secrets, &matcher_main)?;
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if skipped > 0 && !matcher_main.quiet() {
+            eprintln!(
+                "Skipped {} secrets, their protocol does not expose recipients",
+                skipped
+            );
+        }
+
+        if drifted == 0 {
+            if !matcher_main.quiet() {
+                eprintln!("No recipient drift in {} secrets", secrets.len() - skipped);
+            }
+            return Ok(());
+        }
+
+        eprintln!();
+        error::print_error_msg(format!(
+            "Recipient drift in {} of {} secrets",
+            drifted,
+            secrets.len() - skipped,
+        ));
+        if !matcher_main.quiet() {
+            eprintln!(
+                "Use '{}' to re-encrypt for current recipients",
+                style::highlight(&format!("{} housekeeping recrypt --all", util::bin_name()))
+            );
+        }
+        error::exit(1);
+    }
+}
+
+/// Audit recipients of all given secrets, report recipient drift.
+///
+/// Compares the key IDs each secret is encrypted for with the recipients of
+/// its nearest recipients directory. Reports recipients a secret is not
+/// encrypted for, and keys a secret is encrypted for that are not a recipient.
+///
+/// Returns the number of secrets with drift, and the number of secrets that
+/// were skipped because their protocol does not expose recipients.
+fn audit(store: &Store, secrets: &[Secret], matcher_main: &MainMatcher) -> Result<(usize, usize)> {
+    let mut contexts = ContextPool::empty();
+    let mut recipients_cache: HashMap<(PathBuf, Proto), RecipientKeyIds> = HashMap::new();
+    let (mut drifted, mut skipped) = (0, 0);
+
+    for secret in secrets {
+        let proto = secret.proto();
+        let context = crate::crypto::pool_context(&mut contexts, matcher_main, proto)?;
+        let key_ids = match context
+            .recipient_key_ids_file(&secret.path)
+            .map_err(Err::Read)?
+        {
+            Some(key_ids) => key_ids,
+            None => {
+                if matcher_main.verbose() {
+                    eprintln!("Skipped: {}", secret.name);
+                }
+                skipped += 1;
+                continue;
+            }
+        };
+
+        if matcher_main.verbose() {
+            println!("{}: {}", secret.name, key_ids.join(", "));
+        }
+
+        // Find recipients with their key IDs, load once for each directory and protocol
+        let target = (store.recipients_dir(&secret.path), proto);
+        if !recipients_cache.contains_key(&target) {
+            let recipients = store.recipients_for(&secret.path).map_err(Err::Store)?;
+            recipients_cache.insert(target.clone(), recipient_key_ids(context, &recipients)?);
+        }
+
+        let drift = Drift::find(&key_ids, &recipients_cache[&target]);
+        if drift.is_empty() {
+            continue;
+        }
+
+        drifted += 1;
+        if matcher_main.quiet() {
+            println!("{}", secret.name);
+            continue;
+        }
+        println!("{}:", secret.name);
+        for key in drift.missing {
+            println!("  missing recipient: {}", key);
+        }
+        for id in drift.unknown {
+            println!("  not a recipient: {}", id);
+        }
+        for _ in 0..drift.hidden {
+            println!("  hidden recipient: key ID not disclosed, may be a missing recipient");
+        }
+    }
+
+    Ok((drifted, skipped))
+}
+
+/// Recipient keys, with the key IDs ciphertext for them refers to.
+pub(crate) type RecipientKeyIds = Vec<(Key, Vec<String>)>;
+
+/// Get the key IDs for all recipient keys.
+pub(crate) fn recipient_key_ids(
+    context: &mut Context,
+    recipients: &Recipients,
+) -> Result<RecipientKeyIds> {
+    recipients
+        .keys()
+        .iter()
+        .map(|key| Ok((key.clone(), context.key_ids(key).map_err(Err::KeyIds)?)))
+        .collect()
+}
+
+/// Recipient drift of a secret.
+pub(crate) struct Drift<'a> {
+    /// Recipients the secret is not encrypted for.
+    pub missing: Vec<&'a Key>,
+
+    /// Key IDs the secret is encrypted for that are not a recipient.
+    pub unknown: Vec<&'a String>,
+
+    /// Number of hidden recipients the secret is encrypted for, which can't be
+    /// matched against recipients.
+    pub hidden: usize,
+}
+
+impl<'a> Drift<'a> {
+    /// Find drift between key IDs a secret is encrypted for and its recipients.
+    pub(crate) fn find(key_ids: &'a [String], recipients: &'a RecipientKeyIds) -> Self {
+        Self {
+            missing: recipients
+                .iter()
+                .filter(|(_, ids)| !ids.iter().any(|id| key_ids.contains(id)))
+                .map(|(key, _)| key)
+                .collect(),
+            unknown: key_ids
+                .iter()
+                .filter(|id| *id != WILDCARD_KEY_ID)
+                .filter(|id| !recipients.iter().any(|(_, ids)| ids.contains(id)))
+                .collect(),
+            hidden:  key_ids.iter().filter(|id| *id == WILDCARD_KEY_ID).count(),
+        }
+    }
+
+    /// Whether there is no drift.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unknown.is_empty() && self.hidden == 0
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to read secret recipients")]
+    Read(#[source] anyhow::Error),
+
+    #[error("failed to obtain key IDs of recipient")]
+    KeyIds(#[source] anyhow::Error),
+
+    #[error("failed to import store recipients")]
+    ImportRecipients(#[source] anyhow::Error),
+}
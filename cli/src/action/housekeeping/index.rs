@@ -0,0 +1,95 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::{index, Store};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{
+        housekeeping::{index::IndexMatcher, HousekeepingMatcher},
+        MainMatcher, Matcher,
+    },
+    util::{self, sync},
+};
+
+/// A housekeeping index action.
+pub(crate) struct Index<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Index<'a> {
+    /// Construct a new index action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the index action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_housekeeping = HousekeepingMatcher::with(self.cmd_matches).unwrap();
+        let matcher_index = IndexMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_housekeeping.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        if matcher_index.remove() {
+            index::Index::remove(&store).map_err(Err::Index)?;
+        } else {
+            // Never sync the index, it may hold secrets of other recipients
+            let sync = store.sync();
+            if sync.is_init() {
+                sync::ensure_ready(&sync, false);
+                sync.prepare()?;
+                super::run::set_git_ignore(&store).map_err(Err::GitIgnore)?;
+                sync.finalize("Ignore search index")?;
+            }
+
+            let index = util::index::rebuild(&store, &matcher_main).map_err(Err::Index)?;
+            if !matcher_main.quiet() {
+                let unreadable = index.entries().filter(|(_, e)| e.unreadable).count();
+                eprintln!(
+                    "Indexed {} secrets, {} could not be read",
+                    index.len() - unreadable,
+                    unreadable,
+                );
+            }
+        }
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
+
+        if matcher_index.remove() && !matcher_main.quiet() {
+            eprintln!("Search index removed");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to set default .gitignore")]
+    GitIgnore(#[source] std::io::Error),
+
+    #[error("failed to build search index")]
+    Index(#[source] anyhow::Error),
+}
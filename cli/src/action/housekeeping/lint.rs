@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use clap::ArgMatches;
+use thiserror::Error;
+
+use prs_lib::{
+    crypto::ContextPool,
+    template::{Template, TEMPLATE_PROPERTY},
+    Secret, SecretBody, Store,
+};
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{
+        housekeeping::{lint::LintMatcher, HousekeepingMatcher},
+        MainMatcher, Matcher,
+    },
+    util::error,
+};
+
+/// A housekeeping lint action.
+pub(crate) struct Lint<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Lint<'a> {
+    /// Construct a new lint action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the lint action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_housekeeping = HousekeepingMatcher::with(self.cmd_matches).unwrap();
+        let matcher_lint = LintMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_housekeeping.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        let secrets = store.secrets(matcher_lint.query());
+        let (failed, linted) = lint(&store, &secrets, &matcher_main)?;
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if failed == 0 {
+            if !matcher_main.quiet() {
+                eprintln!(
+                    "No missing template fields in {} secrets, {} without template",
+                    linted,
+                    secrets.len() - linted,
+                );
+            }
+            return Ok(());
+        }
+
+        eprintln!();
+        error::print_error_msg(format!(
+            "Problems found in {} of {} secrets",
+            failed, linted,
+        ));
+        error::exit(1);
+    }
+}
+
+/// Lint all given secrets against the template they declare.
+///
+/// Reports required template fields that are missing or empty, templates that
+/// cannot be loaded, and secrets that cannot be decrypted. Secrets without
+/// template are skipped.
+///
+/// Returns the number of secrets with problems, and the number of secrets that
+/// declare a template or cannot be read.
+fn lint(store: &Store, secrets: &[Secret], matcher_main: &MainMatcher) -> Result<(usize, usize)> {
+    let mut contexts = ContextPool::empty();
+    let mut templates: HashMap<String, Result<Template, String>> = HashMap::new();
+    let (mut failed, mut linted) = (0, 0);
+
+    for secret in secrets {
+        // Report unreadable secrets, we cannot tell whether they declare a template
+        let plaintext = match crate::crypto::decrypt_file(&mut contexts, matcher_main, secret) {
+            Ok(plaintext) => plaintext,
+            Err(err) => {
+                linted += 1;
+                failed += 1;
+                report(secret, &[format!("unreadable: {err:#}")], matcher_main);
+                continue;
+            },
+        };
+        let name = SecretBody::parse(&plaintext)
+            .ok()
+            .and_then(|body| Some((body.property(TEMPLATE_PROPERTY)?, body)));
+        let (name, body) = match name {
+            Some((name, body)) if !name.is_empty() => (name, body),
+            _ => {
+                if matcher_main.verbose() {
+                    eprintln!("Skipped: {}", secret.name);
+                }
+                continue;
+            },
+        };
+        linted += 1;
+
+        // Load each template once
+        let name = name.unsecure_to_str()?.trim().to_owned();
+        let template = templates
+            .entry(name.clone())
+            .or_insert_with(|| Template::load(store, &name).map_err(|err| format!("{err:#}")));
+
+        let problems: Vec<String> = match template {
+            Ok(template) => template
+                .missing(&body)
+                .into_iter()
+                .map(|key| format!("missing field: {key}"))
+                .collect(),
+            Err(err) => vec![err.clone()],
+        };
+        if problems.is_empty() {
+            continue;
+        }
+
+        failed += 1;
+        report(secret, &problems, matcher_main);
+    }
+
+    Ok((failed, linted))
+}
+
+/// Print the problems found in the given secret.
+fn report(secret: &Secret, problems: &[String], matcher_main: &MainMatcher) {
+    if matcher_main.quiet() {
+        println!("{}", secret.name);
+        return;
+    }
+    println!("{}:", secret.name);
+    for problem in problems {
+        println!("  {problem}");
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+}
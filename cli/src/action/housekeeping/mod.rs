@@ -1,3 +1,6 @@
+pub(crate) mod audit;
+pub(crate) mod index;
+pub(crate) mod lint;
 pub(crate) mod recrypt;
 pub(crate) mod run;
 pub(crate) mod sync_keys;
@@ -23,6 +26,18 @@ impl<'a> Housekeeping<'a> {
         // Create the command matcher
         let matcher_housekeeping = HousekeepingMatcher::with(self.cmd_matches).unwrap();
 
+        if matcher_housekeeping.audit().is_some() {
+            return audit::Audit::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_housekeeping.index().is_some() {
+            return index::Index::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_housekeeping.lint().is_some() {
+            return lint::Lint::new(self.cmd_matches).invoke();
+        }
+
         if matcher_housekeeping.recrypt().is_some() {
             return recrypt::Recrypt::new(self.cmd_matches).invoke();
         }
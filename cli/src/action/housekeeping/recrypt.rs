@@ -1,12 +1,24 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc,
+};
+use std::thread;
+
 use anyhow::Result;
 use clap::ArgMatches;
 use thiserror::Error;
 
 use prs_lib::{
-    crypto::{self, prelude::*, Context},
-    Recipients, Secret, Store,
+    crypto::{self, prelude::*, Context, ContextPool, Proto},
+    transaction::Transaction,
+    Ciphertext, Recipients, Secret, Store,
 };
 
+use super::audit::{self, RecipientKeyIds};
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 use crate::util::tomb;
 use crate::{
@@ -14,7 +26,7 @@ use crate::{
         housekeeping::{recrypt::RecryptMatcher, HousekeepingMatcher},
         MainMatcher, Matcher,
     },
-    util::{self, error, style, sync},
+    util::{error, sync},
 };
 
 /// A housekeeping recrypt action.
@@ -59,13 +71,19 @@ impl<'a> Recrypt<'a> {
 
         let secrets = store.secrets(matcher_recrypt.query());
 
-        recrypt(&store, &secrets, &matcher_main)?;
+        let mut tx = store.transaction();
+        recrypt(&mut tx, &secrets, &matcher_main, matcher_recrypt.jobs())?;
 
-        // Finalize sync
-        if !matcher_recrypt.no_sync() {
-            sync.finalize("Re-encrypt secrets")?;
+        // Commit changes, finalize sync
+        if matcher_recrypt.no_sync() {
+            tx.commit()?;
+        } else {
+            tx.commit_sync(&sync, "Re-encrypt secrets")?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -74,81 +92,315 @@ impl<'a> Recrypt<'a> {
     }
 }
 
-/// Re-encrypt all secrets in the given store.
-pub(crate) fn recrypt_all(store: &Store, matcher_main: &MainMatcher) -> Result<()> {
-    recrypt(store, &store.secrets(None), matcher_main)
+/// Re-encrypt all secrets in the store of the given transaction.
+pub(crate) fn recrypt_all(tx: &mut Transaction, matcher_main: &MainMatcher) -> Result<()> {
+    let secrets = tx.store().secrets(None);
+    recrypt(tx, &secrets, matcher_main, None)
 }
 
-/// Re-encrypt all given secrets.
-pub(crate) fn recrypt(store: &Store, secrets: &[Secret], matcher_main: &MainMatcher) -> Result<()> {
-    let mut context = crate::crypto::context(matcher_main)?;
-    let recipients = store.recipients().map_err(Err::Store)?;
-    let len = secrets.len();
+/// Re-encrypt all secrets using the recipients of the given store directory.
+///
+/// Secrets in subdirectories having their own recipients are skipped.
+pub(crate) fn recrypt_dir(
+    tx: &mut Transaction,
+    dir: &Path,
+    matcher_main: &MainMatcher,
+) -> Result<()> {
+    let store = tx.store();
+    let secrets: Vec<Secret> = store
+        .secret_iter()
+        .filter(|secret| secret.path.starts_with(dir) && store.recipients_dir(&secret.path) == dir)
+        .collect();
+    recrypt(tx, &secrets, matcher_main, None)
+}
 
-    let mut failed = Vec::new();
+/// A secret to re-encrypt.
+struct Job<'a> {
+    /// The secret.
+    secret: &'a Secret,
+
+    /// Protocol to decrypt with, and protocol to encrypt with.
+    protos: (Proto, Proto),
+
+    /// Recipients to encrypt for.
+    recipients: &'a Recipients,
+}
+
+/// A re-encrypted secret, to write as part of the transaction.
+struct Recrypted {
+    /// The new ciphertext.
+    ciphertext: Ciphertext,
+
+    /// Path to write the ciphertext to.
+    path: PathBuf,
+
+    /// Old path to remove, if the secret is migrated to another protocol.
+    remove: Option<PathBuf>,
+}
+
+/// Re-encrypt all given secrets.
+///
+/// Each secret is encrypted for the recipients of its nearest recipients
+/// directory, using the protocol it is currently encrypted with. If that
+/// directory has no recipients for that protocol, the secret is migrated to
+/// the protocol new secrets in that directory are encrypted with.
+///
+/// Secrets already encrypted for exactly their recipients are skipped, unless
+/// forced. The remaining secrets are re-encrypted in parallel on `jobs`
+/// workers, which defaults to the available parallelism.
+///
+/// Re-encrypted secrets are written as part of the given transaction. If any
+/// secret fails, the transaction is rolled back and the process exits.
+pub(crate) fn recrypt(
+    tx: &mut Transaction,
+    secrets: &[Secret],
+    matcher_main: &MainMatcher,
+    jobs: Option<usize>,
+) -> Result<()> {
+    let store = tx.store();
+    let mut contexts = ContextPool::empty();
+    let mut recipients_cache: HashMap<(PathBuf, Proto), Recipients> = HashMap::new();
+    let mut key_ids_cache: HashMap<(PathBuf, Proto), Option<RecipientKeyIds>> = HashMap::new();
 
-    for (i, secret) in secrets.iter().enumerate() {
-        if matcher_main.verbose() {
-            eprintln!("[{}/{}] Re-encrypting: {}", i + 1, len, secret.name);
+    // Find recipients for each secret, load once for each directory and protocol
+    let mut targets = Vec::with_capacity(secrets.len());
+    for secret in secrets {
+        let dir = store.recipients_dir(&secret.path);
+        let proto = secret.proto();
+        if !recipients_cache.contains_key(&(dir.clone(), proto)) {
+            let recipients = store.recipients_for(&secret.path).map_err(Err::Store)?;
+            recipients_cache.insert((dir.clone(), proto), recipients);
         }
+        let mut target = (dir.clone(), proto);
+        if recipients_cache[&target].keys().is_empty() {
+            target.1 = store.proto_for(&dir);
+            if !recipients_cache.contains_key(&target) {
+                let recipients = store.recipients_for(&dir).map_err(Err::Store)?;
+                recipients_cache.insert(target.clone(), recipients);
+            }
+        }
+        targets.push((secret, proto, target));
+    }
 
-        // Recrypt secret, show status, remember errors
-        match recrypt_single(&mut context, secret, &recipients) {
-            Ok(_) => {
-                if !matcher_main.quiet() {
-                    eprintln!("[{}/{}] Re-encrypted: {}", i + 1, len, secret.name);
+    // Skip secrets already encrypted for their recipients
+    let mut work = Vec::with_capacity(targets.len());
+    let mut paths = HashSet::new();
+    let mut skipped = 0;
+    for (secret, proto, target) in targets {
+        if !matcher_main.force() && proto == target.1 {
+            let context = crate::crypto::pool_context(&mut contexts, matcher_main, proto)?;
+            let recipients = key_ids_cache.entry(target.clone()).or_insert_with(|| {
+                audit::recipient_key_ids(context, &recipients_cache[&target]).ok()
+            });
+            if is_recrypted(context, secret, recipients.as_ref()) {
+                if matcher_main.verbose() {
+                    eprintln!(
+                        "Skipping, already encrypted for recipients: {}",
+                        secret.name
+                    );
                 }
+                skipped += 1;
+                continue;
             }
-            Err(err) => {
-                eprintln!("[{}/{}] Re-encrypting failed: {}", i + 1, len, secret.name);
-                error::print_error(&err.context("recrypting failed"));
-                failed.push(secret);
+        }
+
+        // Aliases and the secret they point to are re-encrypted once
+        if proto == target.1 {
+            if let Ok(path) = fs::canonicalize(&secret.path) {
+                if !paths.insert(path) {
+                    continue;
+                }
             }
         }
+
+        work.push(Job {
+            secret,
+            protos: (proto, target.1),
+            recipients: &recipients_cache[&target],
+        });
+    }
+
+    if skipped > 0 && !matcher_main.quiet() {
+        eprintln!(
+            "Skipped {} of {} secrets, already encrypted for their recipients",
+            skipped,
+            secrets.len()
+        );
     }
 
-    // Show recrypt failures
+    let failed = recrypt_parallel(tx, &work, matcher_main, jobs);
+
+    // Show recrypt failures, undo all changes
     if !failed.is_empty() {
-        let all = failed.len() >= secrets.len();
+        if let Err(err) = tx.rollback() {
+            error::print_error(&err.context("failed to undo changes"));
+        }
 
         eprintln!();
         error::print_error_msg(format!(
-            "Failed to re-encrypt {} of {} secrets",
+            "Failed to re-encrypt {} of {} secrets, no changes are made",
             failed.len(),
-            secrets.len()
+            work.len()
         ));
+        error::exit(1);
+    }
 
-        if !matcher_main.quiet() {
-            eprintln!(
-                "Use '{}' to try again",
-                style::highlight(&format!(
-                    "{} housekeeping recrypt{}",
-                    util::bin_name(),
-                    &if all {
-                        " --all".into()
-                    } else if failed.len() == 1 {
-                        format!(" {}", &failed[0].name)
-                    } else {
-                        "".into()
+    Ok(())
+}
+
+/// Check whether a secret is encrypted for exactly the given recipients.
+///
+/// Returns `false` if this cannot be determined.
+fn is_recrypted(
+    context: &mut Context,
+    secret: &Secret,
+    recipients: Option<&RecipientKeyIds>,
+) -> bool {
+    let recipients = match recipients {
+        Some(recipients) => recipients,
+        None => return false,
+    };
+    match context.recipient_key_ids_file(&secret.path) {
+        Ok(Some(key_ids)) => audit::Drift::find(&key_ids, recipients).is_empty(),
+        Ok(None) | Err(_) => false,
+    }
+}
+
+/// Re-encrypt the given secrets in parallel, report progress.
+///
+/// Each worker uses its own crypto contexts. A single worker is used if
+/// passphrases may be prompted for on the TTY, to not mix up prompts. Results
+/// are written to the transaction on this thread as they come in.
+///
+/// Returns the secrets that failed to re-encrypt.
+fn recrypt_parallel<'a>(
+    transaction: &mut Transaction,
+    work: &[Job<'a>],
+    matcher_main: &MainMatcher,
+    jobs: Option<usize>,
+) -> Vec<&'a Secret> {
+    let len = work.len();
+    let workers = if matcher_main.gpg_tty() {
+        1
+    } else {
+        jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+    }
+    .clamp(1, len.max(1));
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    let mut failed = Vec::new();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let (tx, next) = (tx.clone(), &next);
+            scope.spawn(move || {
+                let mut contexts = ContextPool::empty();
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let job = match work.get(i) {
+                        Some(job) => job,
+                        None => break,
+                    };
+                    let result = recrypt_single(
+                        &mut contexts,
+                        matcher_main,
+                        job.secret,
+                        job.protos,
+                        job.recipients,
+                    );
+                    if tx.send((job.secret, result)).is_err() {
+                        break;
                     }
-                ))
-            );
+                }
+            });
         }
+        drop(tx);
 
-        error::exit(1);
-    }
+        // Write recrypted secrets, show status, remember errors
+        for (i, (secret, result)) in rx.iter().enumerate() {
+            match result.and_then(|recrypted| write(transaction, recrypted)) {
+                Ok(()) => {
+                    if !matcher_main.quiet() {
+                        eprintln!("[{}/{}] Re-encrypted: {}", i + 1, len, secret.name);
+                    }
+                }
+                Err(err) => {
+                    eprintln!("[{}/{}] Re-encrypting failed: {}", i + 1, len, secret.name);
+                    error::print_error(&err.context("recrypting failed"));
+                    failed.push(secret);
+                }
+            }
+        }
+    });
 
-    Ok(())
+    failed
 }
 
 /// Recrypt a single secret.
-fn recrypt_single(context: &mut Context, secret: &Secret, recipients: &Recipients) -> Result<()> {
+///
+/// The secret is decrypted with the first and encrypted with the second
+/// protocol. If the protocol changes, the secret is moved to a path with the
+/// suffix of the new protocol.
+///
+/// This does not write anything, see [`write`].
+fn recrypt_single(
+    contexts: &mut ContextPool,
+    matcher_main: &MainMatcher,
+    secret: &Secret,
+    (from, to): (Proto, Proto),
+    recipients: &Recipients,
+) -> Result<Recrypted> {
     let path = &secret.path;
-    let plaintext = context.decrypt_file(path).map_err(Err::Read)?;
-    context
-        .encrypt_file(recipients, plaintext, path)
-        .map_err(Err::Write)?;
+    let plaintext = crate::crypto::pool_context(contexts, matcher_main, from)?
+        .decrypt_file(path)
+        .map_err(Err::Read)?;
 
+    let ciphertext = crate::crypto::pool_context(contexts, matcher_main, to)?
+        .encrypt(recipients, plaintext)
+        .map_err(Err::Encrypt)?;
+    if from == to {
+        // Resolve aliases, to replace the secret they point to
+        let path = fs::canonicalize(path).map_err(|err| Err::Read(err.into()))?;
+        return Ok(Recrypted {
+            ciphertext,
+            path,
+            remove: None,
+        });
+    }
+
+    // Migrate secret to new protocol, aliases would break
+    if path
+        .symlink_metadata()
+        .map_err(|err| Err::Read(err.into()))?
+        .file_type()
+        .is_symlink()
+    {
+        return Err(Err::MigrateAlias.into());
+    }
+    let mut new_path = path.as_os_str().to_owned();
+    if let Some(stripped) = path
+        .to_str()
+        .and_then(|path| path.strip_suffix(from.secret_suffix()))
+    {
+        new_path = stripped.into();
+    }
+    new_path.push(to.secret_suffix());
+
+    Ok(Recrypted {
+        ciphertext,
+        path: new_path.into(),
+        remove: Some(path.clone()),
+    })
+}
+
+/// Write a re-encrypted secret as part of the given transaction.
+fn write(tx: &mut Transaction, recrypted: Recrypted) -> Result<()> {
+    tx.write_ciphertext(&recrypted.path, &recrypted.ciphertext)
+        .map_err(Err::Write)?;
+    if let Some(path) = recrypted.remove {
+        tx.remove(&path).map_err(Err::Write)?;
+    }
     Ok(())
 }
 
@@ -164,9 +416,15 @@ pub(crate) enum Err {
     #[error("failed to read secret")]
     Read(#[source] anyhow::Error),
 
+    #[error("failed to encrypt secret")]
+    Encrypt(#[source] anyhow::Error),
+
     #[error("failed to write changed secret")]
     Write(#[source] anyhow::Error),
 
+    #[error("cannot migrate alias to different protocol, migrate the secret it points to")]
+    MigrateAlias,
+
     #[error("failed to import store recipients")]
     ImportRecipients(#[source] anyhow::Error),
 }
@@ -1,9 +1,14 @@
+use std::env;
 use std::fs::{self, OpenOptions};
 use std::io::{Read, Write};
 
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::Store;
+use prs_lib::{
+    index::{Index, INDEX_FILE},
+    transaction::BACKUP_DIR,
+    Store,
+};
 use thiserror::Error;
 
 #[cfg(all(feature = "tomb", target_os = "linux"))]
@@ -15,6 +20,12 @@ const LINE_ENDING: &str = "\n";
 #[cfg(windows)]
 const LINE_ENDING: &str = "\r\n";
 
+/// Git diff drivers for secrets, as used in the git attributes file.
+const DIFF_DRIVERS: [&str; 2] = ["gpg", "age"];
+
+/// Git merge driver for secrets, as used in the git attributes file.
+const MERGE_DRIVER: &str = "prs";
+
 use crate::{
     cmd::matcher::{
         housekeeping::{run::RunMatcher, HousekeepingMatcher},
@@ -55,6 +66,11 @@ impl<'a> Run<'a> {
 
         housekeeping(&store, matcher_run.allow_dirty(), matcher_run.no_sync())?;
 
+        // Rebuild search index
+        if Index::is_enabled(&store) {
+            crate::util::index::rebuild(&store, &matcher_main).map_err(Err::Index)?;
+        }
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -82,6 +98,7 @@ pub(crate) fn housekeeping(store: &Store, allow_dirty: bool, no_sync: bool) -> R
     if sync.is_init() {
         set_git_ignore(store).map_err(Err::GitAttributes)?;
         set_git_attributes(store).map_err(Err::GitAttributes)?;
+        set_git_drivers(store).map_err(Err::GitConfig)?;
     }
 
     // Finalize sync
@@ -112,8 +129,17 @@ fn set_store_permissions(_store: &Store) -> Result<(), std::io::Error> {
 }
 
 /// Set up the git ignore file.
-fn set_git_ignore(store: &Store) -> Result<(), std::io::Error> {
-    const ENTRIES: [&str; 6] = [".host", ".last", ".tty", ".uid", ".timer", "lost+found"];
+pub(crate) fn set_git_ignore(store: &Store) -> Result<(), std::io::Error> {
+    const ENTRIES: [&str; 8] = [
+        ".host",
+        ".last",
+        ".tty",
+        ".uid",
+        ".timer",
+        "lost+found",
+        BACKUP_DIR,
+        INDEX_FILE,
+    ];
 
     let file = store.root.join(".gitignore");
 
@@ -145,13 +171,18 @@ fn set_git_ignore(store: &Store) -> Result<(), std::io::Error> {
 
 /// Set up the git attributes file.
 fn set_git_attributes(store: &Store) -> Result<(), std::io::Error> {
-    const GPG_ENTRY: &str = "*.gpg diff=gpg";
+    const ENTRIES: [&str; 4] = [
+        "*.gpg diff=gpg",
+        "*.age diff=age",
+        "*.gpg merge=prs",
+        "*.age merge=prs",
+    ];
 
     let file = store.root.join(".gitattributes");
 
     // Create file if it doesn't exist
     if !file.is_file() {
-        fs::write(&file, GPG_ENTRY)?;
+        fs::write(&file, ENTRIES.join(LINE_ENDING))?;
         return Ok(());
     }
 
@@ -164,15 +195,48 @@ fn set_git_attributes(store: &Store) -> Result<(), std::io::Error> {
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
 
-    // Append GPG entry if it doesn't exist
-    if !contents.lines().any(|l| l.trim() == GPG_ENTRY) {
-        file.write_all(LINE_ENDING.as_bytes())?;
-        file.write_all(GPG_ENTRY.as_bytes())?;
+    // Add each entry if it doesn't exist
+    for entry in &ENTRIES {
+        if !contents.lines().any(|l| &l.trim() == entry) {
+            file.write_all(LINE_ENDING.as_bytes())?;
+            file.write_all(entry.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Set up git drivers to diff and merge decrypted secrets.
+///
+/// Configures the current prs binary as textconv program and merge driver in
+/// the local git config of the store.
+fn set_git_drivers(store: &Store) -> Result<()> {
+    let exe = shell_quote(&env::current_exe()?.to_string_lossy());
+    let sync = store.sync();
+
+    let textconv = format!("{exe} internal textconv");
+    for driver in &DIFF_DRIVERS {
+        sync.set_config(&format!("diff.{driver}.textconv"), &textconv)?;
     }
 
+    sync.set_config(
+        &format!("merge.{MERGE_DRIVER}.name"),
+        "prs encrypted secret merge",
+    )?;
+    sync.set_config(
+        &format!("merge.{MERGE_DRIVER}.driver"),
+        &format!("{exe} internal merge %O %A %B %P"),
+    )?;
+
     Ok(())
 }
 
+/// Quote the given string for use in a git config command, which is run
+/// through a shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Err {
     #[error("failed to access password store")]
@@ -187,4 +251,10 @@ pub(crate) enum Err {
 
     #[error("failed to set default .gitattributes")]
     GitAttributes(#[source] std::io::Error),
+
+    #[error("failed to configure git diff and merge drivers")]
+    GitConfig(#[source] anyhow::Error),
+
+    #[error("failed to rebuild search index")]
+    Index(#[source] anyhow::Error),
 }
@@ -38,6 +38,12 @@ impl<'a> Init<'a> {
         // Open new store
         let store = Store::open(&path).map_err(Err::Store)?;
 
+        // Mark store to use age by creating an empty recipients file
+        #[cfg(feature = "backend-age")]
+        if matcher_init.age() {
+            crypto::store::store_write_age_recipients(&store, &[]).map_err(Err::InitAge)?;
+        }
+
         // Run housekeeping
         crate::action::housekeeping::run::housekeeping(&store, true, false)
             .map_err(Err::Housekeeping)?;
@@ -45,7 +51,7 @@ impl<'a> Init<'a> {
         // Hint user to add our recipient key
         if !matcher_main.quiet() {
             let bin = util::bin_name();
-            let config = crate::crypto::config(&matcher_main);
+            let config = crate::crypto::config(&matcher_main, store.proto());
             let system_has_secret = crypto::util::has_private_key(&config).unwrap_or(true);
 
             if system_has_secret {
@@ -78,6 +84,10 @@ pub(crate) enum Err {
     #[error("failed to initialize store")]
     Init(#[source] std::io::Error),
 
+    #[cfg(feature = "backend-age")]
+    #[error("failed to initialize age recipients file")]
+    InitAge(#[source] anyhow::Error),
+
     #[error("failed to access initialized password store")]
     Store(#[source] anyhow::Error),
 
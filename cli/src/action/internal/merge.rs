@@ -0,0 +1,245 @@
+use std::{env, fs, path::Path};
+
+use anyhow::Result;
+use atty::Stream;
+use clap::ArgMatches;
+use prs_lib::{
+    crypto::{prelude::*, util, Context},
+    Plaintext, Store,
+};
+use thiserror::Error;
+
+use crate::{
+    cmd::matcher::{internal::merge::MergeMatcher, MainMatcher, Matcher},
+    util::{edit, error},
+};
+
+/// Prefixes of lines marking an unresolved merge conflict.
+const CONFLICT_MARKERS: [&str; 3] = ["<<<<<<<", "=======", ">>>>>>>"];
+
+/// A internal git merge driver action.
+///
+/// Git invokes this as merge driver configured by housekeeping, to merge
+/// changes to both sides of an encrypted secret. The merge result is written
+/// to the current secret file, the exit code reports whether the merge was
+/// clean.
+pub(crate) struct Merge<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Merge<'a> {
+    /// Construct a new merge action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the merge action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_merge = MergeMatcher::with(self.cmd_matches).unwrap();
+
+        // Git invokes merge drivers from the repository root
+        let root = env::current_dir().map_err(|err| Err::Store(err.into()))?;
+        let store = Store::open(root.to_string_lossy()).map_err(Err::Store)?;
+        let name = matcher_merge.path().unwrap_or_else(|| matcher_merge.ours());
+
+        let ours_path = Path::new(matcher_merge.ours());
+        let proto = matcher_merge
+            .path()
+            .and_then(|path| util::proto_from_suffix(Path::new(path)))
+            .unwrap_or_else(|| util::detect_proto(ours_path));
+        let mut context = crate::crypto::context(&matcher_main, proto)?;
+
+        let base = decrypt(&mut context, Path::new(matcher_merge.base()))?;
+        let theirs = decrypt(&mut context, Path::new(matcher_merge.theirs()))?;
+        let ours = decrypt(&mut context, ours_path)?;
+
+        let merged = match merge(&base, &ours, &theirs) {
+            Ok(merged) => merged,
+            Err(conflict) => match resolve(&conflict, name, &matcher_main)? {
+                Some(resolved) => resolved,
+                None => {
+                    error::print_error_msg(format!(
+                        "failed to merge secret, unresolved conflict in {name}",
+                    ));
+                    error::exit(1);
+                },
+            },
+        };
+
+        // Encrypt merged plaintext for current recipients
+        let recipients = match matcher_merge.path() {
+            Some(path) => store.recipients_for(store.root.join(path)),
+            None => store.recipients(),
+        }
+        .map_err(Err::Write)?;
+        context
+            .encrypt_file(&recipients, merged, ours_path)
+            .map_err(Err::Write)?;
+
+        if matcher_main.verbose() {
+            eprintln!("Merged secret {name}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Decrypt the given secret file.
+///
+/// An empty file is an empty plaintext, git uses it if there is no common
+/// ancestor.
+fn decrypt(context: &mut Context, path: &Path) -> Result<Plaintext> {
+    if fs::metadata(path)
+        .map_err(|err| Err::Read(err.into()))?
+        .len()
+        == 0
+    {
+        return Ok(Plaintext::empty());
+    }
+    Ok(context.decrypt_file(path).map_err(Err::Read)?)
+}
+
+/// Line-wise 3-way merge of the given plaintexts.
+///
+/// Returns the merged plaintext if clean, or the plaintext with conflict
+/// markers if not. Secrets often lack a trailing newline, so one is added
+/// before merging to keep conflict markers on their own lines.
+fn merge(base: &Plaintext, ours: &Plaintext, theirs: &Plaintext) -> Result<Plaintext, Plaintext> {
+    let with_newline = |plaintext: &Plaintext| {
+        let mut data = plaintext.unsecure_ref().to_vec();
+        if !data.is_empty() && !data.ends_with(b"\n") {
+            data.push(b'\n');
+        }
+        Plaintext::from(data)
+    };
+    let (base, theirs) = (with_newline(base), with_newline(theirs));
+    let ours_newline = ours.is_empty() || ours.unsecure_ref().ends_with(b"\n");
+    let ours = with_newline(ours);
+
+    let mut merged = diffy::merge_bytes(
+        base.unsecure_ref(),
+        ours.unsecure_ref(),
+        theirs.unsecure_ref(),
+    )
+    .map_err(Plaintext::from)?;
+
+    // Keep missing trailing newline of our secret
+    if !ours_newline && merged.ends_with(b"\n") {
+        merged.pop();
+    }
+    Ok(merged.into())
+}
+
+/// Interactively resolve a merge conflict in the editor.
+///
+/// Returns `None` if the conflict is not resolved, or if not interactive. Git
+/// does not pass our arguments to merge drivers, so this also requires a
+/// terminal.
+fn resolve(
+    conflict: &Plaintext,
+    name: &str,
+    matcher_main: &MainMatcher,
+) -> Result<Option<Plaintext>> {
+    if matcher_main.no_interact() || !atty::is(Stream::Stdin) || !atty::is(Stream::Stderr) {
+        return Ok(None);
+    }
+
+    eprintln!("Conflicting changes in secret {name}, resolve them in the editor");
+    let resolved = match edit::edit(conflict).map_err(Err::Edit)? {
+        Some(resolved) => resolved,
+        None => return Ok(None),
+    };
+
+    if has_conflict_markers(&resolved) {
+        return Ok(None);
+    }
+    Ok(Some(resolved))
+}
+
+/// Check whether the given plaintext still has merge conflict markers.
+fn has_conflict_markers(plaintext: &Plaintext) -> bool {
+    plaintext.unsecure_to_str().is_ok_and(|text| {
+        text.lines().any(|line| {
+            CONFLICT_MARKERS
+                .iter()
+                .any(|marker| line.starts_with(marker))
+        })
+    })
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[error("failed to read secret to merge")]
+    Read(#[source] anyhow::Error),
+
+    #[error("failed to edit merge conflict")]
+    Edit(#[source] anyhow::Error),
+
+    #[error("failed to write merged secret")]
+    Write(#[source] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Merge the given plaintexts.
+    fn merge_str(base: &str, ours: &str, theirs: &str) -> Result<String, String> {
+        let text = |plaintext: Plaintext| plaintext.unsecure_to_str().unwrap().to_string();
+        merge(&base.into(), &ours.into(), &theirs.into())
+            .map(text)
+            .map_err(text)
+    }
+
+    #[test]
+    fn merge_clean() {
+        let base = "password\nuser: me\nemail: me@example.com\nurl: example.com";
+        let ours = "password\nuser: you\nemail: me@example.com\nurl: example.com";
+        let theirs = "password\nuser: me\nemail: me@example.com\nurl: example.org";
+        assert_eq!(
+            merge_str(base, ours, theirs).unwrap(),
+            "password\nuser: you\nemail: me@example.com\nurl: example.org",
+        );
+
+        // Keeps trailing newline of our side
+        assert_eq!(
+            merge_str("a\nb\n", "a\nb\nc\n", "z\nb\n").unwrap(),
+            "z\nb\nc\n",
+        );
+
+        // Without common ancestor, git passes an empty base
+        assert_eq!(merge_str("", "password", "password").unwrap(), "password");
+    }
+
+    #[test]
+    fn merge_conflict() {
+        let base = "password\nuser: me";
+        let conflict = merge_str(base, "ours\nuser: me", "theirs\nuser: me").unwrap_err();
+        assert!(conflict.contains("ours"));
+        assert!(conflict.contains("theirs"));
+        assert!(conflict.ends_with("user: me\n"));
+        assert!(has_conflict_markers(&conflict.as_str().into()));
+    }
+
+    #[test]
+    fn merge_identical() {
+        let base = "password\nuser: me";
+        let changed = "changed\nuser: me";
+        assert_eq!(merge_str(base, changed, changed).unwrap(), changed);
+        assert_eq!(merge_str(base, base, base).unwrap(), base);
+        assert_eq!(merge_str(changed, base, base).unwrap(), base);
+    }
+
+    #[test]
+    fn conflict_markers() {
+        assert!(!has_conflict_markers(&"password\nuser: me".into()));
+        assert!(has_conflict_markers(&"a\n<<<<<<< ours\nb".into()));
+        assert!(has_conflict_markers(&"a\n=======\nb".into()));
+        assert!(!has_conflict_markers(&"a\n  =======\nb".into()));
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "clipboard")]
 pub(crate) mod clip_revert;
 pub(crate) mod completions;
+pub(crate) mod merge;
+pub(crate) mod textconv;
 
 use anyhow::Result;
 use clap::ArgMatches;
@@ -32,6 +34,14 @@ impl<'a> Internal<'a> {
             return completions::Completions::new(self.cmd_matches).invoke();
         }
 
+        if matcher_internal.merge().is_some() {
+            return merge::Merge::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_internal.textconv().is_some() {
+            return textconv::Textconv::new(self.cmd_matches).invoke();
+        }
+
         // Unreachable, clap will print help for missing sub command instead
         unreachable!()
     }
@@ -0,0 +1,70 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::crypto::{prelude::*, util};
+use thiserror::Error;
+
+use crate::{
+    cmd::matcher::{internal::textconv::TextconvMatcher, MainMatcher, Matcher},
+    util::error,
+};
+
+/// Placeholder shown in diffs for secrets that cannot be decrypted.
+const UNDECRYPTABLE: &str = "<prs: failed to decrypt secret>\n";
+
+/// A internal git textconv action.
+///
+/// Git invokes this as textconv program for the diff drivers configured by
+/// housekeeping, to show decrypted secrets in diffs.
+pub(crate) struct Textconv<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Textconv<'a> {
+    /// Construct a new textconv action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the textconv action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_textconv = TextconvMatcher::with(self.cmd_matches).unwrap();
+
+        let path = Path::new(matcher_textconv.file());
+        let proto = util::detect_proto(path);
+
+        // Do not fail on secrets we cannot decrypt, it would abort the whole git diff
+        let plaintext = match crate::crypto::context(&matcher_main, proto)
+            .map_err(Into::into)
+            .and_then(|mut context| context.decrypt_file(path))
+        {
+            Ok(plaintext) => plaintext,
+            Err(err) => {
+                error::print_error(&err.context(Err::Decrypt));
+                io::stdout().write_all(UNDECRYPTABLE.as_bytes())?;
+                return Ok(());
+            },
+        };
+
+        io::stdout()
+            .write_all(plaintext.unsecure_ref())
+            .map_err(Err::Write)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to decrypt secret for git diff")]
+    Decrypt,
+
+    #[error("failed to write decrypted secret to stdout")]
+    Write(#[source] io::Error),
+}
@@ -1,24 +1,34 @@
 pub(crate) mod add;
+#[cfg(all(feature = "agent", unix))]
+pub(crate) mod agent;
 #[cfg(feature = "alias")]
 pub(crate) mod alias;
+pub(crate) mod audit;
 pub(crate) mod clone;
 #[cfg(feature = "clipboard")]
 pub(crate) mod copy;
+pub(crate) mod diff;
 pub(crate) mod duplicate;
 pub(crate) mod edit;
+pub(crate) mod find;
 pub(crate) mod generate;
 pub(crate) mod git;
 #[cfg(feature = "grep")]
 pub(crate) mod grep;
+pub(crate) mod history;
 pub(crate) mod housekeeping;
 pub(crate) mod init;
 pub(crate) mod internal;
 pub(crate) mod list;
 pub(crate) mod otp;
+#[cfg(feature = "pwned")]
+pub(crate) mod pwned;
 pub(crate) mod r#move;
 pub(crate) mod recipients;
 pub(crate) mod remove;
+pub(crate) mod restore;
 pub(crate) mod show;
 pub(crate) mod sync;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) mod tomb;
+pub(crate) mod trash;
@@ -1,9 +1,10 @@
-use std::fs;
 #[cfg(feature = "alias")]
-use std::path::Path;
+use std::{fs, path::Path};
 
 use anyhow::Result;
 use clap::ArgMatches;
+#[cfg(feature = "alias")]
+use prs_lib::transaction::Transaction;
 use prs_lib::{Secret, Store};
 use thiserror::Error;
 
@@ -72,27 +73,44 @@ impl<'a> Move<'a> {
             }
         }
 
+        let mut tx = store.transaction();
+
         #[cfg(feature = "alias")]
         {
             // Update this (relative) alias to point to the same target after moving
-            update_secret_alias_target(&store, &secret, &new_secret)?;
+            update_secret_alias_target(&mut tx, &secret, &new_secret)?;
 
             // Update other aliases pointing to this, to point to new location
-            update_alias_for_secret_to(&store, &secret, &new_secret);
+            update_alias_for_secret_to(&mut tx, &secret, &new_secret)?;
         }
 
         // Move secret
-        fs::rename(&secret.path, path)
-            .map(|_| ())
-            .map_err(Err::Move)?;
+        let proto = secret.proto();
+        tx.rename(&secret.path, &path).map_err(Err::Move)?;
 
-        super::remove::remove_empty_secret_dir(&secret);
+        // Re-encrypt if moved to directory with other recipients or protocol
+        if store.recipients_dir(&secret.path) != store.recipients_dir(&path)
+            || proto != new_secret.proto()
+        {
+            super::duplicate::recrypt_for_dest(&mut tx, proto, &new_secret, &matcher_main)
+                .map_err(Err::Recrypt)?;
+        }
 
-        // Finalize sync
-        if !matcher_move.no_sync() {
-            sync.finalize(format!("Move from {} to {}", secret.name, new_secret.name))?;
+        // Commit changes, finalize sync
+        if matcher_move.no_sync() {
+            tx.commit()?;
+        } else {
+            tx.commit_sync(
+                &sync,
+                format!("Move from {} to {}", secret.name, new_secret.name),
+            )?;
         }
 
+        super::remove::remove_empty_secret_dir(&secret);
+
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -119,7 +137,7 @@ impl<'a> Move<'a> {
 /// Returns `true` if a symlink has been updated.
 #[cfg(feature = "alias")]
 fn update_secret_alias_target(
-    store: &Store,
+    tx: &mut Transaction,
     secret: &Secret,
     future_secret: &Secret,
 ) -> Result<bool> {
@@ -137,10 +155,10 @@ fn update_secret_alias_target(
         .join(target)
         .canonicalize()
         .map_err(Err::UpdateAlias)?;
-    let target = Secret::from(store, target);
+    let target = Secret::from(tx.store(), target);
 
     // Update alias to point to same target when moved
-    update_alias(store, &target, &secret.path, &future_secret.path)?;
+    update_alias(tx, &target, &secret.path, &future_secret.path)?;
 
     Ok(true)
 }
@@ -152,14 +170,16 @@ fn update_secret_alias_target(
 /// The `secret` is the old secret location, the `new_secret` is the location it is moved to.
 /// Aliases targetting `secret` will be updated to point to `new_secret`.
 #[cfg(feature = "alias")]
-fn update_alias_for_secret_to(store: &Store, secret: &Secret, new_secret: &Secret) {
-    for secret in super::remove::find_symlinks_to(store, secret) {
-        if let Err(err) = update_alias(store, new_secret, &secret.path, &secret.path) {
-            error::print_error(
-                &err.context("failed to update path of alias that points to moved secret, ignoring"),
-            );
-        }
+fn update_alias_for_secret_to(
+    tx: &mut Transaction,
+    secret: &Secret,
+    new_secret: &Secret,
+) -> Result<()> {
+    for secret in super::remove::find_symlinks_to(tx.store(), secret) {
+        update_alias(tx, new_secret, &secret.path, &secret.path)
+            .map_err(Err::UpdateAliasTo)?;
     }
+    Ok(())
 }
 
 /// Update the path of an alias.
@@ -170,19 +190,20 @@ fn update_alias_for_secret_to(store: &Store, secret: &Secret, new_secret: &Secre
 ///
 /// Panics if the given `symlink` path is not an existing symlink.
 #[cfg(feature = "alias")]
-fn update_alias(store: &Store, src: &Secret, symlink: &Path, future_symlink: &Path) -> Result<()> {
+fn update_alias(
+    tx: &mut Transaction,
+    src: &Secret,
+    symlink: &Path,
+    future_symlink: &Path,
+) -> Result<()> {
     assert!(
         symlink.symlink_metadata()?.file_type().is_symlink(),
         "failed to update symlink, not a symlink"
     );
 
-    // Remove existing file
-    fs::remove_file(symlink)
-        .map(|_| ())
-        .map_err(Err::UpdateAlias)?;
-
-    // Create new symlink
-    super::alias::create_alias(store, src, future_symlink, symlink)?;
+    // Replace existing symlink
+    let target = super::alias::secret_link_path(tx.store(), src, future_symlink)?;
+    tx.symlink(&target, symlink)?;
 
     Ok(())
 }
@@ -203,9 +224,16 @@ pub(crate) enum Err {
     NormalizePath(#[source] anyhow::Error),
 
     #[error("failed to move secret file")]
-    Move(#[source] std::io::Error),
+    Move(#[source] anyhow::Error),
+
+    #[error("failed to re-encrypt secret for recipients at destination")]
+    Recrypt(#[source] anyhow::Error),
 
     #[cfg(feature = "alias")]
     #[error("failed to update alias")]
     UpdateAlias(#[source] std::io::Error),
+
+    #[cfg(feature = "alias")]
+    #[error("failed to update path of alias that points to moved secret")]
+    UpdateAliasTo(#[source] anyhow::Error),
 }
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::{
+    crypto::ContextPool, pwned::Checker, store::SecretIterConfig, Secret, SecretBody, Store,
+};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{pwned::PwnedMatcher, MainMatcher, Matcher},
+    util::error,
+};
+
+/// Pwned passwords action.
+pub(crate) struct Pwned<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Pwned<'a> {
+    /// Construct a new pwned action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the pwned action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_pwned = PwnedMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_pwned.store()).map_err(Err::Store)?;
+        let mut checker = Checker::new(matcher_pwned.source());
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Check secrets, not aliases as they share the password of their target
+        let config = SecretIterConfig {
+            find_files:         true,
+            find_symlink_files: false,
+        };
+        let mut secrets: Vec<Secret> = store
+            .secret_iter_config(config)
+            .filter_name(matcher_pwned.query())
+            .collect();
+        secrets.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let mut contexts = ContextPool::empty();
+        let (mut pwned, mut failed) = (0, 0);
+        for secret in &secrets {
+            let body = crate::crypto::decrypt_file(&mut contexts, &matcher_main, secret)
+                .and_then(|plaintext| SecretBody::parse(&plaintext));
+            let body = match body {
+                Ok(body) => body,
+                Err(err) => {
+                    error::print_error(
+                        &err.context(format!("failed to read secret, skipping: {}", secret.name)),
+                    );
+                    failed += 1;
+                    continue;
+                },
+            };
+            if body.password().is_empty() {
+                continue;
+            }
+
+            let count = match checker.check(body.password()) {
+                Ok(count) => count,
+                Err(err) => {
+                    error::print_error(&err.context(format!(
+                        "failed to check password, skipping: {}",
+                        secret.name
+                    )));
+                    failed += 1;
+                    continue;
+                },
+            };
+            if count > 0 {
+                pwned += 1;
+                let times = if count == 1 { "time" } else { "times" };
+                println!("{}: seen {count} {times} in breaches", secret.name);
+            }
+        }
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        if !matcher_main.quiet() {
+            if pwned > 0 {
+                eprintln!("Found {pwned} pwned passwords in {} secrets", secrets.len());
+            } else {
+                eprintln!("No pwned passwords in {} secrets", secrets.len());
+            }
+        }
+
+        if pwned > 0 || failed > 0 {
+            error::exit(1);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+}
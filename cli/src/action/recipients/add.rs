@@ -48,8 +48,9 @@ impl<'a> Add<'a> {
             sync.prepare()?;
         }
 
-        let mut context = crate::crypto::context(&matcher_main)?;
-        let mut recipients = store.recipients().map_err(Err::Load)?;
+        let dir = super::recipients_dir(&store, &matcher_recipients)?;
+        let mut context = crate::crypto::context(&matcher_main, store.proto_for(&dir))?;
+        let mut recipients = store.recipients_for(&dir).map_err(Err::Load)?;
 
         // Find unused keys, select one and add to recipients
         let mut tmp = Recipients::from(
@@ -63,22 +64,32 @@ impl<'a> Add<'a> {
         tmp.remove_all(recipients.keys());
         let key = select::select_key(tmp.keys(), None).ok_or(Err::NoneSelected)?;
 
+        let mut tx = store.transaction();
         recipients.add(key.clone());
-        recipients.save(&store)?;
+        recipients.save_in_transaction(&mut tx, &dir)?;
 
         if prs_lib::store::can_decrypt(&store) {
             // Recrypt secrets
-            // TODO: do not quit on error, finish sync, ask to revert instead?
             if !matcher_add.no_recrypt() {
-                crate::action::housekeeping::recrypt::recrypt_all(&store, &matcher_main)
+                crate::action::housekeeping::recrypt::recrypt_dir(&mut tx, &dir, &matcher_main)
                     .map_err(Err::Recrypt)?;
             }
         } else if !matcher_main.quiet() {
                 cannot_decrypt_show_recrypt_hints();
         }
 
-        // Finalize sync
-        sync.finalize(format!("Add recipient {}", key.fingerprint(true)))?;
+        // Commit changes, finalize sync
+        tx.commit_sync(
+            &sync,
+            format!(
+                "Add recipient {}{}",
+                key.fingerprint(true),
+                super::recipients_dir_suffix(&store, &dir),
+            ),
+        )?;
+
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
 
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
@@ -52,7 +52,7 @@ impl<'a> Export<'a> {
             .clone();
 
         // Export public key
-        let data = crate::crypto::context(&matcher_main)?.export_key(key)?;
+        let data = crate::crypto::context(&matcher_main, store.proto())?.export_key(key)?;
 
         let mut stdout = true;
 
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Recipients, Store};
+use prs_lib::{
+    crypto::{prelude::*, Proto},
+    Recipients, Store,
+};
 use thiserror::Error;
 
 use crate::cmd::matcher::{
@@ -68,19 +71,25 @@ impl<'a> Generate<'a> {
             )
         }
 
-        // Show warning to user
-        if !matcher_main.force() {
-            eprintln!("This will start a key pair generation wizard through 'gpg'");
-            if !cli::prompt_yes("Continue?", Some(true), &matcher_main) {
-                if matcher_main.verbose() {
-                    eprintln!("Generation cancelled");
+        // Generate new key for the store protocol
+        let new = match store.proto() {
+            #[cfg(feature = "backend-age")]
+            Proto::Age => age_generate(&matcher_main)?,
+            _ => {
+                // Show warning to user
+                if !matcher_main.force() {
+                    eprintln!("This will start a key pair generation wizard through 'gpg'");
+                    if !cli::prompt_yes("Continue?", Some(true), &matcher_main) {
+                        if matcher_main.verbose() {
+                            eprintln!("Generation cancelled");
+                        }
+                        error::quit();
+                    }
                 }
-                error::quit();
-            }
-        }
 
-        // Generate new key through GPG
-        let new = gpg_generate(&matcher_main)?;
+                gpg_generate(&matcher_main)?
+            },
+        };
         let new_keys = new.keys();
 
         if !matcher_generate.no_add() {
@@ -98,33 +107,38 @@ impl<'a> Generate<'a> {
             }
 
             // Add new keys to store
+            let mut tx = store.transaction();
             let mut recipients = store.recipients().map_err(Err::Load)?;
             for key in new_keys {
                 recipients.add(key.clone());
             }
-            recipients.save(&store)?;
+            recipients.save_in_transaction(&mut tx, &store.root)?;
 
             if prs_lib::store::can_decrypt(&store) {
                 // Recrypt secrets
-                // TODO: do not quit on error, finish sync, ask to revert instead?
                 if !matcher_generate.no_recrypt() {
-                    crate::action::housekeeping::recrypt::recrypt_all(&store, &matcher_main)
+                    crate::action::housekeeping::recrypt::recrypt_all(&mut tx, &matcher_main)
                         .map_err(Err::Recrypt)?;
                 };
             } else if !matcher_main.quiet() {
                     super::add::cannot_decrypt_show_recrypt_hints();
             }
 
-            // Finalize sync
-            if !matcher_generate.no_sync() {
-                sync.finalize(format!(
-                    "Generate and add recipient {}",
-                    new_keys
-                        .iter()
-                        .map(|k| k.fingerprint(true))
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                ))?;
+            // Commit changes, finalize sync
+            if matcher_generate.no_sync() {
+                tx.commit()?;
+            } else {
+                tx.commit_sync(
+                    &sync,
+                    format!(
+                        "Generate and add recipient {}",
+                        new_keys
+                            .iter()
+                            .map(|k| k.fingerprint(true))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                )?;
             }
 
             if !matcher_main.quiet() {
@@ -134,6 +148,9 @@ impl<'a> Generate<'a> {
             }
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
@@ -147,7 +164,7 @@ impl<'a> Generate<'a> {
 /// Return new keys as recipients.
 pub(crate) fn gpg_generate(matcher_main: &MainMatcher) -> Result<Recipients> {
     // List recipients before
-    let mut context = crate::crypto::context(matcher_main)?;
+    let mut context = crate::crypto::context(matcher_main, Proto::Gpg)?;
     let before = Recipients::from(context.keys_private()?);
 
     // Generate key through GPG
@@ -170,6 +187,20 @@ pub(crate) fn gpg_generate(matcher_main: &MainMatcher) -> Result<Recipients> {
     Ok(diff)
 }
 
+/// Generate a new age identity.
+///
+/// Return new key as recipients.
+#[cfg(feature = "backend-age")]
+pub(crate) fn age_generate(matcher_main: &MainMatcher) -> Result<Recipients> {
+    let config = crate::crypto::config(matcher_main, Proto::Age);
+    let key = prs_lib::crypto::backend::rage::context::generate_identity(&config)
+        .map_err(Err::Generate)?;
+    if !matcher_main.quiet() {
+        eprintln!("Generated age identity: {}", key.fingerprint(false));
+    }
+    Ok(Recipients::from(vec![key]))
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Err {
     #[error("failed to access password store")]
@@ -185,6 +216,10 @@ pub(crate) enum Err {
     #[error("failed to invoke gpg command")]
     Invoke(#[source] std::io::Error),
 
+    #[cfg(feature = "backend-age")]
+    #[error("failed to generate age identity")]
+    Generate(#[source] anyhow::Error),
+
     #[error("failed to re-encrypt secrets in store")]
     Recrypt(#[source] anyhow::Error),
 }
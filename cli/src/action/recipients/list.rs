@@ -31,12 +31,14 @@ impl<'a> List<'a> {
             matcher_main.verbose(),
             matcher_main.force(),
         );
-        let recipients = store.recipients().map_err(Err::List)?;
 
         // Prepare tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
 
+        let dir = super::recipients_dir(&store, &matcher_recipients)?;
+        let recipients = store.recipients_for(&dir).map_err(Err::List)?;
+
         recipients
             .keys()
             .iter()
@@ -4,8 +4,12 @@ pub(crate) mod generate;
 pub(crate) mod list;
 pub(crate) mod remove;
 
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 use clap::ArgMatches;
+use prs_lib::Store;
+use thiserror::Error;
 
 use crate::cmd::matcher::{Matcher, RecipientsMatcher};
 
@@ -49,3 +53,36 @@ impl<'a> Recipients<'a> {
         unreachable!()
     }
 }
+
+/// Get the directory to manage recipients in.
+///
+/// This is the selected store subfolder, or the store root if none is selected.
+pub(crate) fn recipients_dir(store: &Store, matcher_recipients: &RecipientsMatcher) -> Result<PathBuf> {
+    let path = match matcher_recipients.path() {
+        Some(path) => path,
+        None => return Ok(store.root.clone()),
+    };
+
+    store.check_sneaky_paths(path)?;
+    let dir = store.root.join(path.trim_start_matches('/'));
+    if !dir.is_dir() {
+        return Err(Err::NoDir(dir).into());
+    }
+    Ok(dir)
+}
+
+/// Get a display suffix for the recipients directory, empty for the store root.
+pub(crate) fn recipients_dir_suffix(store: &Store, dir: &Path) -> String {
+    dir.strip_prefix(&store.root)
+        .ok()
+        .and_then(|p| p.to_str())
+        .filter(|p| !p.is_empty())
+        .map(|p| format!(" for {}", p))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to select store subfolder, not a directory: {0}")]
+    NoDir(PathBuf),
+}
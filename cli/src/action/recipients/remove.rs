@@ -48,7 +48,8 @@ impl<'a> Remove<'a> {
             sync.prepare()?;
         }
 
-        let mut recipients = store.recipients().map_err(Err::Load)?;
+        let dir = super::recipients_dir(&store, &matcher_recipients)?;
+        let mut recipients = store.recipients_for(&dir).map_err(Err::Load)?;
 
         // Select key to remove
         let key = select::select_key(recipients.keys(), None)
@@ -84,20 +85,33 @@ impl<'a> Remove<'a> {
             }
         }
 
+        let mut tx = store.transaction();
         recipients.remove(&key);
-        recipients.save(&store)?;
+        recipients.save_in_transaction(&mut tx, &dir)?;
 
         // Recrypt secrets
         if matcher_remove.recrypt() {
-            crate::action::housekeeping::recrypt::recrypt_all(&store, &matcher_main)
+            crate::action::housekeeping::recrypt::recrypt_dir(&mut tx, &dir, &matcher_main)
                 .map_err(Err::Recrypt)?;
         }
 
-        // Finalize sync
-        if !matcher_remove.no_sync() {
-            sync.finalize(format!("Remove recipient {}", key.fingerprint(true)))?;
+        // Commit changes, finalize sync
+        if matcher_remove.no_sync() {
+            tx.commit()?;
+        } else {
+            tx.commit_sync(
+                &sync,
+                format!(
+                    "Remove recipient {}{}",
+                    key.fingerprint(true),
+                    super::recipients_dir_suffix(&store, &dir),
+                ),
+            )?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
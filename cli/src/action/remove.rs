@@ -54,7 +54,8 @@ impl<'a> Remove<'a> {
         let secret =
             select::store_select_secret(&store, matcher_remove.query()).ok_or(Err::NoneSelected)?;
 
-        if !remove_confirm(&store, &secret, &matcher_main, &mut Vec::new())? {
+        let permanent = matcher_remove.permanent();
+        if !remove_confirm(&store, &secret, &matcher_main, permanent, &mut Vec::new())? {
             if matcher_main.verbose() {
                 eprintln!("Removal cancelled");
             }
@@ -66,12 +67,19 @@ impl<'a> Remove<'a> {
             sync.finalize(format!("Remove secret {}", secret.name))?;
         }
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // Finalize tomb
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
 
         if !matcher_main.quiet() {
-            eprintln!("Secret removed");
+            if permanent {
+                eprintln!("Secret removed");
+            } else {
+                eprintln!("Secret moved to trash");
+            }
         }
 
         Ok(())
@@ -82,10 +90,13 @@ impl<'a> Remove<'a> {
 ///
 /// This also asks to remove an alias target, and aliases targeting this secret, effectively asking
 /// to remove all linked aliases.
+///
+/// Secrets are moved to the store trash, unless `permanent` is set.
 fn remove_confirm(
     store: &Store,
     secret: &Secret,
     matcher_main: &MainMatcher,
+    permanent: bool,
     ignore: &mut Vec<PathBuf>,
 ) -> Result<bool> {
     // Prevent infinite loops, skip removal if already on ignore list
@@ -98,8 +109,14 @@ fn remove_confirm(
     let is_alias = fs::symlink_metadata(&secret.path)?.file_type().is_symlink();
     #[cfg(not(feature = "alias"))]
     let is_alias = false;
+    let action = if permanent {
+        "Permanently remove"
+    } else {
+        "Remove"
+    };
     let prompt = &format!(
-        "Remove {}'{}'?",
+        "{} {}'{}'?",
+        action,
         if is_alias { "alias " } else { "" },
         secret.path.display(),
     );
@@ -115,7 +132,7 @@ fn remove_confirm(
         match secret.alias_target(store) {
             Ok(secret) => {
                 // TODO: is this error okay?
-                if let Err(err) = remove_confirm(store, &secret, matcher_main, ignore) {
+                if let Err(err) = remove_confirm(store, &secret, matcher_main, permanent, ignore) {
                     error::print_error(&err.context("failed to remove alias target, ignoring"));
                 }
             }
@@ -126,15 +143,19 @@ fn remove_confirm(
     // Ask to remove aliases targeting this secret
     #[cfg(feature = "alias")]
     for secret in find_symlinks_to(store, secret) {
-        if let Err(err) = remove_confirm(store, &secret, matcher_main, ignore) {
+        if let Err(err) = remove_confirm(store, &secret, matcher_main, permanent, ignore) {
             error::print_error(&err.context("failed to remove alias, ignoring"));
         }
     }
 
-    // Remove secret, remove directories that become empty
-    fs::remove_file(&secret.path)
-        .map(|_| ())
-        .map_err(Err::Remove)?;
+    // Remove secret or move it to trash, remove directories that become empty
+    if permanent {
+        fs::remove_file(&secret.path)
+            .map(|_| ())
+            .map_err(Err::Remove)?;
+    } else {
+        store.trash().add(secret).map_err(Err::Trash)?;
+    }
     remove_empty_secret_dir(secret);
 
     Ok(true)
@@ -241,4 +262,7 @@ pub(crate) enum Err {
 
     #[error("failed to remove secret file")]
     Remove(#[source] std::io::Error),
+
+    #[error("failed to move secret to trash")]
+    Trash(#[source] anyhow::Error),
 }
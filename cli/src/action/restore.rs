@@ -0,0 +1,150 @@
+use std::fs;
+
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::{
+    crypto::{prelude::*, ContextPool},
+    Store,
+};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{restore::RestoreMatcher, MainMatcher, Matcher},
+    util::{cli, error, secret, select, sync},
+};
+
+/// Restore secret from git revision action.
+pub(crate) struct Restore<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Restore<'a> {
+    /// Construct a new restore action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the restore action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_restore = RestoreMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_restore.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+        let sync = store.sync();
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Prepare sync
+        sync::ensure_ready(&sync, matcher_restore.allow_dirty());
+        if !matcher_restore.no_sync() {
+            sync.prepare()?;
+        }
+
+        let secret = select::store_select_secret_history(&store, matcher_restore.query())
+            .ok_or(Err::NoneSelected)?;
+
+        secret::print_name(
+            matcher_restore.query(),
+            &secret,
+            &store,
+            matcher_main.quiet(),
+        );
+
+        // Decrypt secret from revision
+        let path = secret
+            .relative_path(&store.root)
+            .map_err(|err| Err::Read(err.into()))?;
+        let revision = store
+            .history()
+            .revision_at(path, matcher_restore.rev())
+            .map_err(Err::Read)?;
+        let mut contexts = ContextPool::empty();
+        let plaintext =
+            crate::crypto::decrypt_revision(&mut contexts, &matcher_main, &store, &revision)
+                .map_err(Err::Read)?;
+
+        // Compare with current secret, confirm overwrite
+        let exists = secret.path.is_file();
+        if exists {
+            let current = crate::crypto::decrypt_file(&mut contexts, &matcher_main, &secret)
+                .map_err(Err::Read)?;
+            if current == plaintext {
+                if !matcher_main.quiet() {
+                    eprintln!("Secret is unchanged");
+                }
+                error::quit();
+            }
+
+            let prompt = format!(
+                "Overwrite secret with revision {}?",
+                revision.short_commit()
+            );
+            if !matcher_main.force() && !cli::prompt_yes(&prompt, Some(true), &matcher_main) {
+                if matcher_main.verbose() {
+                    eprintln!("Restore cancelled");
+                }
+                error::quit();
+            }
+        } else if let Some(parent) = secret.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| Err::Write(err.into()))?;
+        }
+
+        // Encrypt for current recipients and write
+        let recipients = store.recipients_for(&secret.path)?;
+        crate::crypto::pool_context(&mut contexts, &matcher_main, secret.proto())?
+            .encrypt_file(&recipients, plaintext, &secret.path)
+            .map_err(Err::Write)?;
+
+        // Finalize sync
+        if !matcher_restore.no_sync() {
+            sync.finalize(format!(
+                "Restore secret {} to revision {}",
+                secret.name,
+                revision.short_commit()
+            ))?;
+        }
+
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
+
+        if !matcher_main.quiet() {
+            eprintln!("Secret restored to revision {}", revision.short_commit());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("no secret selected")]
+    NoneSelected,
+
+    #[error("failed to read secret")]
+    Read(#[source] anyhow::Error),
+
+    #[error("failed to write restored secret")]
+    Write(#[source] anyhow::Error),
+}
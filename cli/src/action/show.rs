@@ -2,7 +2,7 @@ use std::{thread, time::Duration};
 
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Store};
+use prs_lib::{crypto::ContextPool, Plaintext, Secret, SecretBody, Store};
 use thiserror::Error;
 
 #[cfg(feature = "clipboard")]
@@ -43,20 +43,29 @@ impl<'a> Show<'a> {
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
 
-        let secret =
-            select::store_select_secret(&store, matcher_show.query()).ok_or(Err::NoneSelected)?;
+        let secret = if matcher_show.rev().is_some() {
+            select::store_select_secret_history(&store, matcher_show.query())
+        } else {
+            select::store_select_secret_indexed(&store, &matcher_main, matcher_show.query())
+        }
+        .ok_or(Err::NoneSelected)?;
 
         secret::print_name(matcher_show.query(), &secret, &store, matcher_main.quiet());
 
-        let mut plaintext = crate::crypto::context(&matcher_main)?
-            .decrypt_file(&secret.path)
-            .map_err(Err::Read)?;
+        let mut contexts = ContextPool::empty();
+        let mut plaintext = match matcher_show.rev() {
+            Some(rev) => read_revision(&mut contexts, &matcher_main, &store, &secret, rev),
+            None => crate::crypto::decrypt_file(&mut contexts, &matcher_main, &secret),
+        }
+        .map_err(Err::Read)?;
 
         // Trim plaintext to first line or property
         if matcher_show.first_line() {
-            plaintext = plaintext.first_line()?;
+            plaintext = SecretBody::parse(&plaintext)?.password().into();
         } else if let Some(property) = matcher_show.property() {
-            plaintext = plaintext.property(property).map_err(Err::Property)?;
+            plaintext = SecretBody::parse(&plaintext)?
+                .property(property)
+                .ok_or_else(|| Err::Property(property.into()))?;
         }
 
         let lines = plaintext.unsecure_to_str().unwrap().lines().count();
@@ -102,6 +111,19 @@ impl<'a> Show<'a> {
     }
 }
 
+/// Decrypt the secret as it was in the given git revision.
+fn read_revision(
+    contexts: &mut ContextPool,
+    matcher_main: &MainMatcher,
+    store: &Store,
+    secret: &Secret,
+    rev: &str,
+) -> Result<Plaintext> {
+    let path = secret.relative_path(&store.root)?;
+    let revision = store.history().revision_at(path, rev)?;
+    crate::crypto::decrypt_revision(contexts, matcher_main, store, &revision)
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum Err {
     #[error("failed to access password store")]
@@ -123,6 +145,6 @@ pub(crate) enum Err {
     #[error("failed to print colored secret to stdout")]
     PrintColor(#[source] anyhow::Error),
 
-    #[error("failed to select property from secret")]
-    Property(#[source] anyhow::Error),
+    #[error("property '{}' does not exist in secret", _0)]
+    Property(String),
 }
@@ -90,6 +90,9 @@ impl<'a> Sync<'a> {
         // Import new keys
         crypto::store::import_missing_keys_from_store(&store).map_err(Err::ImportRecipients)?;
 
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
         // TODO: assert not-dirty state?
 
         if !matcher_main.quiet() {
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use clap::ArgMatches;
-use prs_lib::{crypto::prelude::*, Recipients, Store};
+use prs_lib::{
+    crypto::{prelude::*, Proto},
+    Recipients, Store,
+};
 use thiserror::Error;
 
 use crate::{
@@ -69,7 +72,7 @@ impl<'a> Init<'a> {
         }
 
         // Select GPG key to encrypt Tomb key
-        let mut context = crate::crypto::context(&matcher_main)?;
+        let mut context = crate::crypto::context(&matcher_main, Proto::Gpg)?;
         let tmp = Recipients::from(context.keys_private().map_err(Err::Load)?);
         let key =
             select::select_key(tmp.keys(), Some("Select key for Tomb")).ok_or(Err::NoGpgKey)?;
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::Store;
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{
+        trash::{empty::EmptyMatcher, TrashMatcher},
+        MainMatcher, Matcher,
+    },
+    util::{cli, error, sync},
+};
+
+/// A trash empty action.
+pub(crate) struct Empty<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Empty<'a> {
+    /// Construct a new empty action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the empty action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_trash = TrashMatcher::with(self.cmd_matches).unwrap();
+        let matcher_empty = EmptyMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_trash.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+        let sync = store.sync();
+        let trash = store.trash();
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Prepare sync
+        sync::ensure_ready(&sync, matcher_empty.allow_dirty());
+        if !matcher_empty.no_sync() {
+            sync.prepare()?;
+        }
+
+        // Select secrets to remove
+        let older_than = matcher_empty.older_than().unwrap_or_default();
+        let entries: Vec<_> = trash
+            .list()
+            .map_err(Err::List)?
+            .into_iter()
+            .filter(|entry| entry.age() >= older_than)
+            .collect();
+        if entries.is_empty() {
+            if !matcher_main.quiet() {
+                eprintln!("No secrets to remove from trash");
+            }
+            error::quit();
+        }
+
+        // Confirm removal
+        if !matcher_main.force() {
+            if matcher_main.verbose() {
+                for entry in &entries {
+                    eprintln!("{} (removed {})", entry.name, super::format_age(entry));
+                }
+            }
+            if !cli::prompt_yes(
+                &format!("Permanently remove {} secrets from trash?", entries.len()),
+                Some(true),
+                &matcher_main,
+            ) {
+                if matcher_main.verbose() {
+                    eprintln!("Removal cancelled");
+                }
+                error::quit();
+            }
+        }
+
+        for entry in &entries {
+            trash.remove(entry).map_err(Err::Remove)?;
+        }
+
+        // Finalize sync
+        if !matcher_empty.no_sync() {
+            sync.finalize(format!("Empty trash, remove {} secrets", entries.len()))?;
+        }
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
+
+        if !matcher_main.quiet() {
+            eprintln!("Removed {} secrets from trash", entries.len());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to list secrets in trash")]
+    List(#[source] anyhow::Error),
+
+    #[error("failed to remove secret from trash")]
+    Remove(#[source] anyhow::Error),
+}
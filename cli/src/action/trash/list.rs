@@ -0,0 +1,83 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::Store;
+use thiserror::Error;
+
+use crate::cmd::matcher::{trash::TrashMatcher, MainMatcher, Matcher};
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+
+/// A trash list action.
+pub(crate) struct List<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> List<'a> {
+    /// Construct a new list action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the list action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_trash = TrashMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_trash.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        let entries = store.trash().list().map_err(Err::List)?;
+        if entries.is_empty() && !matcher_main.quiet() {
+            eprintln!("Trash is empty");
+        }
+
+        for entry in entries {
+            if matcher_main.quiet() {
+                println!("{}", entry.id);
+                continue;
+            }
+
+            let alias = entry
+                .target
+                .as_ref()
+                .map(|target| format!(" -> {}", target.display()))
+                .unwrap_or_default();
+            println!(
+                "{}{} (removed {}, ID: {})",
+                entry.name,
+                alias,
+                super::format_age(&entry),
+                entry.id,
+            );
+        }
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, false).map_err(Err::Tomb)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to list secrets in trash")]
+    List(#[source] anyhow::Error),
+}
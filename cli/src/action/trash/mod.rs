@@ -0,0 +1,50 @@
+pub(crate) mod empty;
+pub(crate) mod list;
+pub(crate) mod restore;
+
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::trash::TrashEntry;
+
+use crate::{
+    cmd::matcher::{Matcher, TrashMatcher},
+    util::time,
+};
+
+/// A trash action.
+pub(crate) struct Trash<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Trash<'a> {
+    /// Construct a new trash action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the trash action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matcher
+        let matcher_trash = TrashMatcher::with(self.cmd_matches).unwrap();
+
+        if matcher_trash.cmd_empty().is_some() {
+            return empty::Empty::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_trash.cmd_list().is_some() {
+            return list::List::new(self.cmd_matches).invoke();
+        }
+
+        if matcher_trash.cmd_restore().is_some() {
+            return restore::Restore::new(self.cmd_matches).invoke();
+        }
+
+        // Unreachable, clap will print help for missing sub command instead
+        unreachable!()
+    }
+}
+
+/// Format how long ago the given trash entry was removed.
+pub(crate) fn format_age(entry: &TrashEntry) -> String {
+    time::format_elapsed(entry.removed)
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use prs_lib::{
+    trash::{Trash, TrashEntry},
+    Store,
+};
+use thiserror::Error;
+
+#[cfg(all(feature = "tomb", target_os = "linux"))]
+use crate::util::tomb;
+use crate::{
+    cmd::matcher::{
+        trash::{restore::RestoreMatcher, TrashMatcher},
+        MainMatcher, Matcher,
+    },
+    util::{cli, error, sync},
+};
+
+/// A trash restore action.
+pub(crate) struct Restore<'a> {
+    cmd_matches: &'a ArgMatches,
+}
+
+impl<'a> Restore<'a> {
+    /// Construct a new restore action.
+    pub(crate) fn new(cmd_matches: &'a ArgMatches) -> Self {
+        Self { cmd_matches }
+    }
+
+    /// Invoke the restore action.
+    pub(crate) fn invoke(&self) -> Result<()> {
+        // Create the command matchers
+        let matcher_main = MainMatcher::with(self.cmd_matches).unwrap();
+        let matcher_trash = TrashMatcher::with(self.cmd_matches).unwrap();
+        let matcher_restore = RestoreMatcher::with(self.cmd_matches).unwrap();
+
+        let store = Store::open(matcher_trash.store()).map_err(Err::Store)?;
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        let mut tomb = store.tomb(
+            !matcher_main.verbose(),
+            matcher_main.verbose(),
+            matcher_main.force(),
+        );
+        let sync = store.sync();
+        let trash = store.trash();
+
+        // Prepare tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::prepare_tomb(&mut tomb, &matcher_main).map_err(Err::Tomb)?;
+
+        // Prepare sync
+        sync::ensure_ready(&sync, matcher_restore.allow_dirty());
+        if !matcher_restore.no_sync() {
+            sync.prepare()?;
+        }
+
+        // Find newest matching secret in trash
+        let entries = trash.find(matcher_restore.name()).map_err(Err::List)?;
+        let entry = entries
+            .first()
+            .ok_or_else(|| Err::NotFound(matcher_restore.name().into()))?;
+        if entries.len() > 1 && !matcher_main.quiet() {
+            eprintln!(
+                "Trash has {} secrets named '{}', restoring newest, use its ID to restore another",
+                entries.len(),
+                entry.name,
+            );
+        }
+
+        let restored = restore(&store, &trash, entry, &matcher_main)?;
+
+        // Finalize sync
+        if !matcher_restore.no_sync() {
+            sync.finalize(format!("Restore secret {} from trash", restored.join(", ")))?;
+        }
+
+        // Update search index
+        crate::util::index::update(&store, &matcher_main);
+
+        // Finalize tomb
+        #[cfg(all(feature = "tomb", target_os = "linux"))]
+        tomb::finalize_tomb(&mut tomb, &matcher_main, true).map_err(Err::Tomb)?;
+
+        if !matcher_main.quiet() {
+            eprintln!("Secret restored");
+        }
+
+        Ok(())
+    }
+}
+
+/// Restore the given secret from trash, returns the names of restored secrets.
+///
+/// If the secret is an alias with its target in trash, the target is restored
+/// as well. If the secret has aliases in trash, this asks to restore them.
+fn restore(
+    store: &Store,
+    trash: &Trash,
+    entry: &TrashEntry,
+    matcher_main: &MainMatcher,
+) -> Result<Vec<String>> {
+    let entries = trash.list().map_err(Err::List)?;
+    let mut restored = vec![];
+
+    // Restore alias target first if it is in trash
+    if let Some(target) = entry.alias_target() {
+        let target = entries
+            .iter()
+            .rev()
+            .find(|e| e.path == target)
+            .filter(|e| !store.root.join(&e.path).exists());
+        if let Some(target) = target {
+            trash.restore(target).map_err(Err::Restore)?;
+            restored.push(target.name.clone());
+        }
+    }
+
+    trash.restore(entry).map_err(Err::Restore)?;
+    restored.push(entry.name.clone());
+
+    // Ask to restore aliases targeting this secret
+    for alias in entries
+        .iter()
+        .filter(|alias| alias.alias_target().as_ref() == Some(&entry.path))
+    {
+        let prompt = format!("Restore alias '{}'?", alias.name);
+        if !matcher_main.force() && !cli::prompt_yes(&prompt, Some(true), matcher_main) {
+            continue;
+        }
+        match trash.restore(alias) {
+            Ok(_) => restored.push(alias.name.clone()),
+            Err(err) => error::print_error(&err.context("failed to restore alias, ignoring")),
+        }
+    }
+
+    Ok(restored)
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to access password store")]
+    Store(#[source] anyhow::Error),
+
+    #[cfg(all(feature = "tomb", target_os = "linux"))]
+    #[error("failed to prepare password store tomb for usage")]
+    Tomb(#[source] anyhow::Error),
+
+    #[error("failed to list secrets in trash")]
+    List(#[source] anyhow::Error),
+
+    #[error("no secret in trash named '{}'", _0)]
+    NotFound(String),
+
+    #[error("failed to restore secret from trash")]
+    Restore(#[source] anyhow::Error),
+}
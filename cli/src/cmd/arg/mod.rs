@@ -2,7 +2,9 @@ pub(crate) mod allow_dirty;
 pub(crate) mod no_sync;
 pub(crate) mod property;
 pub(crate) mod query;
+pub(crate) mod rev;
 pub(crate) mod store;
+pub(crate) mod template;
 pub(crate) mod timeout;
 
 use clap::{Arg, ArgMatches};
@@ -10,7 +12,7 @@ use clap::{Arg, ArgMatches};
 // Re-export to arg module
 pub(crate) use self::{
     allow_dirty::ArgAllowDirty, no_sync::ArgNoSync, property::ArgProperty, query::ArgQuery,
-    store::ArgStore, timeout::ArgTimeout,
+    rev::ArgRev, store::ArgStore, template::ArgTemplate, timeout::ArgTimeout,
 };
 
 /// A generic trait, for a reusable command argument struct.
@@ -17,7 +17,7 @@ impl CmdArg for ArgProperty {
             .alias("prop")
             .value_name("NAME")
             .global(true)
-            .help("Select a specific property")
+            .help("Select a specific property, nested YAML keys are separated by '.'")
     }
 }
 
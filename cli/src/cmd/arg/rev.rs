@@ -0,0 +1,31 @@
+use clap::{Arg, ArgMatches};
+
+use super::{CmdArg, CmdArgOption};
+
+/// The revision argument.
+pub(crate) struct ArgRev {}
+
+impl CmdArg for ArgRev {
+    fn name() -> &'static str {
+        "rev"
+    }
+
+    fn build<'b>() -> Arg<'b> {
+        Arg::new("rev")
+            .long("rev")
+            .short('r')
+            .alias("revision")
+            .alias("commit")
+            .value_name("REV")
+            .help("Use secret as it was in git revision, such as HEAD~2")
+    }
+}
+
+impl<'a> CmdArgOption<'a> for ArgRev {
+    type Value = Option<&'a str>;
+
+    #[allow(unused_lifetimes)]
+    fn value<'b: 'a>(matches: &'a ArgMatches) -> Self::Value {
+        Self::value_raw(matches)
+    }
+}
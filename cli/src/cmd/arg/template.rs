@@ -0,0 +1,29 @@
+use clap::{Arg, ArgMatches};
+
+use super::{CmdArg, CmdArgOption};
+
+/// The template argument.
+pub(crate) struct ArgTemplate {}
+
+impl CmdArg for ArgTemplate {
+    fn name() -> &'static str {
+        "template"
+    }
+
+    fn build<'b>() -> Arg<'b> {
+        Arg::new("template")
+            .long("template")
+            .short('T')
+            .value_name("NAME")
+            .help("Use secret template from the store")
+    }
+}
+
+impl<'a> CmdArgOption<'a> for ArgTemplate {
+    type Value = Option<&'a str>;
+
+    #[allow(unused_lifetimes)]
+    fn value<'b: 'a>(matches: &'a ArgMatches) -> Self::Value {
+        Self::value_raw(matches)
+    }
+}
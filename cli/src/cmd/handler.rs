@@ -81,12 +81,16 @@ impl<'a> Handler {
                     .help("Instruct GPG to ask passphrase in TTY rather than pinentry"),
             )
             .subcommand(subcmd::CmdAdd::build())
+            .subcommand(subcmd::CmdAudit::build())
             .subcommand(subcmd::CmdClone::build())
+            .subcommand(subcmd::CmdDiff::build())
             .subcommand(subcmd::CmdDuplicate::build())
             .subcommand(subcmd::CmdEdit::build())
+            .subcommand(subcmd::CmdFind::build())
             .subcommand(subcmd::CmdGenerate::build())
             .subcommand(subcmd::CmdGit::build())
             .subcommand(subcmd::CmdGrep::build())
+            .subcommand(subcmd::CmdHistory::build())
             .subcommand(subcmd::CmdHousekeeping::build())
             .subcommand(subcmd::CmdInit::build())
             .subcommand(subcmd::CmdInternal::build())
@@ -95,8 +99,27 @@ impl<'a> Handler {
             .subcommand(subcmd::CmdOtp::build())
             .subcommand(subcmd::CmdRecipients::build())
             .subcommand(subcmd::CmdRemove::build())
+            .subcommand(subcmd::CmdRestore::build())
             .subcommand(subcmd::CmdShow::build())
-            .subcommand(subcmd::CmdSync::build());
+            .subcommand(subcmd::CmdSync::build())
+            .subcommand(subcmd::CmdTrash::build());
+
+        #[cfg(feature = "backend-age")]
+        let app = app.arg(
+            Arg::new("age-identity")
+                .long("age-identity")
+                .value_name("PATH")
+                .global(true)
+                .help("Identity file to decrypt age secrets with"),
+        );
+
+        #[cfg(all(feature = "agent", unix))]
+        let app = app.subcommand(subcmd::CmdAgent::build()).arg(
+            Arg::new("no-agent")
+                .long("no-agent")
+                .global(true)
+                .help("Do not use running agent to cache decrypted secrets"),
+        );
 
         #[cfg(feature = "alias")]
         let app = app.subcommand(subcmd::CmdAlias::build());
@@ -104,6 +127,9 @@ impl<'a> Handler {
         #[cfg(feature = "clipboard")]
         let app = app.subcommand(subcmd::CmdCopy::build());
 
+        #[cfg(feature = "pwned")]
+        let app = app.subcommand(subcmd::CmdPwned::build());
+
         #[cfg(all(feature = "tomb", target_os = "linux"))]
         let app = app.subcommand(subcmd::CmdTomb::build());
 
@@ -127,12 +153,23 @@ impl<'a> Handler {
         matcher::AddMatcher::with(&self.matches)
     }
 
+    /// Get the agent sub command, if matched.
+    #[cfg(all(feature = "agent", unix))]
+    pub(crate) fn agent(&'a self) -> Option<matcher::AgentMatcher> {
+        matcher::AgentMatcher::with(&self.matches)
+    }
+
     /// Get the alias sub command, if matched.
     #[cfg(feature = "alias")]
     pub(crate) fn alias(&'a self) -> Option<matcher::AliasMatcher> {
         matcher::AliasMatcher::with(&self.matches)
     }
 
+    /// Get the audit sub command, if matched.
+    pub(crate) fn audit(&'a self) -> Option<matcher::AuditMatcher> {
+        matcher::AuditMatcher::with(&self.matches)
+    }
+
     /// Get the clone sub command, if matched.
     pub(crate) fn clone(&'a self) -> Option<matcher::CloneMatcher> {
         matcher::CloneMatcher::with(&self.matches)
@@ -144,6 +181,11 @@ impl<'a> Handler {
         matcher::CopyMatcher::with(&self.matches)
     }
 
+    /// Get the diff sub command, if matched.
+    pub(crate) fn diff(&'a self) -> Option<matcher::DiffMatcher> {
+        matcher::DiffMatcher::with(&self.matches)
+    }
+
     /// Get the duplicate sub command, if matched.
     pub(crate) fn duplicate(&'a self) -> Option<matcher::DuplicateMatcher> {
         matcher::DuplicateMatcher::with(&self.matches)
@@ -154,6 +196,11 @@ impl<'a> Handler {
         matcher::EditMatcher::with(&self.matches)
     }
 
+    /// Get the find sub command, if matched.
+    pub(crate) fn find(&'a self) -> Option<matcher::FindMatcher> {
+        matcher::FindMatcher::with(&self.matches)
+    }
+
     /// Get the generate sub command, if matched.
     pub(crate) fn generate(&'a self) -> Option<matcher::GenerateMatcher> {
         matcher::GenerateMatcher::with(&self.matches)
@@ -169,6 +216,11 @@ impl<'a> Handler {
         matcher::GrepMatcher::with(&self.matches)
     }
 
+    /// Get the history sub command, if matched.
+    pub(crate) fn history(&'a self) -> Option<matcher::HistoryMatcher> {
+        matcher::HistoryMatcher::with(&self.matches)
+    }
+
     /// Get the housekeeping sub command, if matched.
     pub(crate) fn housekeeping(&'a self) -> Option<matcher::HousekeepingMatcher> {
         matcher::HousekeepingMatcher::with(&self.matches)
@@ -199,6 +251,12 @@ impl<'a> Handler {
         matcher::MoveMatcher::with(&self.matches)
     }
 
+    /// Get the pwned sub command, if matched.
+    #[cfg(feature = "pwned")]
+    pub(crate) fn pwned(&'a self) -> Option<matcher::PwnedMatcher> {
+        matcher::PwnedMatcher::with(&self.matches)
+    }
+
     /// Get the recipients sub command, if matched.
     pub(crate) fn recipients(&'a self) -> Option<matcher::RecipientsMatcher> {
         matcher::RecipientsMatcher::with(&self.matches)
@@ -209,6 +267,11 @@ impl<'a> Handler {
         matcher::RemoveMatcher::with(&self.matches)
     }
 
+    /// Get the restore sub command, if matched.
+    pub(crate) fn restore(&'a self) -> Option<matcher::RestoreMatcher> {
+        matcher::RestoreMatcher::with(&self.matches)
+    }
+
     /// Get the show sub command, if matched.
     pub(crate) fn show(&'a self) -> Option<matcher::ShowMatcher> {
         matcher::ShowMatcher::with(&self.matches)
@@ -224,4 +287,9 @@ impl<'a> Handler {
     pub(crate) fn tomb(&'a self) -> Option<matcher::TombMatcher> {
         matcher::TombMatcher::with(&self.matches)
     }
+
+    /// Get the trash sub command, if matched.
+    pub(crate) fn trash(&'a self) -> Option<matcher::TrashMatcher> {
+        matcher::TrashMatcher::with(&self.matches)
+    }
 }
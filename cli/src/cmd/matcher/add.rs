@@ -1,7 +1,9 @@
 use clap::ArgMatches;
 
-use super::Matcher;
-use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, CmdArgFlag, CmdArgOption};
+use super::{generate::PASSWORD_LENGTH, Matcher};
+use crate::cmd::arg::{
+    ArgAllowDirty, ArgNoSync, ArgStore, ArgTemplate, CmdArgFlag, CmdArgOption,
+};
 
 /// The add command matcher.
 pub(crate) struct AddMatcher<'a> {
@@ -25,6 +27,31 @@ impl<'a: 'b, 'b> AddMatcher<'a> {
         self.matches.is_present("stdin")
     }
 
+    /// Properties to add, as key and value.
+    pub(crate) fn attributes(&self) -> Vec<(&str, &str)> {
+        self.matches
+            .values_of("ATTRIBUTE")
+            .map(|values| values.filter_map(|value| value.split_once('=')).collect())
+            .unwrap_or_default()
+    }
+
+    /// Length of the password to generate, if any.
+    pub(crate) fn generate(&self) -> Option<u16> {
+        if !self.matches.is_present("generate") {
+            return None;
+        }
+        Some(
+            self.matches
+                .value_of("generate")
+                .map_or(PASSWORD_LENGTH, |len| len.parse().expect("invalid length")),
+        )
+    }
+
+    /// The template to use.
+    pub(crate) fn template(&self) -> Option<&str> {
+        ArgTemplate::value(self.matches)
+    }
+
     /// The store.
     pub(crate) fn store(&self) -> String {
         ArgStore::value(self.matches)
@@ -0,0 +1,17 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The agent flush command matcher.
+pub(crate) struct FlushMatcher<'a> {
+    _matches: &'a ArgMatches,
+}
+
+impl<'a> Matcher<'a> for FlushMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("agent")?
+            .subcommand_matches("flush")
+            .map(|matches| FlushMatcher { _matches: matches })
+    }
+}
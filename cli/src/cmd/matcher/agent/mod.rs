@@ -0,0 +1,46 @@
+pub(crate) mod flush;
+pub(crate) mod start;
+pub(crate) mod status;
+pub(crate) mod stop;
+
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The agent command matcher.
+pub(crate) struct AgentMatcher<'a> {
+    root:     &'a ArgMatches,
+    _matches: &'a ArgMatches,
+}
+
+impl<'a> AgentMatcher<'a> {
+    /// Get the agent start sub command, if matched.
+    pub(crate) fn cmd_start(&'a self) -> Option<start::StartMatcher> {
+        start::StartMatcher::with(self.root)
+    }
+
+    /// Get the agent stop sub command, if matched.
+    pub(crate) fn cmd_stop(&'a self) -> Option<stop::StopMatcher> {
+        stop::StopMatcher::with(self.root)
+    }
+
+    /// Get the agent flush sub command, if matched.
+    pub(crate) fn cmd_flush(&'a self) -> Option<flush::FlushMatcher> {
+        flush::FlushMatcher::with(self.root)
+    }
+
+    /// Get the agent status sub command, if matched.
+    pub(crate) fn cmd_status(&'a self) -> Option<status::StatusMatcher> {
+        status::StatusMatcher::with(self.root)
+    }
+}
+
+impl<'a> Matcher<'a> for AgentMatcher<'a> {
+    fn with(root: &'a ArgMatches) -> Option<Self> {
+        root.subcommand_matches("agent")
+            .map(|matches| AgentMatcher {
+                root,
+                _matches: matches,
+            })
+    }
+}
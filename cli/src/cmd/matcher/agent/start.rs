@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::ArgMatches;
+use prs_lib::agent::server::DEFAULT_TTL;
+
+use super::Matcher;
+use crate::util::error::{quit_error, ErrorHints};
+
+/// The agent start command matcher.
+pub(crate) struct StartMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+impl StartMatcher<'_> {
+    /// Time to cache decrypted secrets for.
+    pub(crate) fn ttl(&self) -> Duration {
+        let ttl = match self.matches.value_of("ttl") {
+            Some(ttl) => ttl,
+            None => return DEFAULT_TTL,
+        };
+        match crate::util::time::parse_duration(ttl) {
+            Ok(ttl) => Duration::from_secs(ttl as u64),
+            Err(err) => quit_error(&anyhow!(err), ErrorHints::default()),
+        }
+    }
+
+    /// Whether to run the agent in the background.
+    pub(crate) fn daemon(&self) -> bool {
+        self.matches.is_present("daemon")
+    }
+}
+
+impl<'a> Matcher<'a> for StartMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("agent")?
+            .subcommand_matches("start")
+            .map(|matches| StartMatcher { matches })
+    }
+}
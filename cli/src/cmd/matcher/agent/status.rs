@@ -0,0 +1,17 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The agent status command matcher.
+pub(crate) struct StatusMatcher<'a> {
+    _matches: &'a ArgMatches,
+}
+
+impl<'a> Matcher<'a> for StatusMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("agent")?
+            .subcommand_matches("status")
+            .map(|matches| StatusMatcher { _matches: matches })
+    }
+}
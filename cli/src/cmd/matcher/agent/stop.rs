@@ -0,0 +1,24 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The agent stop command matcher.
+pub(crate) struct StopMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+impl StopMatcher<'_> {
+    /// Whether to try to stop.
+    pub(crate) fn do_try(&self) -> bool {
+        self.matches.is_present("try")
+    }
+}
+
+impl<'a> Matcher<'a> for StopMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("agent")?
+            .subcommand_matches("stop")
+            .map(|matches| StopMatcher { matches })
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::ArgMatches;
+use prs_lib::health::Policy;
+
+use super::Matcher;
+use crate::{
+    cmd::arg::{ArgQuery, ArgStore, CmdArgOption},
+    util::error::{quit_error, quit_error_msg, ErrorHints},
+};
+
+/// The audit command matcher.
+pub(crate) struct AuditMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> AuditMatcher<'a> {
+    /// The secret query.
+    pub(crate) fn query(&self) -> Option<String> {
+        ArgQuery::value(self.matches)
+    }
+
+    /// The policy to audit secrets against.
+    pub(crate) fn policy(&self) -> Policy {
+        Policy {
+            min_entropy: self.min_entropy().filter(|_| self.check("weak")),
+            max_age:     self.max_age().filter(|_| self.check("age")),
+            reuse:       self.check("reuse"),
+            fields:      self.check("fields"),
+        }
+    }
+
+    /// Whether to run the given check.
+    fn check(&self, check: &str) -> bool {
+        !self
+            .matches
+            .values_of("skip")
+            .is_some_and(|mut skip| skip.any(|skip| skip == check))
+    }
+
+    /// Minimum password entropy in bits.
+    fn min_entropy(&self) -> Option<f64> {
+        let bits = self.matches.value_of("min-entropy")?;
+        match bits.parse::<f64>() {
+            Ok(bits) if bits > 0.0 => Some(bits),
+            Ok(_) => None,
+            Err(_) => quit_error_msg(
+                format!("invalid minimum entropy: {bits}"),
+                ErrorHints::default(),
+            ),
+        }
+    }
+
+    /// Maximum time since a secret was last changed.
+    fn max_age(&self) -> Option<Duration> {
+        let time = self.matches.value_of("max-age")?;
+        match crate::util::time::parse_duration(time) {
+            Ok(0) => None,
+            Ok(time) => Some(Duration::from_secs(time as u64)),
+            Err(err) => quit_error(&anyhow!(err), ErrorHints::default()),
+        }
+    }
+
+    /// Whether to output JSON.
+    pub(crate) fn json(&self) -> bool {
+        self.matches.is_present("json")
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for AuditMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("audit")
+            .map(|matches| AuditMatcher { matches })
+    }
+}
@@ -0,0 +1,40 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArgOption};
+
+/// The diff command matcher.
+pub(crate) struct DiffMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> DiffMatcher<'a> {
+    /// The secret query.
+    pub(crate) fn query(&self) -> Option<String> {
+        ArgQuery::value(self.matches)
+    }
+
+    /// The old revision, if set.
+    pub(crate) fn rev1(&self) -> Option<&str> {
+        self.matches.value_of("REV1")
+    }
+
+    /// The new revision, if set.
+    pub(crate) fn rev2(&self) -> Option<&str> {
+        self.matches.value_of("REV2")
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for DiffMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("diff")
+            .map(|matches| DiffMatcher { matches })
+    }
+}
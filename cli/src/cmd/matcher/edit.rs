@@ -20,6 +20,33 @@ impl<'a: 'b, 'b> EditMatcher<'a> {
         self.matches.is_present("stdin")
     }
 
+    /// Properties to set, as key and value.
+    pub(crate) fn set(&self) -> Vec<(&str, &str)> {
+        self.matches
+            .values_of("set")
+            .map(|values| values.filter_map(|value| value.split_once('=')).collect())
+            .unwrap_or_default()
+    }
+
+    /// Properties to remove.
+    pub(crate) fn unset(&self) -> Vec<&str> {
+        self.matches
+            .values_of("unset")
+            .map(Iterator::collect)
+            .unwrap_or_default()
+    }
+
+    /// Check whether to read the new password from stdin.
+    pub(crate) fn password_stdin(&self) -> bool {
+        self.matches.is_present("password-stdin")
+    }
+
+    /// Check whether to edit fields from the command line, instead of using
+    /// an editor.
+    pub(crate) fn edit_fields(&self) -> bool {
+        !self.set().is_empty() || !self.unset().is_empty() || self.password_stdin()
+    }
+
     /// The store.
     pub(crate) fn store(&self) -> String {
         ArgStore::value(self.matches)
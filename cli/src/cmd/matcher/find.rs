@@ -0,0 +1,45 @@
+use clap::ArgMatches;
+use prs_lib::find::{FieldFilter, Query};
+
+use super::Matcher;
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArgOption};
+
+/// The find command matcher.
+pub(crate) struct FindMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> FindMatcher<'a> {
+    /// The find query, combining the secret query and filters.
+    pub(crate) fn query(&self) -> Query {
+        let mut query = Query::parse(ArgQuery::value(self.matches).as_deref());
+        query.fields = self
+            .matches
+            .values_of("field")
+            .map(|values| values.map(FieldFilter::parse).collect())
+            .unwrap_or_default();
+        if let Some(url) = self.matches.value_of("url") {
+            query.url = Some(url.into());
+        }
+        query
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+
+    /// Whether to only show the best match.
+    pub(crate) fn first(&self) -> bool {
+        self.matches.is_present("first")
+    }
+}
+
+impl<'a> Matcher<'a> for FindMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("find")
+            .map(|matches| FindMatcher { matches })
+    }
+}
@@ -5,13 +5,15 @@ use clap::ArgMatches;
 use super::Matcher;
 #[cfg(feature = "clipboard")]
 use crate::cmd::arg::ArgTimeout;
-use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, CmdArgFlag, CmdArgOption};
+use crate::cmd::arg::{
+    ArgAllowDirty, ArgNoSync, ArgStore, ArgTemplate, CmdArgFlag, CmdArgOption,
+};
 
 /// Default password length in characters.
-const PASSWORD_LENGTH: u16 = 24;
+pub(crate) const PASSWORD_LENGTH: u16 = 24;
 
 /// Default passphrase length in words.
-const PASSPHRASE_LENGTH: u16 = 5;
+pub(crate) const PASSPHRASE_LENGTH: u16 = 5;
 
 /// The generate command matcher.
 pub(crate) struct GenerateMatcher<'a> {
@@ -44,6 +46,16 @@ impl<'a: 'b, 'b> GenerateMatcher<'a> {
             })
     }
 
+    /// Check whether the kind or length of the password is given explicitly.
+    pub(crate) fn explicit(&self) -> bool {
+        self.passphrase() || self.matches.is_present("length")
+    }
+
+    /// The template to use.
+    pub(crate) fn template(&self) -> Option<&str> {
+        ArgTemplate::value(self.matches)
+    }
+
     /// Check whether to merge the secret.
     pub(crate) fn merge(&self) -> bool {
         self.matches.is_present("merge")
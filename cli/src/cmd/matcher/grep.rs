@@ -14,6 +14,53 @@ impl<'a: 'b, 'b> GrepMatcher<'a> {
         self.matches.value_of("TERM").unwrap()
     }
 
+    /// Property to only search values of.
+    pub(crate) fn field(&self) -> Option<&str> {
+        self.matches.value_of("field")
+    }
+
+    /// Whether to also match secret names.
+    pub(crate) fn names(&self) -> bool {
+        self.matches.is_present("names")
+    }
+
+    /// Whether to only show names of secrets with matches.
+    pub(crate) fn files_with_matches(&self) -> bool {
+        self.matches.is_present("files-with-matches")
+    }
+
+    /// Whether to only show the number of matching lines.
+    pub(crate) fn count(&self) -> bool {
+        self.matches.is_present("count")
+    }
+
+    /// Whether to select non-matching lines.
+    pub(crate) fn invert_match(&self) -> bool {
+        self.matches.is_present("invert-match")
+    }
+
+    /// Whether to match case-insensitively.
+    pub(crate) fn ignore_case(&self) -> bool {
+        self.matches.is_present("ignore-case")
+    }
+
+    /// Whether to output JSON.
+    pub(crate) fn json(&self) -> bool {
+        self.matches.is_present("json")
+    }
+
+    /// Whether to skip secrets that cannot be decrypted.
+    pub(crate) fn skip_errors(&self) -> bool {
+        self.matches.is_present("skip-errors")
+    }
+
+    /// Number of secrets to decrypt in parallel.
+    pub(crate) fn jobs(&self) -> Option<usize> {
+        self.matches
+            .value_of("jobs")
+            .map(|j| j.parse().expect("invalid number of jobs"))
+    }
+
     /// The store.
     pub(crate) fn store(&self) -> String {
         ArgStore::value(self.matches)
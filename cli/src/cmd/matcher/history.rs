@@ -0,0 +1,30 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArgOption};
+
+/// The history command matcher.
+pub(crate) struct HistoryMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> HistoryMatcher<'a> {
+    /// The secret query.
+    pub(crate) fn query(&self) -> Option<String> {
+        ArgQuery::value(self.matches)
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for HistoryMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("history")
+            .map(|matches| HistoryMatcher { matches })
+    }
+}
@@ -0,0 +1,26 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+use crate::cmd::arg::{ArgQuery, CmdArgOption};
+
+/// The housekeeping audit command matcher.
+pub(crate) struct AuditMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> AuditMatcher<'a> {
+    /// The secret query.
+    pub(crate) fn query(&self) -> Option<String> {
+        ArgQuery::value(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for AuditMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("housekeeping")?
+            .subcommand_matches("audit")
+            .map(|matches| AuditMatcher { matches })
+    }
+}
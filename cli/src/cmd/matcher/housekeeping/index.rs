@@ -0,0 +1,25 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The housekeeping index command matcher.
+pub(crate) struct IndexMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> IndexMatcher<'a> {
+    /// Check whether to remove the index.
+    pub(crate) fn remove(&self) -> bool {
+        self.matches.is_present("remove")
+    }
+}
+
+impl<'a> Matcher<'a> for IndexMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("housekeeping")?
+            .subcommand_matches("index")
+            .map(|matches| IndexMatcher { matches })
+    }
+}
@@ -1,3 +1,6 @@
+pub(crate) mod audit;
+pub(crate) mod index;
+pub(crate) mod lint;
 pub(crate) mod recrypt;
 pub(crate) mod run;
 pub(crate) mod sync_keys;
@@ -16,6 +19,21 @@ pub(crate) struct HousekeepingMatcher<'a> {
 
 #[allow(single_use_lifetimes)]
 impl<'a: 'b, 'b> HousekeepingMatcher<'a> {
+    /// Get the housekepeing audit sub command, if matched.
+    pub(crate) fn audit(&'a self) -> Option<audit::AuditMatcher> {
+        audit::AuditMatcher::with(self.root)
+    }
+
+    /// Get the housekepeing index sub command, if matched.
+    pub(crate) fn index(&'a self) -> Option<index::IndexMatcher> {
+        index::IndexMatcher::with(self.root)
+    }
+
+    /// Get the housekepeing lint sub command, if matched.
+    pub(crate) fn lint(&'a self) -> Option<lint::LintMatcher> {
+        lint::LintMatcher::with(self.root)
+    }
+
     /// Get the housekepeing recrypt sub command, if matched.
     pub(crate) fn recrypt(&'a self) -> Option<recrypt::RecryptMatcher> {
         recrypt::RecryptMatcher::with(self.root)
@@ -15,6 +15,13 @@ impl<'a: 'b, 'b> RecryptMatcher<'a> {
         ArgQuery::value(self.matches)
     }
 
+    /// Number of secrets to re-encrypt in parallel.
+    pub(crate) fn jobs(&self) -> Option<usize> {
+        self.matches
+            .value_of("jobs")
+            .map(|j| j.parse().expect("invalid number of jobs"))
+    }
+
     /// Whether to allow a dirty repository for syncing.
     pub(crate) fn allow_dirty(&self) -> bool {
         ArgAllowDirty::is_present(self.matches)
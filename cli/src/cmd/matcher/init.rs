@@ -14,6 +14,12 @@ impl<'a: 'b, 'b> InitMatcher<'a> {
     pub(crate) fn store(&self) -> String {
         ArgStore::value(self.matches)
     }
+
+    /// Check whether to use age for the new store.
+    #[cfg(feature = "backend-age")]
+    pub(crate) fn age(&self) -> bool {
+        self.matches.is_present("age")
+    }
 }
 
 impl<'a> Matcher<'a> for InitMatcher<'a> {
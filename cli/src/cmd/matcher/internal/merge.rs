@@ -0,0 +1,40 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The internal git merge driver command matcher.
+pub(crate) struct MergeMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> MergeMatcher<'a> {
+    /// The encrypted common ancestor secret file.
+    pub(crate) fn base(&self) -> &str {
+        self.matches.value_of("BASE").unwrap()
+    }
+
+    /// The encrypted current secret file.
+    pub(crate) fn ours(&self) -> &str {
+        self.matches.value_of("OURS").unwrap()
+    }
+
+    /// The encrypted other secret file.
+    pub(crate) fn theirs(&self) -> &str {
+        self.matches.value_of("THEIRS").unwrap()
+    }
+
+    /// The secret path in the repository, if known.
+    pub(crate) fn path(&self) -> Option<&str> {
+        self.matches.value_of("PATH")
+    }
+}
+
+impl<'a> Matcher<'a> for MergeMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("internal")?
+            .subcommand_matches("merge")
+            .map(|matches| MergeMatcher { matches })
+    }
+}
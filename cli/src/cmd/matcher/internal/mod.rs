@@ -1,6 +1,8 @@
 #[cfg(feature = "clipboard")]
 pub(crate) mod clip_revert;
 pub(crate) mod completions;
+pub(crate) mod merge;
+pub(crate) mod textconv;
 
 use clap::ArgMatches;
 
@@ -24,6 +26,16 @@ impl<'a: 'b, 'b> InternalMatcher<'a> {
     pub(crate) fn completions(&'a self) -> Option<completions::CompletionsMatcher> {
         completions::CompletionsMatcher::with(self.root)
     }
+
+    /// Get the internal git merge driver sub command, if matched.
+    pub(crate) fn merge(&'a self) -> Option<merge::MergeMatcher> {
+        merge::MergeMatcher::with(self.root)
+    }
+
+    /// Get the internal git textconv sub command, if matched.
+    pub(crate) fn textconv(&'a self) -> Option<textconv::TextconvMatcher> {
+        textconv::TextconvMatcher::with(self.root)
+    }
 }
 
 impl<'a> Matcher<'a> for InternalMatcher<'a> {
@@ -0,0 +1,25 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+
+/// The internal git textconv command matcher.
+pub(crate) struct TextconvMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> TextconvMatcher<'a> {
+    /// The encrypted secret file to decrypt.
+    pub(crate) fn file(&self) -> &str {
+        self.matches.value_of("FILE").unwrap()
+    }
+}
+
+impl<'a> Matcher<'a> for TextconvMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("internal")?
+            .subcommand_matches("textconv")
+            .map(|matches| TextconvMatcher { matches })
+    }
+}
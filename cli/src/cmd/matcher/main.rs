@@ -1,3 +1,6 @@
+#[cfg(feature = "backend-age")]
+use std::path::PathBuf;
+
 use clap::ArgMatches;
 
 use super::Matcher;
@@ -40,6 +43,18 @@ impl<'a: 'b, 'b> MainMatcher<'a> {
     pub fn gpg_tty(&self) -> bool {
         self.matches.is_present("gpg-tty")
     }
+
+    /// Check whether to use a running agent.
+    #[cfg(all(feature = "agent", unix))]
+    pub fn agent(&self) -> bool {
+        !self.matches.is_present("no-agent")
+    }
+
+    /// Get the age identity file to use, if set.
+    #[cfg(feature = "backend-age")]
+    pub fn age_identity(&self) -> Option<PathBuf> {
+        self.matches.value_of("age-identity").map(PathBuf::from)
+    }
 }
 
 impl<'a> Matcher<'a> for MainMatcher<'a> {
@@ -1,15 +1,21 @@
 pub(crate) mod add;
+#[cfg(all(feature = "agent", unix))]
+pub(crate) mod agent;
 #[cfg(feature = "alias")]
 pub(crate) mod alias;
+pub(crate) mod audit;
 pub(crate) mod clone;
 #[cfg(feature = "clipboard")]
 pub(crate) mod copy;
+pub(crate) mod diff;
 pub(crate) mod duplicate;
 pub(crate) mod edit;
+pub(crate) mod find;
 pub(crate) mod generate;
 pub(crate) mod git;
 #[cfg(feature = "grep")]
 pub(crate) mod grep;
+pub(crate) mod history;
 pub(crate) mod housekeeping;
 pub(crate) mod init;
 pub(crate) mod internal;
@@ -18,14 +24,20 @@ pub(crate) mod main;
 pub(crate) mod r#move;
 #[cfg(feature = "otp")]
 pub(crate) mod otp;
+#[cfg(feature = "pwned")]
+pub(crate) mod pwned;
 pub(crate) mod recipients;
 pub(crate) mod remove;
+pub(crate) mod restore;
 pub(crate) mod show;
 pub(crate) mod sync;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) mod tomb;
+pub(crate) mod trash;
 
 // Re-export to matcher module
+#[cfg(all(feature = "agent", unix))]
+pub(crate) use self::agent::AgentMatcher;
 #[cfg(feature = "alias")]
 pub(crate) use self::alias::AliasMatcher;
 #[cfg(feature = "clipboard")]
@@ -34,16 +46,22 @@ pub(crate) use self::copy::CopyMatcher;
 pub(crate) use self::grep::GrepMatcher;
 #[cfg(feature = "otp")]
 pub(crate) use self::otp::OtpMatcher;
+#[cfg(feature = "pwned")]
+pub(crate) use self::pwned::PwnedMatcher;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) use self::tomb::TombMatcher;
 #[rustfmt::skip]
 pub(crate) use self::{
     add::AddMatcher,
+    audit::AuditMatcher,
     clone::CloneMatcher,
+    diff::DiffMatcher,
     duplicate::DuplicateMatcher,
     edit::EditMatcher,
+    find::FindMatcher,
     generate::GenerateMatcher,
     git::GitMatcher,
+    history::HistoryMatcher,
     housekeeping::HousekeepingMatcher,
     init::InitMatcher,
     internal::InternalMatcher,
@@ -52,8 +70,10 @@ pub(crate) use self::{
     r#move::MoveMatcher,
     recipients::RecipientsMatcher,
     remove::RemoveMatcher,
+    restore::RestoreMatcher,
     show::ShowMatcher,
     sync::SyncMatcher,
+    trash::TrashMatcher,
 
 };
 
@@ -0,0 +1,55 @@
+use clap::ArgMatches;
+use prs_lib::pwned::Source;
+
+use super::Matcher;
+use crate::{
+    cmd::arg::{ArgQuery, ArgStore, CmdArgOption},
+    util::error::{quit_error_msg, ErrorHintsBuilder},
+};
+
+/// The pwned command matcher.
+pub(crate) struct PwnedMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> PwnedMatcher<'a> {
+    /// The secret query.
+    pub(crate) fn query(&self) -> Option<String> {
+        ArgQuery::value(self.matches)
+    }
+
+    /// The source of pwned password hashes.
+    pub(crate) fn source(&self) -> Source {
+        if let Some(url) = self.matches.value_of("api") {
+            return Source::Api(url.into());
+        }
+        match self.matches.value_of("dataset") {
+            Some(path) => Source::dataset(path.into()),
+            None => quit_error_msg(
+                "no pwned password dataset configured",
+                ErrorHintsBuilder::default()
+                    .add_info(
+                        "Use '--dataset PATH' or set PRS_PWNED_DATASET, or use '--api' to query \
+                         Have I Been Pwned"
+                            .into(),
+                    )
+                    .build()
+                    .unwrap(),
+            ),
+        }
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for PwnedMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("pwned")
+            .map(|matches| PwnedMatcher { matches })
+    }
+}
@@ -47,6 +47,13 @@ impl<'a: 'b, 'b> RecipientsMatcher<'a> {
     pub(crate) fn store(&self) -> String {
         ArgStore::value(self.matches)
     }
+
+    /// The store subfolder to manage recipients for, if set.
+    pub(crate) fn path(&self) -> Option<&str> {
+        self.matches
+            .value_of("path")
+            .filter(|p| !p.trim().is_empty())
+    }
 }
 
 impl<'a> Matcher<'a> for RecipientsMatcher<'a> {
@@ -15,6 +15,11 @@ impl<'a: 'b, 'b> RemoveMatcher<'a> {
         ArgQuery::value(self.matches)
     }
 
+    /// Whether to remove permanently instead of moving to trash.
+    pub(crate) fn permanent(&self) -> bool {
+        self.matches.is_present("permanent")
+    }
+
     /// The store.
     pub(crate) fn store(&self) -> String {
         ArgStore::value(self.matches)
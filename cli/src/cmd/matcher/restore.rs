@@ -0,0 +1,47 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+use crate::cmd::arg::{
+    ArgAllowDirty, ArgNoSync, ArgQuery, ArgRev, ArgStore, CmdArgFlag, CmdArgOption,
+};
+
+/// The restore command matcher.
+pub(crate) struct RestoreMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> RestoreMatcher<'a> {
+    /// The secret query.
+    pub(crate) fn query(&self) -> Option<String> {
+        ArgQuery::value(self.matches)
+    }
+
+    /// The revision to restore.
+    pub(crate) fn rev(&self) -> &str {
+        ArgRev::value(self.matches).unwrap()
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+
+    /// Whether to allow a dirty repository for syncing.
+    pub(crate) fn allow_dirty(&self) -> bool {
+        ArgAllowDirty::is_present(self.matches)
+    }
+
+    /// Whether to not sync.
+    pub(crate) fn no_sync(&self) -> bool {
+        ArgNoSync::is_present(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for RestoreMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("restore")
+            .map(|matches| RestoreMatcher { matches })
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use clap::ArgMatches;
 
 use super::Matcher;
-use crate::cmd::arg::{ArgProperty, ArgQuery, ArgStore, ArgTimeout, CmdArgOption};
+use crate::cmd::arg::{ArgProperty, ArgQuery, ArgRev, ArgStore, ArgTimeout, CmdArgOption};
 
 /// The show command matcher.
 pub(crate) struct ShowMatcher<'a> {
@@ -36,6 +36,11 @@ impl<'a: 'b, 'b> ShowMatcher<'a> {
         ArgProperty::value(self.matches)
     }
 
+    /// The git revision to show the secret from, if set.
+    pub(crate) fn rev(&self) -> Option<&str> {
+        ArgRev::value(self.matches)
+    }
+
     /// Check whether to read from copy.
     #[cfg(feature = "clipboard")]
     pub(crate) fn copy(&self) -> bool {
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::ArgMatches;
+
+use super::Matcher;
+use crate::{
+    cmd::arg::{ArgAllowDirty, ArgNoSync, CmdArgFlag},
+    util::error::{quit_error, ErrorHints},
+};
+
+/// The trash empty command matcher.
+pub(crate) struct EmptyMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> EmptyMatcher<'a> {
+    /// Only remove secrets removed longer than this ago.
+    pub(crate) fn older_than(&self) -> Option<Duration> {
+        let time = self.matches.value_of("older-than")?;
+        match crate::util::time::parse_duration(time) {
+            Ok(time) => Some(Duration::from_secs(time as u64)),
+            Err(err) => quit_error(&anyhow!(err), ErrorHints::default()),
+        }
+    }
+
+    /// Whether to allow a dirty repository for syncing.
+    pub(crate) fn allow_dirty(&self) -> bool {
+        ArgAllowDirty::is_present(self.matches)
+    }
+
+    /// Whether to not sync.
+    pub(crate) fn no_sync(&self) -> bool {
+        ArgNoSync::is_present(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for EmptyMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("trash")?
+            .subcommand_matches("empty")
+            .map(|matches| EmptyMatcher { matches })
+    }
+}
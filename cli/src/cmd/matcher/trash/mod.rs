@@ -0,0 +1,45 @@
+pub(crate) mod empty;
+pub(crate) mod list;
+pub(crate) mod restore;
+
+use clap::ArgMatches;
+
+use crate::cmd::arg::{ArgStore, CmdArgOption};
+
+use super::Matcher;
+
+/// The trash matcher.
+pub(crate) struct TrashMatcher<'a> {
+    root:    &'a ArgMatches,
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> TrashMatcher<'a> {
+    /// Get the trash empty sub command, if matched.
+    pub(crate) fn cmd_empty(&'a self) -> Option<empty::EmptyMatcher> {
+        empty::EmptyMatcher::with(self.root)
+    }
+
+    /// Get the trash list sub command, if matched.
+    pub(crate) fn cmd_list(&'a self) -> Option<list::ListMatcher> {
+        list::ListMatcher::with(self.root)
+    }
+
+    /// Get the trash restore sub command, if matched.
+    pub(crate) fn cmd_restore(&'a self) -> Option<restore::RestoreMatcher> {
+        restore::RestoreMatcher::with(self.root)
+    }
+
+    /// The store.
+    pub(crate) fn store(&self) -> String {
+        ArgStore::value(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for TrashMatcher<'a> {
+    fn with(root: &'a ArgMatches) -> Option<Self> {
+        root.subcommand_matches("trash")
+            .map(|matches| TrashMatcher { root, matches })
+    }
+}
@@ -0,0 +1,36 @@
+use clap::ArgMatches;
+
+use super::Matcher;
+use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, CmdArgFlag};
+
+/// The trash restore command matcher.
+pub(crate) struct RestoreMatcher<'a> {
+    matches: &'a ArgMatches,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a: 'b, 'b> RestoreMatcher<'a> {
+    /// The secret name or trash ID to restore.
+    pub(crate) fn name(&self) -> &str {
+        self.matches.value_of("NAME").unwrap()
+    }
+
+    /// Whether to allow a dirty repository for syncing.
+    pub(crate) fn allow_dirty(&self) -> bool {
+        ArgAllowDirty::is_present(self.matches)
+    }
+
+    /// Whether to not sync.
+    pub(crate) fn no_sync(&self) -> bool {
+        ArgNoSync::is_present(self.matches)
+    }
+}
+
+impl<'a> Matcher<'a> for RestoreMatcher<'a> {
+    fn with(matches: &'a ArgMatches) -> Option<Self> {
+        matches
+            .subcommand_matches("trash")?
+            .subcommand_matches("restore")
+            .map(|matches| RestoreMatcher { matches })
+    }
+}
@@ -1,6 +1,9 @@
 use clap::{Command, Arg};
 
-use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, CmdArg};
+use crate::{
+    cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, ArgTemplate, CmdArg},
+    util::pass::PASSWORD_MIN_LENGTH,
+};
 
 /// The add command definition.
 pub(crate) struct CmdAdd;
@@ -30,6 +33,49 @@ impl CmdAdd {
                     .help("Read secret from stdin, do not open editor")
                     .conflicts_with("empty"),
             )
+            .arg(
+                Arg::new("ATTRIBUTE")
+                    .value_name("KEY=VALUE")
+                    .multiple_values(true)
+                    .conflicts_with_all(&["empty", "stdin"])
+                    .help("Add property to secret, nested keys are separated by '.', skips editor")
+                    .validator(|p| {
+                        if p.contains('=') {
+                            Ok(())
+                        } else {
+                            Err(String::from("must be in the form KEY=VALUE"))
+                        }
+                    }),
+            )
+            .arg(
+                Arg::new("generate")
+                    .long("generate")
+                    .short('g')
+                    .alias("gen")
+                    .value_name("LENGTH")
+                    .min_values(0)
+                    .max_values(1)
+                    .conflicts_with_all(&["empty", "stdin"])
+                    .help("Generate password with optional length, skips editor")
+                    .long_help(
+                        "Generate password with optional length in characters, skips editor. \
+                         Specify it after all properties.",
+                    )
+                    .validator(|p| {
+                        p.parse::<usize>()
+                            .ok()
+                            .filter(|len| *len >= PASSWORD_MIN_LENGTH && *len <= u16::MAX.into())
+                            .map(|_| ())
+                            .ok_or_else(|| {
+                                format!("must be a number of at least {PASSWORD_MIN_LENGTH}")
+                            })
+                    }),
+            )
+            .arg(
+                ArgTemplate::build()
+                    .conflicts_with_all(&["empty", "stdin"])
+                    .help("Pre-fill secret from template in the store"),
+            )
             .arg(ArgStore::build())
             .arg(ArgAllowDirty::build())
             .arg(ArgNoSync::build())
@@ -0,0 +1,13 @@
+use clap::Command;
+
+/// The agent flush command definition.
+pub(crate) struct CmdFlush;
+
+impl CmdFlush {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("flush")
+            .alias("clear")
+            .alias("lock")
+            .about("Forget all cached secrets")
+    }
+}
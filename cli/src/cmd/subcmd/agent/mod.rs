@@ -0,0 +1,21 @@
+pub(crate) mod flush;
+pub(crate) mod start;
+pub(crate) mod status;
+pub(crate) mod stop;
+
+use clap::Command;
+
+/// The agent command definition.
+pub(crate) struct CmdAgent;
+
+impl CmdAgent {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("agent")
+            .about("Manage agent caching decrypted secrets")
+            .subcommand_required(true)
+            .subcommand(start::CmdStart::build())
+            .subcommand(stop::CmdStop::build())
+            .subcommand(flush::CmdFlush::build())
+            .subcommand(status::CmdStatus::build())
+    }
+}
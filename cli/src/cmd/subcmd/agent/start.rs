@@ -0,0 +1,25 @@
+use clap::{Arg, Command};
+
+/// The agent start command definition.
+pub(crate) struct CmdStart;
+
+impl CmdStart {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("start")
+            .alias("run")
+            .about("Start agent")
+            .arg(
+                Arg::new("ttl")
+                    .long("ttl")
+                    .short('t')
+                    .value_name("TIME")
+                    .help("Time to cache decrypted secrets for (default: 5m)"),
+            )
+            .arg(
+                Arg::new("daemon")
+                    .long("daemon")
+                    .short('d')
+                    .help("Run agent in the background"),
+            )
+    }
+}
@@ -0,0 +1,10 @@
+use clap::Command;
+
+/// The agent status command definition.
+pub(crate) struct CmdStatus;
+
+impl CmdStatus {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("status").about("Query agent status")
+    }
+}
@@ -0,0 +1,17 @@
+use clap::{Arg, Command};
+
+/// The agent stop command definition.
+pub(crate) struct CmdStop;
+
+impl CmdStop {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("stop")
+            .alias("kill")
+            .about("Stop agent, forget cached secrets")
+            .arg(
+                Arg::new("try")
+                    .long("try")
+                    .help("Try to stop, don't fail if not running"),
+            )
+    }
+}
@@ -0,0 +1,47 @@
+use clap::{Arg, Command};
+
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArg};
+
+/// The audit command definition.
+pub(crate) struct CmdAudit;
+
+impl CmdAudit {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("audit")
+            .about("Audit password health")
+            .long_about(
+                "Audit password health. Reports weak passwords, passwords reused across secrets, \
+                 passwords not changed for a long time and secrets missing a user or URL field. \
+                 Exits with a non-zero code if any problem is found.",
+            )
+            .arg(ArgQuery::build())
+            .arg(
+                Arg::new("min-entropy")
+                    .long("min-entropy")
+                    .value_name("BITS")
+                    .default_value("50")
+                    .help("Minimum password entropy in bits, 0 to disable"),
+            )
+            .arg(
+                Arg::new("max-age")
+                    .long("max-age")
+                    .value_name("TIME")
+                    .default_value("365d")
+                    .help("Maximum time since a secret was last changed in git, 0 to disable"),
+            )
+            .arg(
+                Arg::new("skip")
+                    .long("skip")
+                    .value_name("CHECK")
+                    .multiple_occurrences(true)
+                    .possible_values(&["weak", "reuse", "age", "fields"])
+                    .help("Skip a check"),
+            )
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .help("Output the report as JSON"),
+            )
+            .arg(ArgStore::build())
+    }
+}
@@ -0,0 +1,24 @@
+use clap::{Arg, Command};
+
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArg};
+
+/// The diff command definition.
+pub(crate) struct CmdDiff;
+
+impl CmdDiff {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("diff")
+            .alias("changes")
+            .about("Show changes to a secret between revisions")
+            .arg(ArgQuery::build())
+            .arg(
+                Arg::new("REV1")
+                    .help("Old git revision, defaults to the previous version"),
+            )
+            .arg(
+                Arg::new("REV2")
+                    .help("New git revision, defaults to the current secret"),
+            )
+            .arg(ArgStore::build())
+    }
+}
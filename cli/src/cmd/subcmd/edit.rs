@@ -18,6 +18,35 @@ impl CmdEdit {
                     .alias("from-stdin")
                     .help("Read secret from stdin, do not open editor"),
             )
+            .arg(
+                Arg::new("set")
+                    .long("set")
+                    .value_name("KEY=VALUE")
+                    .multiple_occurrences(true)
+                    .conflicts_with("stdin")
+                    .help("Set a property, nested keys are separated by '.', skips editor")
+                    .validator(|p| {
+                        if p.contains('=') {
+                            Ok(())
+                        } else {
+                            Err(String::from("must be in the form KEY=VALUE"))
+                        }
+                    }),
+            )
+            .arg(
+                Arg::new("unset")
+                    .long("unset")
+                    .value_name("KEY")
+                    .multiple_occurrences(true)
+                    .conflicts_with("stdin")
+                    .help("Remove a property, nested keys are separated by '.', skips editor"),
+            )
+            .arg(
+                Arg::new("password-stdin")
+                    .long("password-stdin")
+                    .conflicts_with("stdin")
+                    .help("Read new password from stdin, keep other lines, skips editor"),
+            )
             .arg(ArgStore::build())
             .arg(ArgAllowDirty::build())
             .arg(ArgNoSync::build())
@@ -0,0 +1,35 @@
+use clap::{Arg, Command};
+
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArg};
+
+/// The find command definition.
+pub(crate) struct CmdFind;
+
+impl CmdFind {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("find")
+            .alias("search")
+            .about("Find secrets by fuzzy name, field or URL")
+            .arg(ArgQuery::build().help("Fuzzy secret name query, or a URL"))
+            .arg(
+                Arg::new("field")
+                    .long("field")
+                    .value_name("KEY[=VALUE]")
+                    .multiple_occurrences(true)
+                    .help("Only secrets with this field, containing the value if given"),
+            )
+            .arg(
+                Arg::new("url")
+                    .long("url")
+                    .value_name("URL")
+                    .help("Only secrets for the domain of this URL, by name or URL field"),
+            )
+            .arg(
+                Arg::new("first")
+                    .long("first")
+                    .short('1')
+                    .help("Only show the best matching secret"),
+            )
+            .arg(ArgStore::build())
+    }
+}
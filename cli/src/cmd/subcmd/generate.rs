@@ -2,7 +2,7 @@ use clap::{Command, Arg};
 
 #[cfg(feature = "clipboard")]
 use crate::cmd::arg::ArgTimeout;
-use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, CmdArg};
+use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, ArgTemplate, CmdArg};
 
 /// The generate command definition.
 pub(crate) struct CmdGenerate;
@@ -63,6 +63,7 @@ impl CmdGenerate {
                     .alias("display")
                     .help("Display secret after generation"),
             )
+            .arg(ArgTemplate::build().help("Fill secret from template, generate its fields"))
             .arg(ArgStore::build())
             .arg(ArgAllowDirty::build())
             .arg(ArgNoSync::build());
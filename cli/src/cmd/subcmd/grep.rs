@@ -1,4 +1,6 @@
-use clap::{Command, Arg};
+use std::num::NonZeroUsize;
+
+use clap::{Arg, Command};
 
 use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, CmdArg};
 
@@ -15,6 +17,74 @@ impl CmdGrep {
                     .takes_value(true)
                     .required(true)
             )
+            .arg(
+                Arg::new("field")
+                    .long("field")
+                    .value_name("NAME")
+                    .help("Only search values of this property")
+                    .long_help(
+                        "Only search values of this property, matched case-insensitively. Use \
+                         'password' to search the password on the first line.",
+                    ),
+            )
+            .arg(
+                Arg::new("names")
+                    .long("names")
+                    .help("Also match secret names"),
+            )
+            .arg(
+                Arg::new("files-with-matches")
+                    .long("files-with-matches")
+                    .short('l')
+                    .help("Only show names of secrets with matches"),
+            )
+            .arg(
+                Arg::new("count")
+                    .long("count")
+                    .short('c')
+                    .conflicts_with("files-with-matches")
+                    .help("Only show the number of matching lines for each secret"),
+            )
+            .arg(
+                // Short -v is taken by the global verbose flag
+                Arg::new("invert-match")
+                    .long("invert-match")
+                    .help("Select lines not matching the search term"),
+            )
+            .arg(
+                Arg::new("ignore-case")
+                    .long("ignore-case")
+                    .short('i')
+                    .help("Match the search term case-insensitively"),
+            )
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .conflicts_with_all(&["files-with-matches", "count"])
+                    .help("Output matches as JSON lines"),
+            )
+            .arg(
+                Arg::new("skip-errors")
+                    .long("skip-errors")
+                    .help("Skip secrets that cannot be decrypted, instead of failing"),
+            )
+            .arg(
+                Arg::new("jobs")
+                    .long("jobs")
+                    .short('j')
+                    .value_name("NUM")
+                    .help("Number of secrets to decrypt in parallel")
+                    .long_help(
+                        "Number of secrets to decrypt in parallel. Defaults to the number of CPU \
+                         cores.",
+                    )
+                    .validator(|n| {
+                        n.parse::<NonZeroUsize>()
+                            .map_err(|_| "value must be a positive number")
+                            .map(|_| ())
+                            .map_err(ToString::to_string)
+                    }),
+            )
             .arg(ArgStore::build())
             .arg(ArgAllowDirty::build())
             .arg(ArgNoSync::build())
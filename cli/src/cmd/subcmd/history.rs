@@ -0,0 +1,18 @@
+use clap::Command;
+
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArg};
+
+/// The history command definition.
+pub(crate) struct CmdHistory;
+
+impl CmdHistory {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("history")
+            .alias("hist")
+            .alias("log")
+            .alias("revisions")
+            .about("List changes to a secret")
+            .arg(ArgQuery::build())
+            .arg(ArgStore::build())
+    }
+}
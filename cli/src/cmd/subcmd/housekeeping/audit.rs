@@ -0,0 +1,15 @@
+use clap::Command;
+
+use crate::cmd::arg::{ArgQuery, CmdArg};
+
+/// The housekeeping audit command definition.
+pub(crate) struct CmdAudit;
+
+impl CmdAudit {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("audit")
+            .alias("check-recipients")
+            .about("Audit which keys secrets are encrypted for, report recipient drift")
+            .arg(ArgQuery::build())
+    }
+}
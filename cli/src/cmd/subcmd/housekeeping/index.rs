@@ -0,0 +1,18 @@
+use clap::{Arg, Command};
+
+/// The housekeeping index command definition.
+pub(crate) struct CmdIndex;
+
+impl CmdIndex {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("index")
+            .alias("reindex")
+            .about("Build encrypted search index, enables it")
+            .arg(
+                Arg::new("remove")
+                    .long("remove")
+                    .alias("disable")
+                    .help("Remove search index, disables it"),
+            )
+    }
+}
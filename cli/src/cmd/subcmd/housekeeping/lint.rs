@@ -0,0 +1,15 @@
+use clap::Command;
+
+use crate::cmd::arg::{ArgQuery, CmdArg};
+
+/// The housekeeping lint command definition.
+pub(crate) struct CmdLint;
+
+impl CmdLint {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("lint")
+            .alias("check-templates")
+            .about("Lint secrets, report required fields missing from their template")
+            .arg(ArgQuery::build())
+    }
+}
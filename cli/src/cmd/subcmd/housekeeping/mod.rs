@@ -1,3 +1,6 @@
+pub(crate) mod audit;
+pub(crate) mod index;
+pub(crate) mod lint;
 pub(crate) mod recrypt;
 pub(crate) mod run;
 pub(crate) mod sync_keys;
@@ -16,6 +19,9 @@ impl CmdHousekeeping {
             .alias("housekeep")
             .alias("hk")
             .subcommand_required(true)
+            .subcommand(audit::CmdAudit::build())
+            .subcommand(index::CmdIndex::build())
+            .subcommand(lint::CmdLint::build())
             .subcommand(recrypt::CmdRecrypt::build())
             .subcommand(run::CmdRun::build())
             .subcommand(sync_keys::CmdSyncKeys::build())
@@ -1,3 +1,5 @@
+use std::num::NonZeroUsize;
+
 use clap::{Command, Arg};
 
 use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgQuery, CmdArg};
@@ -18,6 +20,23 @@ impl CmdRecrypt {
                     .conflicts_with("QUERY"),
             )
             .arg(ArgQuery::build().required_unless_present("all"))
+            .arg(
+                Arg::new("jobs")
+                    .long("jobs")
+                    .short('j')
+                    .value_name("NUM")
+                    .help("Number of secrets to re-encrypt in parallel")
+                    .long_help(
+                        "Number of secrets to re-encrypt in parallel. Defaults to the number of \
+                         CPU cores.",
+                    )
+                    .validator(|n| {
+                        n.parse::<NonZeroUsize>()
+                            .map_err(|_| "value must be a positive number")
+                            .map(|_| ())
+                            .map_err(ToString::to_string)
+                    }),
+            )
             .arg(ArgAllowDirty::build())
             .arg(ArgNoSync::build())
     }
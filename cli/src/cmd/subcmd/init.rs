@@ -1,3 +1,5 @@
+#[cfg(feature = "backend-age")]
+use clap::Arg;
 use clap::Command;
 
 use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgStore, CmdArg};
@@ -7,11 +9,20 @@ pub(crate) struct CmdInit;
 
 impl CmdInit {
     pub(crate) fn build<'a>() -> Command<'a> {
-        Command::new("init")
+        let cmd = Command::new("init")
             .alias("initialize")
             .about("Initialize new password store")
             .arg(ArgStore::build())
             .arg(ArgAllowDirty::build())
-            .arg(ArgNoSync::build())
+            .arg(ArgNoSync::build());
+
+        #[cfg(feature = "backend-age")]
+        let cmd = cmd.arg(
+            Arg::new("age")
+                .long("age")
+                .help("Use age rather than GPG to encrypt secrets"),
+        );
+
+        cmd
     }
 }
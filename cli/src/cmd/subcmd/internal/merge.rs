@@ -0,0 +1,27 @@
+use clap::{Arg, Command};
+
+/// The internal git merge driver command definition.
+pub(crate) struct CmdMerge;
+
+impl CmdMerge {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("merge")
+            .about("Merge encrypted secret files for git")
+            .arg(
+                Arg::new("BASE")
+                    .help("Encrypted common ancestor secret file")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("OURS")
+                    .help("Encrypted current secret file, merge result is written here")
+                    .required(true),
+            )
+            .arg(
+                Arg::new("THEIRS")
+                    .help("Encrypted other secret file")
+                    .required(true),
+            )
+            .arg(Arg::new("PATH").help("Secret path in the repository"))
+    }
+}
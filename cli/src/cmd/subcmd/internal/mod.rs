@@ -1,6 +1,8 @@
 #[cfg(feature = "clipboard")]
 pub(crate) mod clip_revert;
 pub(crate) mod completions;
+pub(crate) mod merge;
+pub(crate) mod textconv;
 
 use clap::Command;
 
@@ -14,7 +16,9 @@ impl CmdInternal {
             .about("Commands used by prs internally")
             .hide(true)
             .subcommand_required(true)
-            .subcommand(completions::CmdCompletions::build());
+            .subcommand(completions::CmdCompletions::build())
+            .subcommand(merge::CmdMerge::build())
+            .subcommand(textconv::CmdTextconv::build());
 
         #[cfg(feature = "clipboard")]
         {
@@ -0,0 +1,16 @@
+use clap::{Arg, Command};
+
+/// The internal git textconv command definition.
+pub(crate) struct CmdTextconv;
+
+impl CmdTextconv {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("textconv")
+            .about("Decrypt secret file for git diffs")
+            .arg(
+                Arg::new("FILE")
+                    .help("Encrypted secret file to decrypt")
+                    .required(true),
+            )
+    }
+}
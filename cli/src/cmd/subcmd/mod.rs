@@ -1,15 +1,21 @@
 pub(crate) mod add;
+#[cfg(all(feature = "agent", unix))]
+pub(crate) mod agent;
 #[cfg(feature = "alias")]
 pub(crate) mod alias;
+pub(crate) mod audit;
 pub(crate) mod clone;
 #[cfg(feature = "clipboard")]
 pub(crate) mod copy;
+pub(crate) mod diff;
 pub(crate) mod duplicate;
 pub(crate) mod edit;
+pub(crate) mod find;
 pub(crate) mod generate;
 pub(crate) mod git;
 #[cfg(feature = "grep")]
 pub(crate) mod grep;
+pub(crate) mod history;
 pub(crate) mod housekeeping;
 pub(crate) mod init;
 pub(crate) mod internal;
@@ -17,14 +23,20 @@ pub(crate) mod list;
 pub(crate) mod r#move;
 #[cfg(feature = "otp")]
 pub(crate) mod otp;
+#[cfg(feature = "pwned")]
+pub(crate) mod pwned;
 pub(crate) mod recipients;
 pub(crate) mod remove;
+pub(crate) mod restore;
 pub(crate) mod show;
 pub(crate) mod sync;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) mod tomb;
+pub(crate) mod trash;
 
 // Re-export to cmd module
+#[cfg(all(feature = "agent", unix))]
+pub(crate) use self::agent::CmdAgent;
 #[cfg(feature = "alias")]
 pub(crate) use self::alias::CmdAlias;
 #[cfg(feature = "clipboard")]
@@ -33,16 +45,22 @@ pub(crate) use self::copy::CmdCopy;
 pub(crate) use self::grep::CmdGrep;
 #[cfg(feature = "otp")]
 pub(crate) use self::otp::CmdOtp;
+#[cfg(feature = "pwned")]
+pub(crate) use self::pwned::CmdPwned;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) use self::tomb::CmdTomb;
 #[rustfmt::skip]
 pub(crate) use self::{
     add::CmdAdd,
+    audit::CmdAudit,
     clone::CmdClone,
+    diff::CmdDiff,
     duplicate::CmdDuplicate,
     edit::CmdEdit,
+    find::CmdFind,
     generate::CmdGenerate,
     git::CmdGit,
+    history::CmdHistory,
     housekeeping::CmdHousekeeping,
     init::CmdInit,
     internal::CmdInternal,
@@ -50,6 +68,8 @@ pub(crate) use self::{
     r#move::CmdMove,
     recipients::CmdRecipients,
     remove::CmdRemove,
+    restore::CmdRestore,
     show::CmdShow,
     sync::CmdSync,
+    trash::CmdTrash,
 };
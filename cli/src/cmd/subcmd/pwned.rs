@@ -0,0 +1,49 @@
+use clap::{Arg, Command};
+use prs_lib::pwned::API_URL;
+
+use crate::cmd::arg::{ArgQuery, ArgStore, CmdArg};
+
+/// The pwned command definition.
+pub(crate) struct CmdPwned;
+
+impl CmdPwned {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("pwned")
+            .about("Check passwords against Have I Been Pwned hashes")
+            .long_about(
+                "Check passwords against Have I Been Pwned hashes. Uses a local dataset, or a \
+                 k-anonymity range API which is only sent the first 5 characters of each password \
+                 hash. Exits with a non-zero code if any password is pwned.",
+            )
+            .arg(ArgQuery::build())
+            .arg(
+                Arg::new("dataset")
+                    .long("dataset")
+                    .value_name("PATH")
+                    .env("PRS_PWNED_DATASET")
+                    .help("Local dataset of pwned password hashes")
+                    .long_help(
+                        "Local dataset of pwned password SHA-1 hashes. Either a file with \
+                         'HASH:COUNT' lines ordered by hash, or a directory of range files named \
+                         by the first 5 hash characters with 'SUFFIX:COUNT' lines.",
+                    ),
+            )
+            .arg(
+                Arg::new("api")
+                    .long("api")
+                    .value_name("URL")
+                    .env("PRS_PWNED_API")
+                    .min_values(0)
+                    .max_values(1)
+                    .require_equals(true)
+                    .default_missing_value(API_URL)
+                    .help("Query a k-anonymity range API instead, Have I Been Pwned if no URL")
+                    .long_help(
+                        "Query a k-anonymity range API instead of a local dataset, at \
+                         URL/range/PREFIX. Uses the Have I Been Pwned API if no URL is given, set \
+                         a URL with '--api=URL'. Takes precedence over a dataset. Requires curl.",
+                    ),
+            )
+            .arg(ArgStore::build())
+    }
+}
@@ -4,7 +4,7 @@ pub(crate) mod generate;
 pub(crate) mod list;
 pub(crate) mod remove;
 
-use clap::Command;
+use clap::{Arg, Command};
 
 use crate::cmd::arg::{ArgStore, CmdArg};
 
@@ -27,5 +27,15 @@ impl CmdRecipients {
             .subcommand(list::CmdList::build())
             .subcommand(remove::CmdRemove::build())
             .arg(ArgStore::build())
+            .arg(
+                Arg::new("path")
+                    .long("path")
+                    .short('p')
+                    .alias("dir")
+                    .alias("folder")
+                    .value_name("SUBFOLDER")
+                    .global(true)
+                    .help("Manage recipients of a store subfolder"),
+            )
     }
 }
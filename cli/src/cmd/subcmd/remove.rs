@@ -1,4 +1,4 @@
-use clap::Command;
+use clap::{Arg, Command};
 
 use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgQuery, ArgStore, CmdArg};
 
@@ -14,6 +14,13 @@ impl CmdRemove {
             .alias("yeet")
             .about("Remove a secret")
             .arg(ArgQuery::build())
+            .arg(
+                Arg::new("permanent")
+                    .long("permanent")
+                    .short('P')
+                    .alias("no-trash")
+                    .help("Remove permanently, do not move to trash"),
+            )
             .arg(ArgStore::build())
             .arg(ArgAllowDirty::build())
             .arg(ArgNoSync::build())
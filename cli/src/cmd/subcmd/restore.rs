@@ -0,0 +1,24 @@
+use clap::Command;
+
+use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, ArgQuery, ArgRev, ArgStore, CmdArg};
+
+/// The restore command definition.
+pub(crate) struct CmdRestore;
+
+impl CmdRestore {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("restore")
+            .alias("rollback")
+            .alias("revert")
+            .about("Restore a secret from a git revision")
+            .arg(ArgQuery::build())
+            .arg(
+                ArgRev::build()
+                    .required(true)
+                    .help("Git revision to restore secret from, such as HEAD~2"),
+            )
+            .arg(ArgStore::build())
+            .arg(ArgAllowDirty::build())
+            .arg(ArgNoSync::build())
+    }
+}
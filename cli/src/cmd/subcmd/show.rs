@@ -1,6 +1,6 @@
 use clap::{Command, Arg};
 
-use crate::cmd::arg::{ArgProperty, ArgQuery, ArgStore, ArgTimeout, CmdArg};
+use crate::cmd::arg::{ArgProperty, ArgQuery, ArgRev, ArgStore, ArgTimeout, CmdArg};
 
 /// The show command definition.
 pub(crate) struct CmdShow;
@@ -23,7 +23,8 @@ impl CmdShow {
             .arg(ArgQuery::build())
             .arg(ArgStore::build())
             .arg(ArgTimeout::build().help("Timeout after which to clear output"))
-            .arg(ArgProperty::build().conflicts_with("first"));
+            .arg(ArgProperty::build().conflicts_with("first"))
+            .arg(ArgRev::build());
 
         #[cfg(feature = "clipboard")]
         let cmd = cmd.arg(
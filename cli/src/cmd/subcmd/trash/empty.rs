@@ -0,0 +1,24 @@
+use clap::{Arg, Command};
+
+use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, CmdArg};
+
+/// The trash empty command definition.
+pub(crate) struct CmdEmpty;
+
+impl CmdEmpty {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("empty")
+            .alias("clear")
+            .alias("purge")
+            .about("Permanently remove secrets from trash")
+            .arg(
+                Arg::new("older-than")
+                    .long("older-than")
+                    .short('o')
+                    .value_name("TIME")
+                    .help("Only remove secrets removed longer than TIME ago, such as 30d"),
+            )
+            .arg(ArgAllowDirty::build())
+            .arg(ArgNoSync::build())
+    }
+}
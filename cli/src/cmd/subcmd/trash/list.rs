@@ -0,0 +1,13 @@
+use clap::Command;
+
+/// The trash list command definition.
+pub(crate) struct CmdList;
+
+impl CmdList {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("list")
+            .alias("ls")
+            .alias("l")
+            .about("List secrets in trash")
+    }
+}
@@ -0,0 +1,23 @@
+pub(crate) mod empty;
+pub(crate) mod list;
+pub(crate) mod restore;
+
+use clap::Command;
+
+use crate::cmd::arg::{ArgStore, CmdArg};
+
+/// The trash command definition.
+pub(crate) struct CmdTrash;
+
+impl CmdTrash {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("trash")
+            .about("Manage removed secrets in trash")
+            .alias("bin")
+            .subcommand_required(true)
+            .subcommand(empty::CmdEmpty::build())
+            .subcommand(list::CmdList::build())
+            .subcommand(restore::CmdRestore::build())
+            .arg(ArgStore::build())
+    }
+}
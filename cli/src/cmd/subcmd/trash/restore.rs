@@ -0,0 +1,22 @@
+use clap::{Arg, Command};
+
+use crate::cmd::arg::{ArgAllowDirty, ArgNoSync, CmdArg};
+
+/// The trash restore command definition.
+pub(crate) struct CmdRestore;
+
+impl CmdRestore {
+    pub(crate) fn build<'a>() -> Command<'a> {
+        Command::new("restore")
+            .alias("undelete")
+            .alias("r")
+            .about("Restore secret from trash")
+            .arg(
+                Arg::new("NAME")
+                    .required(true)
+                    .help("Secret name or trash ID to restore, newest if ambiguous"),
+            )
+            .arg(ArgAllowDirty::build())
+            .arg(ArgNoSync::build())
+    }
+}
@@ -1,18 +1,143 @@
+#[cfg(all(feature = "agent", unix))]
+use std::sync::Mutex;
+
+use anyhow::Result;
+#[cfg(all(feature = "agent", unix))]
+use once_cell::sync::Lazy;
+
 use crate::cmd::matcher::MainMatcher;
-use prs_lib::crypto::{self, Config, Context, Proto};
+#[cfg(all(feature = "agent", unix))]
+use prs_lib::agent;
+use prs_lib::{
+    crypto::{self, prelude::*, Config, Context, ContextPool, Proto},
+    history::Revision,
+    Plaintext, Secret, Store,
+};
+
+/// Connection to the running agent, if any.
+#[cfg(all(feature = "agent", unix))]
+static AGENT: Lazy<Mutex<Option<agent::Client>>> =
+    Lazy::new(|| Mutex::new(agent::Client::connect_default()));
 
 /// Default cryptography protocol.
-const PROTO: Proto = Proto::Gpg;
+pub(crate) const PROTO: Proto = Proto::Gpg;
 
-/// Construct crypto config, respect CLI arguments.
-pub(crate) fn config(matcher_main: &MainMatcher) -> Config {
-    let mut config = Config::from(PROTO);
+/// Construct crypto config for protocol, respect CLI arguments.
+pub(crate) fn config(matcher_main: &MainMatcher, proto: Proto) -> Config {
+    let mut config = Config::from(proto);
     config.gpg_tty = matcher_main.gpg_tty();
+    #[cfg(feature = "backend-age")]
+    {
+        config.age_identity = matcher_main.age_identity();
+    }
     config
 }
 
-/// Construct crypto context, respect CLI arguments.
-pub(crate) fn context(matcher_main: &MainMatcher) -> Result<Context, crypto::Err> {
-    let config = config(matcher_main);
+/// Construct crypto context for protocol, respect CLI arguments.
+pub(crate) fn context(matcher_main: &MainMatcher, proto: Proto) -> Result<Context, crypto::Err> {
+    let config = config(matcher_main, proto);
     crypto::context(&config)
 }
+
+/// Get crypto context for protocol from the given pool, respect CLI arguments.
+///
+/// The context is initialized on first use for each protocol.
+pub(crate) fn pool_context<'a>(
+    pool: &'a mut ContextPool,
+    matcher_main: &MainMatcher,
+    proto: Proto,
+) -> Result<&'a mut Context> {
+    pool.get_mut(&config(matcher_main, proto))
+}
+
+/// Decrypt secret with a context from the given pool, respect CLI arguments.
+///
+/// If an agent is running, the plaintext is served from and stored in its
+/// cache.
+pub(crate) fn decrypt_file(
+    pool: &mut ContextPool,
+    matcher_main: &MainMatcher,
+    secret: &Secret,
+) -> Result<Plaintext> {
+    let mut decrypt =
+        || pool_context(pool, matcher_main, secret.proto())?.decrypt_file(&secret.path);
+
+    #[cfg(all(feature = "agent", unix))]
+    if matcher_main.agent() {
+        return with_agent(|agent| agent::decrypt_file(agent, &secret.path, decrypt));
+    }
+
+    decrypt()
+}
+
+/// Run the given function with the agent connection, if any.
+///
+/// The connection is taken out of its lock while the function runs, so
+/// concurrent callers never wait on each other. They go without the agent
+/// until the connection is put back, unless the function dropped it.
+#[cfg(all(feature = "agent", unix))]
+fn with_agent<T>(f: impl FnOnce(&mut Option<agent::Client>) -> T) -> T {
+    let mut client = AGENT.lock().unwrap().take();
+    let result = f(&mut client);
+    if client.is_some() {
+        *AGENT.lock().unwrap() = client;
+    }
+    result
+}
+
+/// Decrypt secret as it was in the given git revision, respect CLI arguments.
+///
+/// Past revisions are never served from or stored in the agent cache.
+pub(crate) fn decrypt_revision(
+    pool: &mut ContextPool,
+    matcher_main: &MainMatcher,
+    store: &Store,
+    revision: &Revision,
+) -> Result<Plaintext> {
+    let ciphertext = store.history().read_revision(revision)?;
+    let proto = crypto::util::proto_from_suffix(&revision.path).unwrap_or(PROTO);
+    pool_context(pool, matcher_main, proto)?.decrypt(ciphertext)
+}
+
+#[cfg(all(test, feature = "agent", unix))]
+mod tests {
+    use std::{
+        path::Path,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    #[test]
+    fn agent_decrypts_concurrently() {
+        let running = AtomicUsize::new(0);
+        let decrypt = || {
+            // Wait for the other decryption to start, fails if run serially
+            running.fetch_add(1, Ordering::SeqCst);
+            let start = Instant::now();
+            while running.load(Ordering::SeqCst) < 2 && start.elapsed() < Duration::from_secs(5) {
+                thread::yield_now();
+            }
+            let concurrent = running.load(Ordering::SeqCst) >= 2;
+            Ok(Plaintext::from(concurrent.to_string()))
+        };
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        with_agent(|agent| {
+                            agent::decrypt_file(agent, Path::new("/nonexistent.gpg"), decrypt)
+                        })
+                    })
+                })
+                .collect();
+            for worker in workers {
+                let plaintext = worker.join().unwrap().unwrap();
+                assert_eq!(plaintext.unsecure_to_str().unwrap(), "true");
+            }
+        });
+    }
+}
@@ -120,7 +120,9 @@ use std::{error, io, env};
 
 use anyhow::Result;
 use clap::{crate_description, crate_name, crate_version};
-use prs_lib::Store;
+use prs_lib::{
+    crypto::store::Err as StoreErr, sync::Err as SyncErr, template::Err as TemplateErr, Store,
+};
 
 use crate::{
     cmd::{
@@ -128,7 +130,7 @@ use crate::{
         Handler,
     },
     util::{
-        error::{quit, quit_error, ErrorHints},
+        error::{quit, quit_error, ErrorHints, ErrorHintsBuilder},
         style,
     },
 };
@@ -196,10 +198,80 @@ fn main() {
 
     // Invoke the proper action
     if let Err(err) = invoke_action(&cmd_handler) {
-        quit_error(&err, ErrorHints::default());
+        quit_error(&err, error_hints(&err));
     };
 }
 
+/// Build hints for the given error.
+///
+/// Explains how to recover from failed recipient or sync verification, and
+/// where to find templates, otherwise returns the default hints.
+fn error_hints(err: &anyhow::Error) -> ErrorHints {
+    for cause in err.chain() {
+        if let Some(StoreErr::UnsignedRecipients(path) | StoreErr::UntrustedRecipients(path)) =
+            cause.downcast_ref()
+        {
+            return ErrorHintsBuilder::default()
+                .add_info("recipients may have been tampered with, refusing to use them".into())
+                .add_info(format!(
+                    "Use '{}' to inspect recent changes to recipients",
+                    style::highlight(&format!("{} git log -p", util::bin_name()))
+                ))
+                .add_info(format!(
+                    "If the recipients are correct, sign them with a trusted key using '{}'",
+                    style::highlight(&format!(
+                        "gpg --local-user KEY --detach-sign {}",
+                        path.display()
+                    ))
+                ))
+                .verbose(false)
+                .help(false)
+                .build()
+                .unwrap();
+        }
+
+        if let Some(SyncErr::UnverifiedCommits(_)) = cause.downcast_ref() {
+            return ErrorHintsBuilder::default()
+                .add_info(
+                    "commits may have been pushed by someone else, refusing to pull them".into(),
+                )
+                .add_info(format!(
+                    "Use '{}' to inspect the commits",
+                    style::highlight(&format!(
+                        "{} git log --show-signature HEAD..@{{upstream}}",
+                        util::bin_name()
+                    ))
+                ))
+                .add_info(format!(
+                    "Use '{}' to disable verified sync",
+                    style::highlight(&format!(
+                        "{} git config {} false",
+                        util::bin_name(),
+                        prs_lib::sync::GIT_CONFIG_VERIFY_SYNC
+                    ))
+                ))
+                .verbose(false)
+                .help(false)
+                .build()
+                .unwrap();
+        }
+
+        if let Some(TemplateErr::Unknown(_)) = cause.downcast_ref() {
+            return ErrorHintsBuilder::default()
+                .add_info(format!(
+                    "Templates are YAML files in the '{}' directory of the password store",
+                    prs_lib::template::TEMPLATES_DIR
+                ))
+                .verbose(false)
+                .help(false)
+                .build()
+                .unwrap();
+        }
+    }
+
+    ErrorHints::default()
+}
+
 /// Invoke the proper action based on the CLI input.
 ///
 /// If no proper action is selected, the program will quit with an error
@@ -209,11 +281,20 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::add::Add::new(handler.matches()).invoke();
     }
 
+    #[cfg(all(feature = "agent", unix))]
+    if handler.agent().is_some() {
+        return action::agent::Agent::new(handler.matches()).invoke();
+    }
+
     #[cfg(feature = "alias")]
     if handler.alias().is_some() {
         return action::alias::Alias::new(handler.matches()).invoke();
     }
 
+    if handler.audit().is_some() {
+        return action::audit::Audit::new(handler.matches()).invoke();
+    }
+
     if handler.clone().is_some() {
         return action::clone::Clone::new(handler.matches()).invoke();
     }
@@ -223,6 +304,10 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::copy::Copy::new(handler.matches()).invoke();
     }
 
+    if handler.diff().is_some() {
+        return action::diff::Diff::new(handler.matches()).invoke();
+    }
+
     if handler.duplicate().is_some() {
         return action::duplicate::Duplicate::new(handler.matches()).invoke();
     }
@@ -231,6 +316,10 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::edit::Edit::new(handler.matches()).invoke();
     }
 
+    if handler.find().is_some() {
+        return action::find::Find::new(handler.matches()).invoke();
+    }
+
     if handler.generate().is_some() {
         return action::generate::Generate::new(handler.matches()).invoke();
     }
@@ -243,6 +332,10 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::grep::Grep::new(handler.matches()).invoke();
     }
 
+    if handler.history().is_some() {
+        return action::history::History::new(handler.matches()).invoke();
+    }
+
     if handler.housekeeping().is_some() {
         return action::housekeeping::Housekeeping::new(handler.matches()).invoke();
     }
@@ -267,6 +360,11 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::otp::Otp::new(handler.matches()).invoke();
     }
 
+    #[cfg(feature = "pwned")]
+    if handler.pwned().is_some() {
+        return action::pwned::Pwned::new(handler.matches()).invoke();
+    }
+
     if handler.recipients().is_some() {
         return action::recipients::Recipients::new(handler.matches()).invoke();
     }
@@ -275,6 +373,10 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::remove::Remove::new(handler.matches()).invoke();
     }
 
+    if handler.restore().is_some() {
+        return action::restore::Restore::new(handler.matches()).invoke();
+    }
+
     if handler.show().is_some() {
         return action::show::Show::new(handler.matches()).invoke();
     }
@@ -288,6 +390,10 @@ fn invoke_action(handler: &Handler) -> Result<()> {
         return action::tomb::Tomb::new(handler.matches()).invoke();
     }
 
+    if handler.trash().is_some() {
+        return action::trash::Trash::new(handler.matches()).invoke();
+    }
+
     // Get the main matcher
     let matcher_main = MainMatcher::with(handler.matches()).unwrap();
     if !matcher_main.quiet() {
@@ -321,7 +427,7 @@ pub fn print_main_info(matcher_main: &MainMatcher) -> ! {
             .and_then(|recip| prs_lib::crypto::recipients::contains_own_secret_key(&recip))
             .unwrap_or(false);
         if !we_own_any_recipient {
-            let config = crate::crypto::config(matcher_main);
+            let config = crate::crypto::config(matcher_main, store.proto());
             let system_has_secret = prs_lib::crypto::util::has_private_key(&config).unwrap_or(true);
             if system_has_secret {
                 eprintln!("Add your own key as recipient or generate a new one:");
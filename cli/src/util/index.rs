@@ -0,0 +1,134 @@
+use anyhow::Result;
+use thiserror::Error;
+
+use prs_lib::{
+    crypto::{util::detect_proto, ContextPool},
+    index::Index,
+    Store,
+};
+
+use crate::{cmd::matcher::MainMatcher, util::error};
+
+/// Update the search index of the given store, if it is enabled.
+///
+/// Secrets changed since the last update are indexed again, see
+/// [`Index::stale`]. This never errors, but reports an error to the user when
+/// it does, as the index can always be rebuilt.
+pub(crate) fn update(store: &Store, matcher_main: &MainMatcher) {
+    if !Index::is_enabled(store) {
+        return;
+    }
+
+    if let Err(err) = load(store, matcher_main) {
+        error::print_error(&err.context("failed to update search index, ignoring"));
+    }
+}
+
+/// Load the search index of the given store, if it is enabled.
+///
+/// The index is updated first, and saved if anything changed. An index that
+/// cannot be read is rebuilt.
+pub(crate) fn load(store: &Store, matcher_main: &MainMatcher) -> Result<Option<Index>> {
+    if !Index::is_enabled(store) {
+        return Ok(None);
+    }
+
+    let mut contexts = ContextPool::empty();
+    let proto = detect_proto(&Index::path(store));
+    let index = Index::load(
+        store,
+        crate::crypto::pool_context(&mut contexts, matcher_main, proto)?,
+    );
+    let index = match index {
+        Ok(index) => index,
+        Err(err) => {
+            if matcher_main.verbose() {
+                error::print_error(&err.context("rebuilding search index"));
+            }
+            Index::new()
+        },
+    };
+
+    refresh(store, matcher_main, &mut contexts, index).map(Some)
+}
+
+/// Rebuild the search index of the given store from scratch, enabling it.
+pub(crate) fn rebuild(store: &Store, matcher_main: &MainMatcher) -> Result<Index> {
+    refresh(store, matcher_main, &mut ContextPool::empty(), Index::new())
+}
+
+/// Load the search index of the given store, or index all secrets in memory if
+/// it is not enabled.
+///
+/// The in-memory index is not saved, building it decrypts every secret.
+pub(crate) fn load_or_scan(store: &Store, matcher_main: &MainMatcher) -> Result<Index> {
+    if let Some(index) = load(store, matcher_main)? {
+        return Ok(index);
+    }
+
+    let mut index = Index::new();
+    index_stale(store, matcher_main, &mut ContextPool::empty(), &mut index);
+    Ok(index)
+}
+
+/// Index all stale secrets in the given index, and save it if anything
+/// changed.
+fn refresh(
+    store: &Store,
+    matcher_main: &MainMatcher,
+    contexts: &mut ContextPool,
+    mut index: Index,
+) -> Result<Index> {
+    let changed = index_stale(store, matcher_main, contexts, &mut index) || index.is_empty();
+
+    if changed {
+        index
+            .save(
+                store,
+                crate::crypto::pool_context(contexts, matcher_main, store.proto())?,
+            )
+            .map_err(Err::Save)?;
+    }
+
+    Ok(index)
+}
+
+/// Index all stale secrets in the given index, returns whether anything
+/// changed.
+///
+/// Secrets that fail to decrypt are indexed without fields.
+fn index_stale(
+    store: &Store,
+    matcher_main: &MainMatcher,
+    contexts: &mut ContextPool,
+    index: &mut Index,
+) -> bool {
+    let stale = index.stale(store);
+
+    for name in &stale.removed {
+        index.forget(name);
+    }
+    for secret in &stale.changed {
+        let plaintext = match crate::crypto::decrypt_file(contexts, matcher_main, secret) {
+            Ok(plaintext) => Some(plaintext),
+            Err(err) => {
+                if matcher_main.verbose() {
+                    error::print_error(&err.context(format!(
+                        "failed to decrypt secret for search index: {}",
+                        secret.name
+                    )));
+                }
+                None
+            },
+        };
+        index.insert(store, secret, plaintext.as_ref());
+    }
+
+    !stale.changed.is_empty() || !stale.removed.is_empty()
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum Err {
+    #[error("failed to save search index")]
+    Save(#[source] anyhow::Error),
+}
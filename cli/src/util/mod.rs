@@ -4,6 +4,7 @@ pub(crate) mod clipboard;
 pub(crate) mod edit;
 pub(crate) mod error;
 pub(crate) mod fs;
+pub(crate) mod index;
 pub(crate) mod pass;
 pub(crate) mod secret;
 pub(crate) mod select;
@@ -17,7 +18,6 @@ pub(crate) mod select_skim_bin;
 pub(crate) mod stdin;
 pub(crate) mod style;
 pub(crate) mod sync;
-#[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) mod time;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) mod tomb;
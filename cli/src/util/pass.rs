@@ -11,6 +11,9 @@ const PASSWORD_CHAR_SETS: [&str; 4] = [
     "!@#$%&*+-=/[]<>(),.;|",
 ];
 
+/// Minimum length of generated passwords, one character from each set.
+pub(crate) const PASSWORD_MIN_LENGTH: usize = PASSWORD_CHAR_SETS.len();
+
 /// Generate secure random password.
 ///
 /// This generates a cryptografically secure random password string.
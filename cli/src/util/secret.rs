@@ -26,7 +26,7 @@ const SECRET_ALIAS_DEPTH: u32 = 30;
 static PROPERTY_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
         r"(?x)
-        (?P<prop>^.*)
+        (?P<prop>^.*?)   # up to the first delimiter, values may contain it
         (?:(:|\s=)\s?)   # prop: value OR prop = value
         (?P<value>.*$)
         ",
@@ -130,3 +130,21 @@ fn resolve_alias(secret: &Secret, store: &Store) -> Option<Secret> {
     }
     f(secret, store, 0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_regex_splits_at_first_delimiter() {
+        let caps = PROPERTY_REGEX.captures("url: https://example.com:8443").unwrap();
+        assert_eq!(&caps["prop"], "url");
+        assert_eq!(&caps["value"], "https://example.com:8443");
+
+        let caps = PROPERTY_REGEX.captures("key = a = b").unwrap();
+        assert_eq!(&caps["prop"], "key");
+        assert_eq!(&caps["value"], "a = b");
+
+        assert!(PROPERTY_REGEX.captures("no delimiter").is_none());
+    }
+}
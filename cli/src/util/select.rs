@@ -1,18 +1,58 @@
 use prs_lib::{
+    find::Query,
     otp::{Account, OtpFile},
-    store::FindSecret,
+    store::{FindSecret, SECRET_SUFFIX, SECRET_SUFFIX_AGE},
     Key, Secret, Store,
 };
 
+use super::error;
+use crate::cmd::matcher::MainMatcher;
+
 /// Find and select a secret in the given store.
 ///
 /// If no exact secret is found, the user will be able to choose.
 ///
 /// `None` is returned if no secret was found or selected.
 pub(crate) fn store_select_secret(store: &Store, query: Option<String>) -> Option<Secret> {
+    let parsed = Query::parse(query.as_deref());
+    select_found_secret(store.find(query), &parsed)
+}
+
+/// Find and select a secret in the given store, using the search index.
+///
+/// Like [`store_select_secret`], but URL queries also match URL fields of
+/// secrets if the search index is enabled.
+pub(crate) fn store_select_secret_indexed(
+    store: &Store,
+    matcher_main: &MainMatcher,
+    query: Option<String>,
+) -> Option<Secret> {
+    let parsed = Query::parse(query.as_deref());
+    let index = if parsed.uses_fields() {
+        super::index::load(store, matcher_main).unwrap_or_else(|err| {
+            error::print_error(&err.context("failed to load search index, ignoring"));
+            None
+        })
+    } else {
+        None
+    };
+
+    select_found_secret(
+        store.find_indexed(index.as_ref(), query.as_deref()),
+        &parsed,
+    )
+}
+
+/// Select a secret from the found secrets.
+///
+/// A single found secret is only selected automatically if it matches the
+/// query literally, the user must confirm fuzzy matches.
+///
+/// `None` is returned if no secret was found or selected.
+fn select_found_secret(found: FindSecret, query: &Query) -> Option<Secret> {
     // TODO: do not use interactive selection with --no-interact mode
     #[allow(unreachable_code)]
-    match store.find(query) {
+    match found {
         FindSecret::Exact(secret) => Some(secret),
         FindSecret::Many(secrets) => {
             // Do not show selection dialog if no secret is selected
@@ -20,6 +60,13 @@ pub(crate) fn store_select_secret(store: &Store, query: Option<String>) -> Optio
                 return None;
             }
 
+            // Return if theres just one to choose that matches literally
+            if let [secret] = secrets.as_slice() {
+                if query.is_literal_match(&secret.name) {
+                    return Some(secret.clone());
+                }
+            }
+
             #[cfg(all(feature = "select-skim", unix))]
             {
                 return super::select_skim::select_secret(&secrets).cloned();
@@ -37,6 +84,26 @@ pub(crate) fn store_select_secret(store: &Store, query: Option<String>) -> Optio
     }
 }
 
+/// Find and select a secret in the given store, including removed secrets.
+///
+/// Like [`store_select_secret`], but if the query exactly names a secret that
+/// no longer exists and has git history, that secret is returned.
+pub(crate) fn store_select_secret_history(store: &Store, query: Option<String>) -> Option<Secret> {
+    if let Some(query) = query.as_deref() {
+        if store.find_at(query).is_none() && store.check_sneaky_paths(query).is_ok() {
+            let history = store.history();
+            for suffix in &[SECRET_SUFFIX, SECRET_SUFFIX_AGE] {
+                let path = format!("{query}{suffix}");
+                if history.log(path.as_ref()).is_ok_and(|log| !log.is_empty()) {
+                    return Some(Secret::from(store, store.root.join(path)));
+                }
+            }
+        }
+    }
+
+    store_select_secret(store, query)
+}
+
 /// Select key.
 #[allow(unreachable_code)]
 pub(crate) fn select_key<'a>(keys: &'a [Key], prompt: Option<&'a str>) -> Option<&'a Key> {
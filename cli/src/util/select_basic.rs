@@ -9,11 +9,6 @@ use prs_lib::{
 
 /// Select secret.
 pub(crate) fn select_secret(secrets: &[Secret]) -> Option<&Secret> {
-    // Return if theres just one to choose
-    if secrets.len() == 1 {
-        return secrets.get(0);
-    }
-
     let map: HashMap<_, _> = secrets
         .iter()
         .map(|secret| (secret.name.clone(), secret))
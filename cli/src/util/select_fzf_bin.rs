@@ -18,11 +18,6 @@ const BIN_NAME: &str = "fzf.exe";
 
 /// Select secret.
 pub(crate) fn select_secret(secrets: &[Secret]) -> Option<&Secret> {
-    // Return if theres just one to choose
-    if secrets.len() == 1 {
-        return secrets.get(0);
-    }
-
     let map: HashMap<_, _> = secrets
         .iter()
         .map(|secret| (secret.name.clone(), secret))
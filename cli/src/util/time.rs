@@ -1,3 +1,5 @@
+use std::{convert::TryFrom, time::SystemTime};
+
 use regex::Regex;
 use thiserror::Error;
 
@@ -97,6 +99,23 @@ pub(crate) fn format_duration(mut secs: u32) -> String {
     components.join("")
 }
 
+/// Format the time elapsed since the given time in a human readable format.
+///
+/// Produces strings such as `3d4h ago`, or `just now` for times less than a
+/// second ago or in the future.
+pub(crate) fn format_elapsed(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    if secs == 0 {
+        return "just now".into();
+    }
+    format!(
+        "{} ago",
+        format_duration(u32::try_from(secs).unwrap_or(u32::MAX))
+    )
+}
+
 /// Represents a duration parsing error.
 #[derive(Debug, Error)]
 pub(crate) enum ParseDurationError {
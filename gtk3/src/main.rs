@@ -9,6 +9,8 @@ use notify_rust::Hint;
 #[cfg(all(feature = "notify", not(target_env = "musl")))]
 use notify_rust::Notification;
 
+#[cfg(all(feature = "agent", unix))]
+use prs_lib::agent;
 use prs_lib::{
     crypto::{self, prelude::*, Config, Proto},
     store::FindSecret,
@@ -217,13 +219,19 @@ fn selected_entry(
 ///
 /// Copies to clipboard with revert timeout.
 fn selected(secret: Secret, window: gtk::ApplicationWindow, input: gtk::SearchEntry) {
-    // Decrypt first line of plaintext
+    // Decrypt first line of plaintext, through agent if running
     let config = Config::from(PROTO);
-    let plaintext = match crypto::context(&config)
-        .map_err(|err| err.into())
-        .and_then(|mut context| context.decrypt_file(&secret.path))
-        .and_then(|plaintext| plaintext.first_line())
-    {
+    let decrypt = || {
+        crypto::context(&config)
+            .map_err(|err| err.into())
+            .and_then(|mut context| context.decrypt_file(&secret.path))
+    };
+    #[cfg(all(feature = "agent", unix))]
+    let plaintext =
+        agent::decrypt_file(&mut agent::Client::connect_default(), &secret.path, decrypt);
+    #[cfg(not(all(feature = "agent", unix)))]
+    let plaintext = decrypt();
+    let plaintext = match plaintext.and_then(|plaintext| plaintext.first_line()) {
         Ok(plaintext) => plaintext,
         Err(err) => {
             error_dialog(
@@ -1,11 +1,24 @@
 fn main() {
     // Crypto features warning
-    #[cfg(not(any(feature = "backend-gnupg-bin", feature = "backend-gpgme")))]
+    #[cfg(not(any(
+        feature = "backend-gnupg-bin",
+        feature = "backend-gpgme",
+        feature = "backend-rpgp",
+        feature = "backend-age"
+    )))]
     {
-        println!("cargo:warning=prs-lib: no crypto backend selected, you must set any of the following features: backend-gnupg-bin, backend-gpgme");
+        println!("cargo:warning=prs-lib: no crypto backend selected, you must set any of the following features: backend-gnupg-bin, backend-gpgme, backend-rpgp, backend-age");
     }
 
     // GPG cryptography
-    #[cfg(any(feature = "backend-gpgme", feature = "backend-gnupg-bin"))]
+    #[cfg(any(
+        feature = "backend-gpgme",
+        feature = "backend-gnupg-bin",
+        feature = "backend-rpgp"
+    ))]
     println!("cargo:rustc-cfg=feature=\"_crypto-gpg\"");
+
+    // age cryptography
+    #[cfg(feature = "backend-age")]
+    println!("cargo:rustc-cfg=feature=\"_crypto-age\"");
 }
@@ -0,0 +1,207 @@
+//! Agent caching decrypted secrets.
+//!
+//! The agent is a daemon listening on a per-user Unix socket. It holds
+//! decrypted plaintext in locked memory for a limited time, and flushes it when
+//! the session locks or the system suspends.
+//!
+//! The agent never decrypts anything itself. Clients ask it for a secret before
+//! decrypting, and hand it the plaintext after decrypting on a miss, see
+//! [`decrypt_file`]. Passphrase prompts therefore stay with the client.
+
+pub mod proto;
+pub mod server;
+
+use std::{
+    env, io,
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Result;
+use thiserror::Error;
+
+pub use proto::{FileStamp, Request, Response, Status};
+pub use server::Server;
+
+use crate::Plaintext;
+
+/// Environment variable to override the agent socket path with.
+pub const AGENT_SOCKET_ENV: &str = "PRS_AGENT_SOCKET";
+
+/// Agent socket file name.
+pub const AGENT_SOCKET_FILE: &str = "agent.sock";
+
+/// Timeout for agent responses.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Get the agent socket path for the current user.
+///
+/// This is `$PRS_AGENT_SOCKET` if set, or `agent.sock` in a `prs` directory in
+/// `$XDG_RUNTIME_DIR`. Falls back to a per-user directory in the temporary
+/// directory.
+#[must_use]
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = env::var_os(AGENT_SOCKET_ENV) {
+        return path.into();
+    }
+    env::var_os("XDG_RUNTIME_DIR")
+        .map_or_else(
+            || env::temp_dir().join(format!("prs-{}", nix::unistd::getuid())),
+            |dir| PathBuf::from(dir).join("prs"),
+        )
+        .join(AGENT_SOCKET_FILE)
+}
+
+/// Agent client.
+pub struct Client {
+    stream: UnixStream,
+}
+
+impl Client {
+    /// Connect to the agent listening on the given socket.
+    pub fn connect<P: AsRef<Path>>(socket: P) -> Result<Self> {
+        let stream = UnixStream::connect(socket).map_err(Err::Connect)?;
+        stream
+            .set_read_timeout(Some(CLIENT_TIMEOUT))
+            .map_err(Err::Connect)?;
+        Ok(Self { stream })
+    }
+
+    /// Connect to the agent of the current user, if it is running.
+    #[must_use]
+    pub fn connect_default() -> Option<Self> {
+        Self::connect(socket_path()).ok()
+    }
+
+    /// Send a request to the agent and wait for its response.
+    pub fn request(&mut self, request: &Request) -> Result<Response> {
+        request.write_to(&mut self.stream).map_err(Err::Protocol)?;
+        match Response::read_from(&mut self.stream).map_err(Err::Protocol)? {
+            Response::Error(msg) => Err(Err::Agent(msg).into()),
+            response => Ok(response),
+        }
+    }
+
+    /// Get cached plaintext for a secret file.
+    ///
+    /// Returns `None` if it is not cached, or if the file changed since it was
+    /// cached.
+    pub fn get(&mut self, path: &Path, stamp: FileStamp) -> Result<Option<Plaintext>> {
+        let path = path.canonicalize().map_err(Err::Stamp)?;
+        match self.request(&Request::Get { path, stamp })? {
+            Response::Plaintext(plaintext) => Ok(Some(plaintext)),
+            Response::Miss => Ok(None),
+            _ => Err(Err::Unexpected.into()),
+        }
+    }
+
+    /// Cache plaintext for a secret file.
+    ///
+    /// The stamp must be taken before the file was decrypted.
+    pub fn put(&mut self, path: &Path, stamp: FileStamp, plaintext: Plaintext) -> Result<()> {
+        let path = path.canonicalize().map_err(Err::Stamp)?;
+        self.request_ok(&Request::Put {
+            path,
+            stamp,
+            plaintext,
+        })
+    }
+
+    /// Flush all cached plaintext.
+    pub fn flush(&mut self) -> Result<()> {
+        self.request_ok(&Request::Flush)
+    }
+
+    /// Get the agent status.
+    pub fn status(&mut self) -> Result<Status> {
+        match self.request(&Request::Status)? {
+            Response::Status(status) => Ok(status),
+            _ => Err(Err::Unexpected.into()),
+        }
+    }
+
+    /// Flush all cached plaintext and stop the agent.
+    pub fn stop(&mut self) -> Result<()> {
+        self.request_ok(&Request::Stop)
+    }
+
+    /// Send a request the agent must respond to with `Ok`.
+    fn request_ok(&mut self, request: &Request) -> Result<()> {
+        match self.request(request)? {
+            Response::Ok => Ok(()),
+            _ => Err(Err::Unexpected.into()),
+        }
+    }
+}
+
+/// Decrypt a secret file through the agent cache.
+///
+/// Returns the cached plaintext if the agent has it. Otherwise the file is
+/// decrypted with `decrypt`, and the agent caches the result. If the agent
+/// fails, it is dropped and the file is decrypted without it.
+pub fn decrypt_file<F>(agent: &mut Option<Client>, path: &Path, decrypt: F) -> Result<Plaintext>
+where
+    F: FnOnce() -> Result<Plaintext>,
+{
+    let stamp = match agent {
+        Some(_) => FileStamp::of(path).ok(),
+        None => None,
+    };
+
+    if let (Some(client), Some(stamp)) = (agent.as_mut(), stamp) {
+        match client.get(path, stamp) {
+            Ok(Some(plaintext)) => return Ok(plaintext),
+            Ok(None) => {},
+            Err(err) => {
+                tracing::warn!("dropping agent connection: {:?}", err);
+                *agent = None;
+            },
+        }
+    }
+
+    let plaintext = decrypt()?;
+
+    if let (Some(client), Some(stamp)) = (agent.as_mut(), stamp) {
+        if let Err(err) = client.put(path, stamp, plaintext.clone()) {
+            tracing::warn!("dropping agent connection: {:?}", err);
+            *agent = None;
+        }
+    }
+
+    Ok(plaintext)
+}
+
+/// Agent error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to connect to agent")]
+    Connect(#[source] io::Error),
+
+    #[error("failed to communicate with agent")]
+    Protocol(#[source] io::Error),
+
+    #[error("agent failed: {}", _0)]
+    Agent(String),
+
+    #[error("agent sent unexpected response")]
+    Unexpected,
+
+    #[error("failed to read secret file metadata")]
+    Stamp(#[source] io::Error),
+
+    #[error("failed to prepare agent socket directory")]
+    SocketDir(#[source] io::Error),
+
+    #[error("agent socket directory is accessible by other users: {}", _0.display())]
+    InsecureSocketDir(PathBuf),
+
+    #[error("agent is already running")]
+    Running,
+
+    #[error("failed to bind agent socket")]
+    Bind(#[source] io::Error),
+
+    #[error("failed to watch for session lock and system suspend")]
+    Watch(#[source] io::Error),
+}
@@ -0,0 +1,464 @@
+//! Agent wire protocol.
+//!
+//! Each message is a tag byte followed by its fields. Integers are big endian
+//! `u64`, byte strings are prefixed with their length as integer. A connection
+//! carries any number of requests, each answered by exactly one response.
+
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, Read, Write},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use zeroize::Zeroizing;
+
+use crate::Plaintext;
+
+/// Maximum length of a byte string in a message.
+const MAX_LEN: u64 = 16 * 1024 * 1024;
+
+const TAG_GET: u8 = 1;
+const TAG_PUT: u8 = 2;
+const TAG_FLUSH: u8 = 3;
+const TAG_STATUS: u8 = 4;
+const TAG_STOP: u8 = 5;
+
+const TAG_OK: u8 = 128;
+const TAG_MISS: u8 = 129;
+const TAG_PLAINTEXT: u8 = 130;
+const TAG_STATUS_INFO: u8 = 131;
+const TAG_ERROR: u8 = 132;
+
+/// Modification stamp of a secret file.
+///
+/// Cached plaintext is only served if the stamp of its file is still the same,
+/// so it is invalidated when the file changes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileStamp {
+    /// Modification time in nanoseconds since the Unix epoch.
+    modified: u64,
+
+    /// File size in bytes.
+    len: u64,
+}
+
+impl FileStamp {
+    /// Get the current stamp of the given file.
+    pub fn of<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        Ok(Self {
+            modified,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Agent request.
+pub enum Request {
+    /// Get cached plaintext for a secret file.
+    Get { path: PathBuf, stamp: FileStamp },
+
+    /// Cache plaintext for a secret file.
+    Put {
+        path:      PathBuf,
+        stamp:     FileStamp,
+        plaintext: Plaintext,
+    },
+
+    /// Flush all cached plaintext.
+    Flush,
+
+    /// Get agent status.
+    Status,
+
+    /// Flush and stop the agent.
+    Stop,
+}
+
+impl Request {
+    /// Write request to the given writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = match self {
+            Self::Get { path, .. } => message(path.as_os_str().len()),
+            Self::Put {
+                path, plaintext, ..
+            } => message(path.as_os_str().len() + plaintext.unsecure_ref().len()),
+            _ => message(0),
+        };
+        match self {
+            Self::Get { path, stamp } => {
+                buf.push(TAG_GET);
+                put_path(&mut buf, path);
+                put_stamp(&mut buf, *stamp);
+            },
+            Self::Put {
+                path,
+                stamp,
+                plaintext,
+            } => {
+                buf.push(TAG_PUT);
+                put_path(&mut buf, path);
+                put_stamp(&mut buf, *stamp);
+                put_bytes(&mut buf, plaintext.unsecure_ref());
+            },
+            Self::Flush => buf.push(TAG_FLUSH),
+            Self::Status => buf.push(TAG_STATUS),
+            Self::Stop => buf.push(TAG_STOP),
+        }
+        writer.write_all(&buf)?;
+        writer.flush()
+    }
+
+    /// Read request from the given reader.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(match read_u8(reader)? {
+            TAG_GET => Self::Get {
+                path:  read_path(reader)?,
+                stamp: read_stamp(reader)?,
+            },
+            TAG_PUT => Self::Put {
+                path:      read_path(reader)?,
+                stamp:     read_stamp(reader)?,
+                plaintext: read_plaintext(reader)?,
+            },
+            TAG_FLUSH => Self::Flush,
+            TAG_STATUS => Self::Status,
+            TAG_STOP => Self::Stop,
+            tag => return Err(invalid(format!("unknown request tag {tag}"))),
+        })
+    }
+}
+
+/// Agent response.
+pub enum Response {
+    /// Request succeeded.
+    Ok,
+
+    /// No cached plaintext for requested file.
+    Miss,
+
+    /// Cached plaintext for requested file.
+    Plaintext(Plaintext),
+
+    /// Agent status.
+    Status(Status),
+
+    /// Request failed with the given message.
+    Error(String),
+}
+
+impl Response {
+    /// Write response to the given writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = match self {
+            Self::Plaintext(plaintext) => message(plaintext.unsecure_ref().len()),
+            Self::Error(msg) => message(msg.len()),
+            _ => message(0),
+        };
+        match self {
+            Self::Ok => buf.push(TAG_OK),
+            Self::Miss => buf.push(TAG_MISS),
+            Self::Plaintext(plaintext) => {
+                buf.push(TAG_PLAINTEXT);
+                put_bytes(&mut buf, plaintext.unsecure_ref());
+            },
+            Self::Status(status) => {
+                buf.push(TAG_STATUS_INFO);
+                put_u64(&mut buf, status.entries);
+                put_u64(&mut buf, status.ttl.as_secs());
+                put_u64(&mut buf, status.pid.into());
+            },
+            Self::Error(msg) => {
+                buf.push(TAG_ERROR);
+                put_bytes(&mut buf, msg.as_bytes());
+            },
+        }
+        writer.write_all(&buf)?;
+        writer.flush()
+    }
+
+    /// Read response from the given reader.
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(match read_u8(reader)? {
+            TAG_OK => Self::Ok,
+            TAG_MISS => Self::Miss,
+            TAG_PLAINTEXT => Self::Plaintext(read_plaintext(reader)?),
+            TAG_STATUS_INFO => Self::Status(Status {
+                entries: read_u64(reader)?,
+                ttl:     Duration::from_secs(read_u64(reader)?),
+                pid:     read_u64(reader)? as u32,
+            }),
+            TAG_ERROR => Self::Error(String::from_utf8_lossy(&read_bytes(reader)?).into()),
+            tag => return Err(invalid(format!("unknown response tag {tag}"))),
+        })
+    }
+}
+
+/// Agent status.
+#[derive(Clone, Copy, Debug)]
+pub struct Status {
+    /// Number of cached secrets.
+    pub entries: u64,
+
+    /// Time plaintext is cached for.
+    pub ttl: Duration,
+
+    /// Process ID of the agent.
+    pub pid: u32,
+}
+
+/// Allocate a buffer for a message with variable length fields of the given
+/// size.
+///
+/// The buffer is zeroed when dropped. It is allocated up front, so it is never
+/// reallocated leaving copies of plaintext behind.
+fn message(fields_len: usize) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(Vec::with_capacity(64 + fields_len))
+}
+
+fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    put_u64(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_path(buf: &mut Vec<u8>, path: &Path) {
+    put_bytes(buf, path.as_os_str().as_bytes());
+}
+
+fn put_stamp(buf: &mut Vec<u8>, stamp: FileStamp) {
+    put_u64(buf, stamp.modified);
+    put_u64(buf, stamp.len);
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Read a byte string.
+///
+/// The returned buffer is zeroed when dropped, it may hold plaintext.
+fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Zeroizing<Vec<u8>>> {
+    let len = read_u64(reader)?;
+    if len > MAX_LEN {
+        return Err(invalid(format!("message field of {len} bytes too large")));
+    }
+    let mut bytes = Zeroizing::new(vec![0; len as usize]);
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_plaintext<R: Read>(reader: &mut R) -> io::Result<Plaintext> {
+    Ok(std::mem::take(&mut *read_bytes(reader)?).into())
+}
+
+fn read_path<R: Read>(reader: &mut R) -> io::Result<PathBuf> {
+    Ok(OsStr::from_bytes(&read_bytes(reader)?).into())
+}
+
+fn read_stamp<R: Read>(reader: &mut R) -> io::Result<FileStamp> {
+    Ok(FileStamp {
+        modified: read_u64(reader)?,
+        len:      read_u64(reader)?,
+    })
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAMP: FileStamp = FileStamp {
+        modified: 1_234_567_890_123_456_789,
+        len:      42,
+    };
+
+    /// Write and read back the given request.
+    fn round_trip_request(request: &Request) -> Request {
+        let mut buf = vec![];
+        request.write_to(&mut buf).unwrap();
+        let mut reader = buf.as_slice();
+        let request = Request::read_from(&mut reader).unwrap();
+        assert!(reader.is_empty(), "request not fully read");
+        request
+    }
+
+    /// Write and read back the given response.
+    fn round_trip_response(response: &Response) -> Response {
+        let mut buf = vec![];
+        response.write_to(&mut buf).unwrap();
+        let mut reader = buf.as_slice();
+        let response = Response::read_from(&mut reader).unwrap();
+        assert!(reader.is_empty(), "response not fully read");
+        response
+    }
+
+    #[test]
+    fn request_round_trip() {
+        let path = PathBuf::from("/store/dir/secret.gpg");
+
+        match round_trip_request(&Request::Get {
+            path:  path.clone(),
+            stamp: STAMP,
+        }) {
+            Request::Get { path: p, stamp } => {
+                assert_eq!(p, path);
+                assert_eq!(stamp, STAMP);
+            },
+            _ => panic!("expected get request"),
+        }
+
+        match round_trip_request(&Request::Put {
+            path:      path.clone(),
+            stamp:     STAMP,
+            plaintext: "secret\nuser: me".into(),
+        }) {
+            Request::Put {
+                path: p,
+                stamp,
+                plaintext,
+            } => {
+                assert_eq!(p, path);
+                assert_eq!(stamp, STAMP);
+                assert_eq!(plaintext.unsecure_ref(), b"secret\nuser: me");
+            },
+            _ => panic!("expected put request"),
+        }
+
+        match round_trip_request(&Request::Put {
+            path:      PathBuf::new(),
+            stamp:     STAMP,
+            plaintext: Plaintext::empty(),
+        }) {
+            Request::Put {
+                path, plaintext, ..
+            } => {
+                assert_eq!(path, PathBuf::new());
+                assert!(plaintext.unsecure_ref().is_empty());
+            },
+            _ => panic!("expected put request"),
+        }
+
+        assert!(matches!(
+            round_trip_request(&Request::Flush),
+            Request::Flush
+        ));
+        assert!(matches!(
+            round_trip_request(&Request::Status),
+            Request::Status
+        ));
+        assert!(matches!(round_trip_request(&Request::Stop), Request::Stop));
+    }
+
+    #[test]
+    fn response_round_trip() {
+        assert!(matches!(round_trip_response(&Response::Ok), Response::Ok));
+        assert!(matches!(
+            round_trip_response(&Response::Miss),
+            Response::Miss
+        ));
+
+        match round_trip_response(&Response::Plaintext("secret".into())) {
+            Response::Plaintext(plaintext) => assert_eq!(plaintext.unsecure_ref(), b"secret"),
+            _ => panic!("expected plaintext response"),
+        }
+
+        match round_trip_response(&Response::Status(Status {
+            entries: 3,
+            ttl:     Duration::from_secs(600),
+            pid:     u32::MAX,
+        })) {
+            Response::Status(status) => {
+                assert_eq!(status.entries, 3);
+                assert_eq!(status.ttl, Duration::from_secs(600));
+                assert_eq!(status.pid, u32::MAX);
+            },
+            _ => panic!("expected status response"),
+        }
+
+        match round_trip_response(&Response::Error("failed to decrypt".into())) {
+            Response::Error(msg) => assert_eq!(msg, "failed to decrypt"),
+            _ => panic!("expected error response"),
+        }
+    }
+
+    #[test]
+    fn unknown_tag_is_invalid() {
+        let err = Request::read_from(&mut [TAG_OK].as_slice()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = Response::read_from(&mut [TAG_GET].as_slice())
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn oversized_length_is_rejected() {
+        // Rejected before allocating, reading a field this large would fail
+        // with an unexpected EOF instead
+        for len in [MAX_LEN + 1, u64::MAX] {
+            let mut buf = vec![TAG_PLAINTEXT];
+            put_u64(&mut buf, len);
+            let err = Response::read_from(&mut buf.as_slice()).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+            let mut buf = vec![TAG_GET];
+            put_u64(&mut buf, len);
+            let err = Request::read_from(&mut buf.as_slice()).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn truncated_frame_is_error() {
+        let mut buf = vec![];
+        Request::Put {
+            path:      "/store/secret.gpg".into(),
+            stamp:     STAMP,
+            plaintext: "secret".into(),
+        }
+        .write_to(&mut buf)
+        .unwrap();
+
+        // Every strict prefix of a frame must fail, including the empty one
+        for len in 0..buf.len() {
+            let err = Request::read_from(&mut &buf[..len]).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof, "prefix {len}");
+        }
+
+        let mut buf = vec![];
+        Response::Status(Status {
+            entries: 1,
+            ttl:     Duration::from_secs(1),
+            pid:     1,
+        })
+        .write_to(&mut buf)
+        .unwrap();
+        for len in 0..buf.len() {
+            let err = Response::read_from(&mut &buf[..len]).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof, "prefix {len}");
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Agent server, caching plaintext for its clients.
+
+use std::{
+    collections::HashMap,
+    fs::{self, DirBuilder},
+    io::{self, BufRead, BufReader},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use nix::{
+    sys::resource::{setrlimit, Resource},
+    unistd::getuid,
+};
+
+use super::{proto::Status, Err, FileStamp, Request, Response};
+use crate::Plaintext;
+
+/// Default time to cache plaintext for.
+pub const DEFAULT_TTL: Duration = Duration::from_mins(5);
+
+/// Interval to purge expired plaintext at.
+const PURGE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// dbus-monitor binary.
+pub const DBUS_MONITOR_BIN: &str = "dbus-monitor";
+
+/// D-Bus match rules on the system bus to flush on, for system suspend and
+/// session lock.
+const FLUSH_RULES_SYSTEM: &[&str] = &[
+    "type='signal',interface='org.freedesktop.login1.Manager',member='PrepareForSleep'",
+    "type='signal',interface='org.freedesktop.login1.Session',member='Lock'",
+];
+
+/// D-Bus match rules on the session bus to flush on, for screen saver
+/// activation.
+const FLUSH_RULES_SESSION: &[&str] = &[
+    "type='signal',interface='org.freedesktop.ScreenSaver',member='ActiveChanged'",
+    "type='signal',interface='org.gnome.ScreenSaver',member='ActiveChanged'",
+];
+
+/// D-Bus signal members matched by the flush rules.
+const FLUSH_MEMBERS: &[&str] = &["PrepareForSleep", "Lock", "ActiveChanged"];
+
+/// Agent server.
+pub struct Server {
+    /// Listener on the agent socket.
+    listener: UnixListener,
+
+    /// Path of the agent socket.
+    socket: PathBuf,
+
+    /// The plaintext cache.
+    cache: Arc<Cache>,
+
+    /// Whether a client requested the agent to stop.
+    stop: Arc<AtomicBool>,
+
+    /// Running `dbus-monitor` processes to flush on.
+    watchers: Vec<Child>,
+}
+
+impl Server {
+    /// Bind the agent server to the given socket, caching plaintext for the
+    /// given time.
+    ///
+    /// The socket directory is created accessible by the current user only, an
+    /// existing directory must be as well. A stale socket is replaced, but
+    /// this fails if an agent is already listening on it.
+    pub fn bind(socket: PathBuf, ttl: Duration) -> Result<Self> {
+        if let Some(dir) = socket.parent() {
+            prepare_socket_dir(dir)?;
+        }
+        if socket.exists() {
+            if UnixStream::connect(&socket).is_ok() {
+                return Err(Err::Running.into());
+            }
+            fs::remove_file(&socket).map_err(Err::Bind)?;
+        }
+        let listener = UnixListener::bind(&socket).map_err(Err::Bind)?;
+        fs::set_permissions(&socket, fs::Permissions::from_mode(0o600)).map_err(Err::Bind)?;
+
+        // Never write cached plaintext to disk in a core dump
+        if let Err(err) = setrlimit(Resource::RLIMIT_CORE, Some(0), Some(0)) {
+            tracing::warn!("failed to disable core dumps: {}", err);
+        }
+
+        Ok(Self {
+            listener,
+            socket,
+            cache: Arc::new(Cache::new(ttl)),
+            stop: Arc::new(AtomicBool::new(false)),
+            watchers: vec![],
+        })
+    }
+
+    /// Flush cached plaintext when the session locks or the system suspends.
+    ///
+    /// Watches logind and screen saver signals on D-Bus using `dbus-monitor`.
+    /// Fails if it is not available. A bus that is not available is
+    /// silently ignored.
+    pub fn flush_on_lock(&mut self) -> Result<()> {
+        for (bus, rules) in [
+            ("--system", FLUSH_RULES_SYSTEM),
+            ("--session", FLUSH_RULES_SESSION),
+        ] {
+            let mut command = Command::new(DBUS_MONITOR_BIN);
+            command
+                .arg(bus)
+                .args(rules)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null());
+
+            // Stop watcher along with the agent, even if the agent is killed
+            #[cfg(target_os = "linux")]
+            unsafe {
+                use nix::libc::{prctl, PR_SET_PDEATHSIG, SIGTERM};
+                use std::os::unix::process::CommandExt;
+                command.pre_exec(|| {
+                    prctl(PR_SET_PDEATHSIG, SIGTERM);
+                    Ok(())
+                });
+            }
+
+            let mut watcher = command.spawn().map_err(Err::Watch)?;
+            let stdout = watcher.stdout.take().unwrap();
+            let cache = Arc::clone(&self.cache);
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if is_flush_signal(&line) {
+                        tracing::debug!("flushing agent cache on signal: {}", line);
+                        cache.flush();
+                    }
+                }
+            });
+            self.watchers.push(watcher);
+        }
+        Ok(())
+    }
+
+    /// Serve clients until one requests the agent to stop.
+    pub fn serve(mut self) -> Result<()> {
+        // Purge expired plaintext in the background
+        let (cache, stop) = (Arc::clone(&self.cache), Arc::clone(&self.stop));
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                thread::sleep(PURGE_INTERVAL);
+                cache.purge();
+            }
+        });
+
+        for stream in self.listener.incoming() {
+            if self.stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    tracing::warn!("failed to accept agent client: {}", err);
+                    continue;
+                },
+            };
+            let (cache, stop) = (Arc::clone(&self.cache), Arc::clone(&self.stop));
+            let socket = self.socket.clone();
+            thread::spawn(move || {
+                if let Err(err) = handle(stream, &cache, &stop, &socket) {
+                    tracing::debug!("agent client failed: {}", err);
+                }
+            });
+        }
+
+        self.cache.flush();
+        for watcher in &mut self.watchers {
+            watcher.kill().ok();
+            watcher.wait().ok();
+        }
+        fs::remove_file(&self.socket).ok();
+        Ok(())
+    }
+}
+
+/// Plaintext cache, entries expire after a fixed time.
+struct Cache {
+    /// Time to cache plaintext for.
+    ttl: Duration,
+
+    /// Cached plaintext by canonical secret file path.
+    entries: Mutex<HashMap<PathBuf, Entry>>,
+}
+
+/// Cached plaintext of a secret file.
+struct Entry {
+    /// Stamp of the secret file the plaintext was decrypted from.
+    stamp: FileStamp,
+
+    /// The plaintext, in locked memory.
+    plaintext: Plaintext,
+
+    /// Time after which the plaintext is purged.
+    expires: Instant,
+}
+
+impl Cache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get cached plaintext, if it is not expired and the file did not change.
+    fn get(&self, path: &Path, stamp: FileStamp) -> Option<Plaintext> {
+        self.entries()
+            .get(path)
+            .filter(|entry| entry.stamp == stamp && entry.expires > Instant::now())
+            .map(|entry| entry.plaintext.clone())
+    }
+
+    fn put(&self, path: PathBuf, stamp: FileStamp, plaintext: Plaintext) {
+        let expires = Instant::now() + self.ttl;
+        self.entries().insert(path, Entry {
+            stamp,
+            plaintext,
+            expires,
+        });
+    }
+
+    fn flush(&self) {
+        self.entries().clear();
+    }
+
+    /// Drop all expired plaintext.
+    fn purge(&self) {
+        let now = Instant::now();
+        self.entries().retain(|_, entry| entry.expires > now);
+    }
+
+    fn status(&self) -> Status {
+        Status {
+            entries: self.entries().len() as u64,
+            ttl:     self.ttl,
+            pid:     std::process::id(),
+        }
+    }
+
+    /// Lock the cache entries.
+    ///
+    /// A poisoned lock is recovered from, entries are always left in a
+    /// consistent state.
+    fn entries(&self) -> MutexGuard<'_, HashMap<PathBuf, Entry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handle requests of a connected client until it disconnects.
+fn handle(
+    mut stream: UnixStream,
+    cache: &Cache,
+    stop: &AtomicBool,
+    socket: &Path,
+) -> io::Result<()> {
+    if !is_current_user(&stream)? {
+        return Response::Error("client is not owned by agent user".into()).write_to(&mut stream);
+    }
+
+    loop {
+        let request = match Request::read_from(&mut stream) {
+            Ok(request) => request,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let response = match request {
+            Request::Get { path, stamp } => cache
+                .get(&path, stamp)
+                .map_or(Response::Miss, Response::Plaintext),
+            Request::Put {
+                path,
+                stamp,
+                plaintext,
+            } => {
+                cache.put(path, stamp, plaintext);
+                Response::Ok
+            },
+            Request::Flush => {
+                cache.flush();
+                Response::Ok
+            },
+            Request::Status => Response::Status(cache.status()),
+            Request::Stop => {
+                cache.flush();
+                stop.store(true, Ordering::SeqCst);
+                Response::Ok.write_to(&mut stream)?;
+
+                // Wake up listener to notice the stop request
+                UnixStream::connect(socket).ok();
+                return Ok(());
+            },
+        };
+        response.write_to(&mut stream)?;
+    }
+}
+
+/// Check whether the peer of the given stream runs as the current user.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn is_current_user(stream: &UnixStream) -> io::Result<bool> {
+    use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+    use std::os::unix::io::AsRawFd;
+
+    let credentials = getsockopt(stream.as_raw_fd(), PeerCredentials)?;
+    Ok(credentials.uid() == getuid().as_raw())
+}
+
+/// Check whether the peer of the given stream runs as the current user.
+///
+/// Peer credentials are not checked on this platform, the socket directory
+/// permissions are relied on instead.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn is_current_user(_stream: &UnixStream) -> io::Result<bool> {
+    Ok(true)
+}
+
+/// Check whether a `dbus-monitor` output line is a signal to flush on.
+fn is_flush_signal(line: &str) -> bool {
+    line.starts_with("signal ")
+        && FLUSH_MEMBERS
+            .iter()
+            .any(|member| line.contains(&format!("member={member}")))
+}
+
+/// Prepare the agent socket directory.
+///
+/// Creates it accessible by the current user only, or checks an existing
+/// directory is.
+fn prepare_socket_dir(dir: &Path) -> Result<()> {
+    if !dir.exists() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(Err::SocketDir)?;
+    }
+    let metadata = fs::metadata(dir).map_err(Err::SocketDir)?;
+    if metadata.uid() != getuid().as_raw() || metadata.mode() & 0o077 != 0 {
+        return Err(Err::InsecureSocketDir(dir.into()).into());
+    }
+    Ok(())
+}
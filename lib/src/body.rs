@@ -0,0 +1,859 @@
+//! Structured secret body.
+//!
+//! A secret body is the password on the first line, followed by any number of
+//! lines. Lines in the form `Key: value` are fields, other lines are kept as
+//! free text. Keys are matched case-insensitively and may be repeated.
+//!
+//! A field value spans multiple lines if the lines following it are indented:
+//!
+//! ```text
+//! hunter2
+//! User: alice
+//! Notes: first line
+//!   second line
+//! Cert:
+//!   -----BEGIN CERTIFICATE-----
+//!   ...
+//!   -----END CERTIFICATE-----
+//! ```
+//!
+//! A `---` line may start a YAML document holding nested data, all lines after
+//! it are part of the document:
+//!
+//! ```text
+//! hunter2
+//! ---
+//! api:
+//!   scopes: [read, write]
+//!   endpoints:
+//!     - url: https://example.com
+//! ```
+//!
+//! Properties are looked up in fields first, then in the YAML document. Nested
+//! keys and sequence indices in a property path are separated by `.`, such as
+//! `api.endpoints.0.url`.
+//!
+//! Parsing is lossless, a body that is not modified renders back into the exact
+//! same plaintext.
+
+use anyhow::Result;
+use serde_yaml::{Mapping, Value};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::types::{Plaintext, NEWLINE, PROPERTY_DELIMITER};
+
+/// Indentation for continuation lines of multi-line values.
+const INDENT: &str = "  ";
+
+/// Line starting a YAML document.
+const DOCUMENT_SEPARATOR: &str = "---";
+
+/// Separator of nested keys in property paths.
+pub const PATH_SEPARATOR: char = '.';
+
+/// Structured secret body.
+pub struct SecretBody {
+    /// The password line.
+    password: Line,
+
+    /// Lines after the password, grouped in fields and free text.
+    items: Vec<Item>,
+
+    /// YAML document lines, starting with the separator. Empty if there is no
+    /// document.
+    document: Vec<Line>,
+
+    /// Newline used for new lines, the first newline in the parsed plaintext.
+    newline: &'static str,
+
+    /// Newline the plaintext ends with, if any.
+    trailing: &'static str,
+}
+
+impl SecretBody {
+    /// Parse secret body from the given plaintext.
+    pub fn parse(plaintext: &Plaintext) -> Result<Self> {
+        let text = plaintext.unsecure_to_str().map_err(Err::Utf8)?;
+        let mut lines = text.split_inclusive('\n').map(Line::parse);
+        let password = lines.next().unwrap_or_default();
+        let mut lines: Vec<Line> = lines.collect();
+
+        let newline = std::iter::once(&password)
+            .chain(&lines)
+            .map(|line| line.end)
+            .find(|end| !end.is_empty())
+            .unwrap_or(NEWLINE);
+        let trailing = lines.last().unwrap_or(&password).end;
+
+        // Split off YAML document, only if it is valid
+        let document = lines
+            .iter()
+            .position(|line| *line.text == DOCUMENT_SEPARATOR)
+            .filter(|&i| parse_document(&lines[i + 1..]).is_ok())
+            .map_or_else(Vec::new, |i| lines.split_off(i));
+
+        // Group lines into fields with their continuation lines, and free text
+        let mut items = vec![];
+        let mut i = 0;
+        while i < lines.len() {
+            if !lines[i].is_field() {
+                items.push(Item::Text(lines[i].clone()));
+                i += 1;
+                continue;
+            }
+
+            // Blank lines only continue a value if more continuation follows
+            let mut end = i + 1;
+            loop {
+                let next = lines[end..]
+                    .iter()
+                    .position(|line| !line.is_blank())
+                    .map_or(lines.len(), |blank| end + blank);
+                if !lines.get(next).is_some_and(Line::is_continuation) {
+                    break;
+                }
+                end = next + 1;
+            }
+            items.push(Item::Field(Field::from_lines(lines[i..end].to_vec())));
+            i = end;
+        }
+
+        Ok(Self {
+            password,
+            items,
+            document,
+            newline,
+            trailing,
+        })
+    }
+
+    /// Render the secret body into plaintext.
+    #[must_use]
+    pub fn to_plaintext(&self) -> Plaintext {
+        self.render(std::iter::once(&self.password).chain(self.lines()))
+    }
+
+    /// Get the password, the first line.
+    #[must_use]
+    pub fn password(&self) -> &str {
+        &self.password.text
+    }
+
+    /// Set the password, the first line.
+    ///
+    /// Only the first line of the given password is used.
+    pub fn set_password(&mut self, password: &str) {
+        self.password.text = password
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_owned()
+            .into();
+    }
+
+    /// Get all lines except the password as plaintext.
+    #[must_use]
+    pub fn body(&self) -> Plaintext {
+        self.render(self.lines())
+    }
+
+    /// Iterate over all fields, in order.
+    pub fn fields(&self) -> impl Iterator<Item = &Field> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Field(field) => Some(field),
+            Item::Text(_) => None,
+        })
+    }
+
+    /// Get the value of the first field with the given key.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields().find(|field| field.is(key)).map(Field::value)
+    }
+
+    /// Get the values of all fields with the given key, in order.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields()
+            .filter(move |field| field.is(key))
+            .map(Field::value)
+    }
+
+    /// Set the value of the field with the given key.
+    ///
+    /// Replaces the first field with this key, and removes other fields with
+    /// the same key. The field is added at the end if it does not exist.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        // Keep the key as it was spelled before
+        let spelled = self
+            .fields()
+            .find(|field| field.is(key))
+            .map_or(key, Field::key);
+        let mut field = Some(Field::new(spelled, value, self.newline)?);
+
+        self.items.retain_mut(|item| match item {
+            Item::Field(existing) if existing.is(key) => field.take().is_some_and(|field| {
+                *existing = field;
+                true
+            }),
+            _ => true,
+        });
+        if let Some(field) = field {
+            self.items.push(Item::Field(field));
+        }
+        Ok(())
+    }
+
+    /// Add a field with the given key at the end, keeping existing fields with
+    /// the same key.
+    pub fn add(&mut self, key: &str, value: &str) -> Result<()> {
+        let field = Field::new(key, value, self.newline)?;
+        self.items.push(Item::Field(field));
+        Ok(())
+    }
+
+    /// Remove all fields with the given key.
+    ///
+    /// Returns whether any field was removed.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.items.len();
+        self.items
+            .retain(|item| !matches!(item, Item::Field(field) if field.is(key)));
+        self.items.len() != len
+    }
+
+    /// Check whether this body has a YAML document.
+    #[must_use]
+    pub const fn has_document(&self) -> bool {
+        !self.document.is_empty()
+    }
+
+    /// Get the value of the given property.
+    ///
+    /// This is the value of the first field with the given key. Otherwise the
+    /// property path is looked up in the YAML document. Nested mappings and
+    /// sequences are returned as YAML.
+    #[must_use]
+    pub fn property(&self, path: &str) -> Option<Plaintext> {
+        if let Some(value) = self.get(path) {
+            return Some(value.into());
+        }
+        let document = parse_document(self.document.get(1..)?).ok()?;
+        lookup(&document, path).and_then(|value| to_plaintext(value).ok())
+    }
+
+    /// Set the value of the given property.
+    ///
+    /// Sets the field with the given key if it exists. Otherwise the property
+    /// path is set in the YAML document, creating nested mappings as needed.
+    /// A document is added for nested paths if there is none. Numbers and
+    /// booleans are stored as such in the document.
+    ///
+    /// Setting a property in the YAML document reformats it, comments in it
+    /// are lost.
+    pub fn set_property(&mut self, path: &str, value: &str) -> Result<()> {
+        if self.get(path).is_some() || !self.has_document() && !path.contains(PATH_SEPARATOR) {
+            return self.set(path, value);
+        }
+
+        let mut document = parse_document(self.document.get(1..).unwrap_or_default())?;
+        assign(&mut document, path, parse_scalar(value))?;
+        self.document = render_document(&document, self.newline)?;
+        Ok(())
+    }
+
+    /// Remove the given property.
+    ///
+    /// Removes all fields with the given key. Otherwise the property path is
+    /// removed from the YAML document. Returns whether anything was removed.
+    pub fn remove_property(&mut self, path: &str) -> Result<bool> {
+        if self.remove(path) {
+            return Ok(true);
+        }
+        if !self.has_document() {
+            return Ok(false);
+        }
+
+        let mut document = parse_document(&self.document[1..])?;
+        if !unassign(&mut document, path) {
+            return Ok(false);
+        }
+        self.document = render_document(&document, self.newline)?;
+        Ok(true)
+    }
+
+    /// Iterate over all lines after the password.
+    fn lines(&self) -> impl Iterator<Item = &Line> {
+        self.items
+            .iter()
+            .flat_map(Item::lines)
+            .chain(&self.document)
+    }
+
+    /// Render the given lines into plaintext.
+    ///
+    /// Lines without newline get one if more lines follow, the last line ends
+    /// with the newline the parsed plaintext ended with.
+    fn render<'a>(&self, lines: impl Iterator<Item = &'a Line>) -> Plaintext {
+        let lines: Vec<&Line> = lines.collect();
+        let mut data = Zeroizing::new(Vec::with_capacity(
+            lines.iter().map(|line| line.text.len() + 2).sum(),
+        ));
+        for (i, line) in lines.iter().enumerate() {
+            data.extend_from_slice(line.text.as_bytes());
+            let end = if i + 1 == lines.len() {
+                self.trailing
+            } else if line.end.is_empty() {
+                self.newline
+            } else {
+                line.end
+            };
+            data.extend_from_slice(end.as_bytes());
+        }
+        std::mem::take(&mut *data).into()
+    }
+}
+
+impl From<&SecretBody> for Plaintext {
+    fn from(body: &SecretBody) -> Self {
+        body.to_plaintext()
+    }
+}
+
+/// Field in a secret body.
+pub struct Field {
+    /// The key, trimmed.
+    key: Zeroizing<String>,
+
+    /// The value, trimmed and without continuation line indentation.
+    value: Zeroizing<String>,
+
+    /// The lines this field is made of.
+    lines: Vec<Line>,
+}
+
+impl Field {
+    /// Create field with the given key and value.
+    ///
+    /// Continuation lines of multi-line values are indented.
+    fn new(key: &str, value: &str, newline: &'static str) -> Result<Self> {
+        let key = key.trim();
+        if key.is_empty() || key.contains(PROPERTY_DELIMITER) || key.contains(['\r', '\n']) {
+            return Err(Err::Key(key.into()).into());
+        }
+
+        let mut values = value.trim_end().lines();
+        let first = values.next().unwrap_or_default().trim();
+        let delimiter = PROPERTY_DELIMITER.to_string();
+        let mut lines = vec![Line::new(
+            &[
+                key,
+                &delimiter,
+                if first.is_empty() { "" } else { " " },
+                first,
+            ],
+            newline,
+        )];
+
+        // Blank lines are indented as well, to continue the value
+        lines.extend(values.map(|value| Line::new(&[INDENT, value.trim_end()], newline)));
+
+        Ok(Self::from_lines(lines))
+    }
+
+    /// Create field from its lines, the first line holds the key.
+    fn from_lines(lines: Vec<Line>) -> Self {
+        let (key, first) = lines[0]
+            .text
+            .split_once(PROPERTY_DELIMITER)
+            .unwrap_or((&lines[0].text, ""));
+        let key = key.trim().to_owned().into();
+        let first = first.trim();
+
+        // Strip common indentation of continuation lines
+        let continuation = &lines[1..];
+        let indent = continuation
+            .iter()
+            .filter(|line| !line.text.trim().is_empty())
+            .map(|line| line.text.len() - line.text.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let mut value = Zeroizing::new(String::with_capacity(
+            lines.iter().map(|line| line.text.len() + 1).sum(),
+        ));
+        value.push_str(first);
+        for (i, line) in continuation.iter().enumerate() {
+            if i > 0 || !first.is_empty() {
+                value.push('\n');
+            }
+            value.push_str(
+                line.text
+                    .get(indent..)
+                    .unwrap_or_else(|| line.text.trim_start())
+                    .trim_end(),
+            );
+        }
+
+        Self { key, value, lines }
+    }
+
+    /// Get the key.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Get the value, lines of multi-line values are separated by `\n`.
+    #[must_use]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Check whether this field has the given key, case-insensitively.
+    #[must_use]
+    pub fn is(&self, key: &str) -> bool {
+        self.key.to_lowercase() == key.trim().to_lowercase()
+    }
+}
+
+/// Item in a secret body after the password.
+enum Item {
+    /// A field with its continuation lines.
+    Field(Field),
+
+    /// A free text line.
+    Text(Line),
+}
+
+impl Item {
+    fn lines(&self) -> &[Line] {
+        match self {
+            Self::Field(field) => &field.lines,
+            Self::Text(line) => std::slice::from_ref(line),
+        }
+    }
+}
+
+/// Line in a secret body.
+#[derive(Clone, Default)]
+struct Line {
+    /// Line text, without newline.
+    text: Zeroizing<String>,
+
+    /// Newline the line ends with, empty for the last line without newline.
+    end: &'static str,
+}
+
+impl Line {
+    /// Create line from the given parts.
+    fn new(parts: &[&str], end: &'static str) -> Self {
+        let mut text = Zeroizing::new(String::with_capacity(
+            parts.iter().map(|part| part.len()).sum(),
+        ));
+        for part in parts {
+            text.push_str(part);
+        }
+        Self { text, end }
+    }
+
+    fn parse(line: &str) -> Self {
+        let (text, end) = line
+            .strip_suffix("\r\n")
+            .map(|text| (text, "\r\n"))
+            .or_else(|| line.strip_suffix('\n').map(|text| (text, "\n")))
+            .unwrap_or((line, ""));
+        Self {
+            text: text.to_owned().into(),
+            end,
+        }
+    }
+
+    /// Check whether this line starts a field.
+    fn is_field(&self) -> bool {
+        self.text
+            .split_once(PROPERTY_DELIMITER)
+            .is_some_and(|(key, _)| !key.trim().is_empty())
+    }
+
+    /// Check whether this line continues a multi-line value.
+    fn is_continuation(&self) -> bool {
+        self.text.starts_with([' ', '\t']) && !self.text.trim().is_empty()
+    }
+
+    /// Check whether this line is blank.
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
+
+/// Parse YAML document from the given lines.
+///
+/// The document must be empty or a mapping.
+fn parse_document(lines: &[Line]) -> Result<Value> {
+    let mut text = Zeroizing::new(String::with_capacity(
+        lines.iter().map(|line| line.text.len() + 1).sum(),
+    ));
+    for line in lines {
+        text.push_str(&line.text);
+        text.push('\n');
+    }
+    if text.trim().is_empty() {
+        return Ok(Value::Mapping(Mapping::new()));
+    }
+    match serde_yaml::from_str(&text).map_err(Err::Document)? {
+        Value::Null => Ok(Value::Mapping(Mapping::new())),
+        document @ Value::Mapping(_) => Ok(document),
+        _ => Err(Err::NoMapping.into()),
+    }
+}
+
+/// Render YAML document into lines, starting with the separator.
+fn render_document(document: &Value, newline: &'static str) -> Result<Vec<Line>> {
+    let text = Zeroizing::new(serde_yaml::to_string(document).map_err(Err::Document)?);
+    let text = text.strip_prefix("---\n").unwrap_or(&text);
+    Ok(std::iter::once(DOCUMENT_SEPARATOR)
+        .chain(text.lines())
+        .map(|line| Line::new(&[line], newline))
+        .collect())
+}
+
+/// Look up the value at the given property path.
+fn lookup<'a>(document: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split(PATH_SEPARATOR)
+        .try_fold(document, |value, key| match value {
+            Value::Mapping(mapping) => mapping.get(&find_key(mapping, key)?),
+            Value::Sequence(sequence) => sequence.get(key.trim().parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// Look up the value at the given property path, mutably.
+fn lookup_mut<'a>(document: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split(PATH_SEPARATOR)
+        .try_fold(document, |value, key| match value {
+            Value::Mapping(mapping) => {
+                let key = find_key(mapping, key)?;
+                mapping.get_mut(&key)
+            },
+            Value::Sequence(sequence) => sequence.get_mut(key.trim().parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// Assign a value at the given property path, creating nested mappings.
+fn assign(document: &mut Value, path: &str, value: Value) -> Result<()> {
+    let invalid = || Err::Path(path.into());
+    let mut keys = path.split(PATH_SEPARATOR).peekable();
+    let mut current = document;
+    while let Some(key) = keys.next() {
+        if key.trim().is_empty() {
+            return Err(invalid().into());
+        }
+        if current.is_null() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let last = keys.peek().is_none();
+
+        current = match current {
+            Value::Mapping(mapping) => {
+                let key =
+                    find_key(mapping, key).unwrap_or_else(|| Value::String(key.trim().into()));
+                if last {
+                    mapping.insert(key, value);
+                    return Ok(());
+                }
+                if !mapping.contains_key(&key) {
+                    mapping.insert(key.clone(), Value::Null);
+                }
+                mapping.get_mut(&key).ok_or_else(invalid)?
+            },
+            Value::Sequence(sequence) => {
+                let index = key.trim().parse::<usize>().map_err(|_| invalid())?;
+                let item = sequence.get_mut(index).ok_or_else(invalid)?;
+                if last {
+                    *item = value;
+                    return Ok(());
+                }
+                item
+            },
+            _ => return Err(invalid().into()),
+        };
+    }
+    Ok(())
+}
+
+/// Remove the value at the given property path.
+///
+/// Returns whether a value was removed.
+fn unassign(document: &mut Value, path: &str) -> bool {
+    let (parent, key) = match path.rsplit_once(PATH_SEPARATOR) {
+        Some((parent, key)) => (lookup_mut(document, parent), key),
+        None => (Some(document), path),
+    };
+    match parent {
+        Some(Value::Mapping(mapping)) => find_key(mapping, key)
+            .and_then(|key| mapping.remove(&key))
+            .is_some(),
+        Some(Value::Sequence(sequence)) => match key.trim().parse::<usize>() {
+            Ok(index) if index < sequence.len() => {
+                sequence.remove(index);
+                true
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Find the key in a mapping matching the given key.
+///
+/// Prefers an exact match, and falls back to a case-insensitive match.
+fn find_key(mapping: &Mapping, key: &str) -> Option<Value> {
+    let key = key.trim();
+    let keys: Vec<(&Value, String)> = mapping
+        .iter()
+        .filter_map(|(k, _)| scalar_to_string(k).map(|s| (k, s)))
+        .collect();
+    keys.iter()
+        .find(|(_, k)| k == key)
+        .or_else(|| {
+            keys.iter()
+                .find(|(_, k)| k.to_lowercase() == key.to_lowercase())
+        })
+        .map(|(k, _)| (*k).clone())
+}
+
+/// Parse a value to set in a YAML document.
+///
+/// Numbers and booleans are kept as such, anything else is a string.
+fn parse_scalar(value: &str) -> Value {
+    match serde_yaml::from_str(value) {
+        Ok(value @ (Value::Bool(_) | Value::Number(_))) => value,
+        _ => Value::String(value.into()),
+    }
+}
+
+/// Get a scalar YAML value as string.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::String(value) => Some(value.clone()),
+        Value::Sequence(_) | Value::Mapping(_) => None,
+    }
+}
+
+/// Get a YAML value as plaintext, nested values are rendered as YAML.
+fn to_plaintext(value: &Value) -> Result<Plaintext> {
+    if let Some(scalar) = scalar_to_string(value) {
+        return Ok(scalar.into());
+    }
+    let text = Zeroizing::new(serde_yaml::to_string(value).map_err(Err::Document)?);
+    let text = text.strip_prefix("---\n").unwrap_or(&text);
+    Ok(text.trim_end().into())
+}
+
+/// A secret body error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed parse plaintext as UTF-8")]
+    Utf8(#[source] std::str::Utf8Error),
+
+    #[error("invalid field key: '{}'", _0)]
+    Key(String),
+
+    #[error("failed to parse YAML document in secret")]
+    Document(#[source] serde_yaml::Error),
+
+    #[error("YAML document in secret is not a mapping")]
+    NoMapping,
+
+    #[error("cannot set property '{}' in YAML document", _0)]
+    Path(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> SecretBody {
+        SecretBody::parse(&Plaintext::from(text)).unwrap()
+    }
+
+    fn render(body: &SecretBody) -> String {
+        body.to_plaintext().unsecure_to_str().unwrap().to_owned()
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn body_round_trip(text: String) -> bool {
+        // Parsing and rendering an unmodified body must never change it
+        render(&parse(&text)) == text
+    }
+
+    #[test]
+    fn body_round_trip_formats() {
+        let set = vec![
+            "",
+            "\n",
+            "pass",
+            "pass\n",
+            "pass\r\nUser: abc\r\n",
+            "pass\nUser: abc\n\n\nfree text\n  indented text",
+            "pass\nNotes: a\n  b\n\n  c\n\nUser: abc",
+            "pass\nUser: abc\nUser: def\r\n",
+        ];
+        for text in set {
+            assert_eq!(
+                render(&parse(text)),
+                text,
+                "body must round-trip losslessly"
+            );
+        }
+    }
+
+    #[test]
+    fn body_fields() {
+        let body = parse(
+            "Name: pass\nUser: abc\nfree text\nUSER: def\nNotes: a\n   b\n\n     \
+             c\n\nCert:\n\t1\n\t2\nEmpty:",
+        );
+        assert_eq!(body.password(), "Name: pass");
+        assert_eq!(body.get("user"), Some("abc"));
+        assert_eq!(body.get_all("User").collect::<Vec<_>>(), vec!["abc", "def"]);
+        assert_eq!(body.get("notes"), Some("a\nb\n\n  c"));
+        assert_eq!(body.get("cert"), Some("1\n2"));
+        assert_eq!(body.get("empty"), Some(""));
+        assert_eq!(body.get("name"), None, "password line is never a field");
+        assert_eq!(body.get("free text"), None);
+        assert_eq!(body.fields().map(Field::key).collect::<Vec<_>>(), vec![
+            "User", "USER", "Notes", "Cert", "Empty"
+        ]);
+    }
+
+    #[test]
+    fn body_set() {
+        // Replace first, remove duplicates, keep key spelling
+        let mut body = parse("pass\r\nUser: abc\r\nfree\r\nuser: def\r\n");
+        body.set("USER", "ghi").unwrap();
+        assert_eq!(render(&body), "pass\r\nUser: ghi\r\nfree\r\n");
+
+        // Add missing field, with multi-line value
+        let mut body = parse("pass");
+        body.set("Notes", "a\n  b\n\nc\n").unwrap();
+        assert_eq!(render(&body), "pass\nNotes: a\n    b\n  \n  c");
+        assert_eq!(body.get("notes"), Some("a\n  b\n\nc"));
+        body.add("notes", "").unwrap();
+        assert_eq!(render(&body), "pass\nNotes: a\n    b\n  \n  c\nnotes:");
+
+        // Invalid keys
+        assert!(body.set("", "abc").is_err());
+        assert!(body.set("a:b", "abc").is_err());
+        assert!(body.add("a\nb", "abc").is_err());
+    }
+
+    #[test]
+    fn body_remove() {
+        let mut body = parse("pass\nUser: abc\nNotes: a\n  b\nuser: def\n");
+        assert!(body.remove("user"));
+        assert!(!body.remove("user"));
+        assert_eq!(render(&body), "pass\nNotes: a\n  b\n");
+        assert!(body.remove("notes"));
+        assert_eq!(render(&body), "pass\n");
+    }
+
+    #[test]
+    fn body_password() {
+        let mut body = parse("old\nUser: abc\n");
+        body.set_password("new\nignored");
+        assert_eq!(render(&body), "new\nUser: abc\n");
+        assert_eq!(body.body().unsecure_to_str().unwrap(), "User: abc\n");
+
+        let mut body = parse("");
+        body.set_password("new");
+        assert_eq!(render(&body), "new");
+        assert!(body.body().is_empty());
+    }
+
+    #[test]
+    fn body_document() {
+        let text = "pass\nUser: abc\n---\napi:\n  key: secret # comment\n  port: 8080\n  \
+                    endpoints:\n    - url: https://a.example.com\n    - url: https://b.example.com\n";
+        let body = parse(text);
+        assert!(body.has_document());
+        assert_eq!(
+            render(&body),
+            text,
+            "body with document must round-trip losslessly"
+        );
+
+        let property = |path| {
+            body.property(path)
+                .map(|value| value.unsecure_to_str().unwrap().to_owned())
+        };
+        assert_eq!(property("user").as_deref(), Some("abc"));
+        assert_eq!(property("api.key").as_deref(), Some("secret"));
+        assert_eq!(property("API.Port").as_deref(), Some("8080"));
+        assert_eq!(
+            property("api.endpoints.1.url").as_deref(),
+            Some("https://b.example.com")
+        );
+        assert_eq!(
+            property("api.endpoints.0").as_deref(),
+            Some("url: \"https://a.example.com\"")
+        );
+        assert_eq!(property("api.endpoints.2"), None);
+        assert_eq!(property("api.missing"), None);
+
+        // Fields are never parsed from the document
+        assert_eq!(body.fields().count(), 1);
+
+        // Not a mapping, not a document
+        let body = parse("pass\n---\n- a\n- b");
+        assert!(!body.has_document());
+        assert!(body.property("0").is_none());
+    }
+
+    #[test]
+    fn body_set_property() {
+        // Nested keys create a document, plain keys stay fields
+        let mut body = parse("pass\nUser: abc\n");
+        body.set_property("user", "def").unwrap();
+        body.set_property("url", "https://example.com").unwrap();
+        assert!(!body.has_document());
+        body.set_property("api.port", "8080").unwrap();
+        body.set_property("api.key", "secret").unwrap();
+        assert_eq!(
+            render(&body),
+            "pass\nUser: def\nurl: https://example.com\n---\napi:\n  port: 8080\n  key: secret\n"
+        );
+
+        // Set in existing document
+        let mut body = parse("pass\r\n---\r\nAPI:\r\n  hosts:\r\n    - a\r\n");
+        body.set_property("api.hosts.0", "b").unwrap();
+        body.set_property("user", "abc").unwrap();
+        assert_eq!(
+            render(&body),
+            "pass\r\n---\r\nAPI:\r\n  hosts:\r\n    - b\r\nuser: abc\r\n"
+        );
+        assert!(body.set_property("api.hosts.5", "c").is_err());
+        assert!(body.set_property("api.hosts.0.x", "c").is_err());
+        assert!(body.set_property("api..x", "c").is_err());
+    }
+
+    #[test]
+    fn body_remove_property() {
+        let mut body = parse("pass\nUser: abc\n---\nuser: def\napi:\n  hosts:\n    - a\n    - b\n");
+        assert!(body.remove_property("USER").unwrap());
+        assert_eq!(
+            body.property("user").unwrap().unsecure_to_str().unwrap(),
+            "def"
+        );
+        assert!(body.remove_property("user").unwrap());
+        assert!(body.remove_property("api.hosts.0").unwrap());
+        assert!(!body.remove_property("api.hosts.1").unwrap());
+        assert!(!body.remove_property("api.missing").unwrap());
+        assert_eq!(render(&body), "pass\n---\napi:\n  hosts:\n    - b\n");
+        assert!(!parse("pass\nUser: abc").remove_property("api.key").unwrap());
+    }
+}
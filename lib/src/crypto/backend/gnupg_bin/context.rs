@@ -1,5 +1,7 @@
 //! Provides GnuPG binary context adapter.
 
+use std::path::Path;
+
 use anyhow::Result;
 use thiserror::Error;
 use version_compare::Version;
@@ -57,6 +59,10 @@ impl IsContext for Context {
         raw::can_decrypt(&self.config, &ciphertext)
     }
 
+    fn recipient_key_ids(&mut self, ciphertext: Ciphertext) -> Result<Option<Vec<String>>> {
+        Ok(Some(proto::gpg::recipient_key_ids(ciphertext.unsecure_ref())?))
+    }
+
     fn keys_public(&mut self) -> Result<Vec<Key>> {
         Ok(raw::public_keys(&self.config)?
             .into_iter()
@@ -81,6 +87,10 @@ impl IsContext for Context {
             .collect())
     }
 
+    fn key_ids(&mut self, key: &Key) -> Result<Vec<String>> {
+        raw::key_ids(&self.config, &key.fingerprint(false))
+    }
+
     fn import_key(&mut self, key: &[u8]) -> Result<()> {
         raw::import_key(&self.config, key)
     }
@@ -89,6 +99,14 @@ impl IsContext for Context {
         raw::export_key(&self.config, &key.fingerprint(false))
     }
 
+    fn sign_detached(&mut self, key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+        raw::sign_detached(&self.config, &key.fingerprint(false), data)
+    }
+
+    fn verify_detached_file(&mut self, path: &Path, signature: &Path) -> Result<Vec<String>> {
+        raw::verify_detached_file(&self.config, path, signature)
+    }
+
     fn supports_proto(&self, proto: Proto) -> bool {
         proto == Proto::Gpg
     }
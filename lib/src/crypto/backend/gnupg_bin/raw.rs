@@ -3,18 +3,23 @@
 //! This provides the most basic and bare functions to interface with a GnuPG backend binary.
 
 use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::path::Path;
 
 use anyhow::Result;
 use regex::Regex;
 use thiserror::Error;
 
-use super::raw_cmd::{gpg_stdin_output, gpg_stdin_stdout_ok_bin, gpg_stdout_ok, gpg_stdout_ok_bin};
+use super::raw_cmd::{gpg_output, gpg_stdin_stdout_ok_bin, gpg_stdout_ok, gpg_stdout_ok_bin};
 use super::Config;
-use crate::crypto::util;
+use crate::crypto::{proto, util};
 use crate::{Ciphertext, Plaintext};
 
-/// Partial output from gpg if the user does not own the secret key.
-const GPG_OUTPUT_ERR_NO_SECKEY: &str = "decryption failed: No secret key";
+/// Secret key availability marker for keys without secret material in colon listings.
+const GPG_COLON_SECRET_STUB: &str = "#";
+
+/// Status line prefix for a valid signature.
+const GPG_STATUS_VALIDSIG: &str = "[GNUPG:] VALIDSIG ";
 
 /// Encrypt plaintext for the given recipients.
 ///
@@ -59,22 +64,17 @@ pub fn decrypt(config: &Config, ciphertext: &Ciphertext) -> Result<Plaintext> {
 
 /// Check whether we can decrypt ciphertext.
 ///
-/// This checks whether whether we own the secret key to decrypt the given ciphertext.
+/// This checks whether we own the secret key to decrypt the given ciphertext. The
+/// recipients are read from the message packets, no decryption is attempted.
 ///
 /// - `config`: GPG config
 /// - `ciphertext`: ciphertext to check
-// To check this, actual decryption is attempted, see this if this can be improved:
-// https://stackoverflow.com/q/64633736/1000145
 pub fn can_decrypt(config: &Config, ciphertext: &Ciphertext) -> Result<bool> {
-    // TODO: ensure ciphertext ends with PGP footer
-
-    let output = gpg_stdin_output(config, &["--quiet", "--decrypt"], ciphertext.unsecure_ref())
-        .map_err(Err::Decrypt)?;
-
-    match output.status.code() {
-        Some(2) => Ok(!std::str::from_utf8(&output.stdout)?.contains(GPG_OUTPUT_ERR_NO_SECKEY)),
-        Some(0 | _) | None => Ok(true),
-    }
+    let recipients = proto::gpg::recipient_key_ids(ciphertext.unsecure_ref())?;
+    Ok(proto::gpg::has_recipient(
+        &recipients,
+        &private_key_ids(config)?,
+    ))
 }
 
 /// Get all public keys from keychain.
@@ -95,6 +95,27 @@ pub fn private_keys(config: &Config) -> Result<Vec<KeyId>> {
     parse_key_list(&list).ok_or_else(|| Err::UnexpectedOutput.into())
 }
 
+/// Get the key IDs of all private/secret keys and subkeys from keychain.
+///
+/// Subkeys of which the secret part is not available are excluded.
+///
+/// - `config`: GPG config
+pub fn private_key_ids(config: &Config) -> Result<Vec<String>> {
+    let list =
+        gpg_stdout_ok(config, ["--list-secret-keys", "--with-colons"]).map_err(Err::Keys)?;
+    Ok(parse_colon_key_ids(&list, &["sec", "ssb"]))
+}
+
+/// Get the key IDs of the given key and its subkeys from keychain.
+///
+/// - `config`: GPG config
+/// - `fingerprint`: fingerprint of the key
+pub fn key_ids(config: &Config, fingerprint: &str) -> Result<Vec<String>> {
+    let list = gpg_stdout_ok(config, ["--list-keys", "--with-colons", fingerprint])
+        .map_err(Err::Keys)?;
+    Ok(parse_colon_key_ids(&list, &["pub", "sub"]))
+}
+
 /// Import given key from bytes into keychain.
 ///
 /// - `config`: GPG config
@@ -145,6 +166,53 @@ pub fn export_key(config: &Config, fingerprint: &str) -> Result<Vec<u8>> {
     Ok(data)
 }
 
+/// Create an ASCII armored detached signature for data.
+///
+/// - `config`: GPG config
+/// - `fingerprint`: fingerprint of the secret key to sign with
+/// - `data`: data to sign
+pub fn sign_detached(config: &Config, fingerprint: &str, data: &[u8]) -> Result<Vec<u8>> {
+    gpg_stdin_stdout_ok_bin(
+        config,
+        [
+            "--quiet",
+            "--armor",
+            "--local-user",
+            fingerprint,
+            "--detach-sign",
+        ],
+        data,
+    )
+    .map_err(|err| Err::Sign(err).into())
+}
+
+/// Verify a detached signature file for the given file.
+///
+/// Returns the primary key fingerprints of all valid signatures, read from the `VALIDSIG` status
+/// lines. gpg fails if any signature is bad, valid signatures are still reported in that case.
+///
+/// - `config`: GPG config
+/// - `path`: path to the signed file
+/// - `signature`: path to the detached signature file
+pub fn verify_detached_file(config: &Config, path: &Path, signature: &Path) -> Result<Vec<String>> {
+    let output = gpg_output(config, [
+        OsStr::new("--status-fd"),
+        OsStr::new("1"),
+        OsStr::new("--verify"),
+        signature.as_os_str(),
+        path.as_os_str(),
+    ])
+    .map_err(Err::Verify)?;
+
+    // Primary key fingerprint is the last field
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix(GPG_STATUS_VALIDSIG))
+        .filter_map(|line| line.split_whitespace().nth(9))
+        .map(Into::into)
+        .collect())
+}
+
 /// A key identifier with a fingerprint and user IDs.
 #[derive(Clone)]
 pub struct KeyId(pub String, pub Vec<String>);
@@ -219,6 +287,19 @@ fn parse_key_list(list: &str) -> Option<Vec<KeyId>> {
     Some(keys)
 }
 
+/// Parse key IDs from colon separated key list output from gnupg.
+///
+/// Only includes records of the given types. Secret keys and subkeys of which the secret part is
+/// not available are excluded.
+fn parse_colon_key_ids(list: &str, records: &[&str]) -> Vec<String> {
+    list.lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .filter(|fields| records.contains(&fields[0]))
+        .filter(|fields| fields.get(14) != Some(&GPG_COLON_SECRET_STUB))
+        .filter_map(|fields| fields.get(4).map(|id| id.to_uppercase()))
+        .collect()
+}
+
 /// GnuPG binary error.
 #[derive(Debug, Error)]
 pub enum Err {
@@ -239,4 +320,10 @@ pub enum Err {
 
     #[error("failed to export key from gpg keychain")]
     Export(#[source] anyhow::Error),
+
+    #[error("failed to sign data")]
+    Sign(#[source] anyhow::Error),
+
+    #[error("failed to verify signature")]
+    Verify(#[source] anyhow::Error),
 }
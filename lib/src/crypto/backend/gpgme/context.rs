@@ -1,13 +1,13 @@
 //! Provides GPGME binary context adapter.
 
-use std::env;
+use std::{env, fs, path::Path};
 
 use anyhow::Result;
 use gpgme::{Context as GpgmeContext, PinentryMode, Protocol};
 use thiserror::Error;
 
 use super::raw;
-use crate::crypto::{proto, Config, IsContext, Key, Proto};
+use crate::crypto::{proto, Config, Err as CryptoErr, IsContext, Key, Proto};
 use crate::{util, Ciphertext, Plaintext, Recipients};
 
 /// Protocol to use.
@@ -65,6 +65,10 @@ impl IsContext for Context {
         raw::can_decrypt(&mut self.context, ciphertext)
     }
 
+    fn recipient_key_ids(&mut self, ciphertext: Ciphertext) -> Result<Option<Vec<String>>> {
+        Ok(Some(proto::gpg::recipient_key_ids(ciphertext.unsecure_ref())?))
+    }
+
     fn keys_public(&mut self) -> Result<Vec<Key>> {
         Ok(raw::public_keys(&mut self.context)?
             .into_iter()
@@ -89,6 +93,10 @@ impl IsContext for Context {
             .collect())
     }
 
+    fn key_ids(&mut self, key: &Key) -> Result<Vec<String>> {
+        raw::key_ids(&mut self.context, &key.fingerprint(false))
+    }
+
     fn import_key(&mut self, key: &[u8]) -> Result<()> {
         raw::import_key(&mut self.context, key)
     }
@@ -97,6 +105,18 @@ impl IsContext for Context {
         raw::export_key(&mut self.context, &key.fingerprint(false))
     }
 
+    fn sign_detached(&mut self, key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+        raw::sign_detached(&mut self.context, &key.fingerprint(false), data)
+    }
+
+    fn verify_detached_file(&mut self, path: &Path, signature: &Path) -> Result<Vec<String>> {
+        raw::verify_detached(
+            &mut self.context,
+            &fs::read(path).map_err(CryptoErr::ReadFile)?,
+            &fs::read(signature).map_err(CryptoErr::ReadFile)?,
+        )
+    }
+
     fn supports_proto(&self, proto: Proto) -> bool {
         proto == Proto::Gpg
     }
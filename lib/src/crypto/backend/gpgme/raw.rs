@@ -5,8 +5,8 @@
 use anyhow::Result;
 use gpgme::{Context, EncryptFlags, Key};
 use thiserror::Error;
-use zeroize::Zeroize;
 
+use crate::crypto::proto;
 use crate::{Ciphertext, Plaintext};
 
 /// GPGME encryption flags.
@@ -58,24 +58,17 @@ pub fn decrypt(context: &mut Context, ciphertext: Ciphertext) -> Result<Plaintex
 
 /// Check whether we can decrypt ciphertext.
 ///
-/// This checks whether whether we own the secret key to decrypt the given ciphertext.
-/// Assumes `true` if GPGME returns an error different than `NO_SECKEY`.
+/// This checks whether we own the secret key to decrypt the given ciphertext. The
+/// recipients are read from the message packets, no decryption is attempted.
 ///
 /// - `context`: GPGME context
 /// - `ciphertext`: ciphertext to check
-// To check this, actual decryption is attempted, see this if this can be improved:
-// https://stackoverflow.com/q/64633736/1000145
 pub fn can_decrypt(context: &mut Context, ciphertext: Ciphertext) -> Result<bool> {
-    // Try to decrypt, explicit zeroing of unsecure buffer required
-    let mut plaintext = vec![];
-    let result = context.decrypt(ciphertext.unsecure_ref(), &mut plaintext);
-    plaintext.zeroize();
-
-    match result {
-        Ok(_) => Ok(true),
-        Err(err) if gpgme::error::Error::NO_SECKEY.code() == err.code() => Ok(false),
-        Err(_) => Ok(true),
-    }
+    let recipients = proto::gpg::recipient_key_ids(ciphertext.unsecure_ref())?;
+    Ok(proto::gpg::has_recipient(
+        &recipients,
+        &private_key_ids(context)?,
+    ))
 }
 
 /// Get all public keys from keychain.
@@ -104,6 +97,38 @@ pub fn private_keys(context: &mut Context) -> Result<Vec<KeyId>> {
         .collect())
 }
 
+/// Get the key IDs of all private/secret keys and subkeys from keychain.
+///
+/// Subkeys of which the secret part is not available are excluded.
+///
+/// - `context`: GPGME context
+pub fn private_key_ids(context: &mut Context) -> Result<Vec<String>> {
+    Ok(context
+        .secret_keys()?
+        .into_iter()
+        .filter_map(|k| k.ok())
+        .flat_map(|k| {
+            k.subkeys()
+                .filter(|s| s.is_secret())
+                .filter_map(|s| s.id().ok().map(str::to_uppercase))
+                .collect::<Vec<_>>()
+        })
+        .collect())
+}
+
+/// Get the key IDs of the given key and its subkeys from keychain.
+///
+/// - `context`: GPGME context
+/// - `fingerprint`: fingerprint of the key
+pub fn key_ids(context: &mut Context, fingerprint: &str) -> Result<Vec<String>> {
+    Ok(context
+        .get_key(fingerprint)
+        .map_err(Err::UnknownFingerprint)?
+        .subkeys()
+        .filter_map(|s| s.id().ok().map(str::to_uppercase))
+        .collect())
+}
+
 /// Import given key from bytes into keychain.
 ///
 /// - `context`: GPGME context
@@ -163,6 +188,59 @@ pub fn export_key(context: &mut Context, fingerprint: &str) -> Result<Vec<u8>> {
     Ok(data)
 }
 
+/// Create an ASCII armored detached signature for data.
+///
+/// - `context`: GPGME context
+/// - `fingerprint`: fingerprint of the secret key to sign with
+/// - `data`: data to sign
+pub fn sign_detached(context: &mut Context, fingerprint: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let key = context
+        .get_secret_key(fingerprint)
+        .map_err(Err::UnknownFingerprint)?;
+
+    // Sign with only the given key, armor enabled
+    let mut signature: Vec<u8> = vec![];
+    let armor = context.armor();
+    context.set_armor(true);
+    context.clear_signers();
+    context.add_signer(&key).map_err(Err::Sign)?;
+    let result = context.sign_detached(data, &mut signature);
+    context.clear_signers();
+    context.set_armor(armor);
+
+    result.map_err(Err::Sign)?;
+    Ok(signature)
+}
+
+/// Verify a detached signature for data.
+///
+/// Returns the primary key fingerprints of all valid signatures.
+///
+/// - `context`: GPGME context
+/// - `data`: signed data
+/// - `signature`: detached signature
+pub fn verify_detached(
+    context: &mut Context,
+    data: &[u8],
+    signature: &[u8],
+) -> Result<Vec<String>> {
+    let result = context
+        .verify_detached(signature, data)
+        .map_err(Err::Verify)?;
+    let fingerprints: Vec<String> = result
+        .signatures()
+        .filter(|sig| sig.status().is_ok())
+        .filter_map(|sig| sig.fingerprint().ok().map(Into::into))
+        .collect();
+
+    // Signatures may be made by a subkey, resolve the primary key
+    Ok(fingerprints
+        .iter()
+        .filter_map(|fp| context.get_key(fp.as_str()).ok())
+        .filter_map(|key| key.fingerprint().ok().map(Into::into))
+        .collect())
+}
+
 /// A key identifier with a fingerprint and user IDs.
 #[derive(Clone)]
 pub struct KeyId(pub String, pub Vec<String>);
@@ -233,4 +311,10 @@ pub enum Err {
 
     #[error("fingerprint does not match public key in keychain")]
     UnknownFingerprint(#[source] gpgme::Error),
+
+    #[error("failed to sign data")]
+    Sign(#[source] gpgme::Error),
+
+    #[error("failed to verify signature")]
+    Verify(#[source] gpgme::Error),
 }
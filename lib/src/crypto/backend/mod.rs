@@ -6,5 +6,7 @@
 pub mod gnupg_bin;
 #[cfg(feature = "backend-gpgme")]
 pub mod gpgme;
-// #[cfg(feature = "backend-age")]
-// pub mod rage;
+#[cfg(feature = "backend-age")]
+pub mod rage;
+#[cfg(feature = "backend-rpgp")]
+pub mod rpgp;
@@ -1,81 +1,158 @@
-//! Provides `age` binary context adapter.
+//! Provides rage library context adapter.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
-use super::Config;
 use anyhow::Result;
 use thiserror::Error;
 
-use super::raw;
-use crate::{
-    crypto::{proto, IsContext, Key, Proto},
-    Ciphertext, Plaintext,
-};
+use super::{raw, Config};
+use crate::crypto::{proto, Config as CryptoConfig, IsContext, Key, Proto};
+use crate::{vendor::shellexpand, Ciphertext, Plaintext, Recipients};
+
+/// Environment variable to override the age identity file with.
+const IDENTITY_ENV: &str = "PASSWORD_STORE_AGE_IDENTITIES_FILE";
+
+/// Create rage context.
+pub fn context(config: &CryptoConfig) -> Result<Context, Err> {
+    let path = match &config.age_identity {
+        Some(path) => path.clone(),
+        None => find_identity_path().ok_or(Err::NoIdentityPath)?,
+    };
+    Ok(Context::from(Config::from(path)))
+}
+
+/// Generate a new age identity and append it to the configured identity file.
+///
+/// Returns the public key for the generated identity.
+pub fn generate_identity(config: &CryptoConfig) -> Result<Key> {
+    let context = context(config)?;
+    Ok(Key::Age(raw::generate_identity(&context.config)?))
+}
 
-/// GPGME crypto context.
+/// rage context.
 pub struct Context {
-    /// `age` crytpo context.
+    /// age config.
     config: Config,
 }
 
 impl Context {
-    /// Construct context from `age` `Config`.
-    fn from(config: Config) -> Self {
+    /// Construct context from age config.
+    const fn from(config: Config) -> Self {
         Self { config }
     }
 }
 
 impl IsContext for Context {
-    fn encrypt(&mut self, plaintext: Plaintext) -> Result<Ciphertext> {
-        raw::encrypt(config.recipient, config.recipients_file, plaintext)
+    fn encrypt(&mut self, recipients: &Recipients, plaintext: Plaintext) -> Result<Ciphertext> {
+        let recipients: Vec<String> = recipients
+            .keys()
+            .iter()
+            .map(|key| key.fingerprint(false))
+            .collect();
+        let recipients: Vec<&str> = recipients.iter().map(String::as_str).collect();
+        raw::encrypt(&recipients, &plaintext)
     }
 
     fn decrypt(&mut self, ciphertext: Ciphertext) -> Result<Plaintext> {
-        raw::decrypt(&mut self.context, ciphertext)
+        raw::decrypt(&self.config, &ciphertext)
     }
 
     fn can_decrypt(&mut self, ciphertext: Ciphertext) -> Result<bool> {
-        raw::can_decrypt(&mut self.context, ciphertext)
+        raw::can_decrypt(&self.config, &ciphertext)
+    }
+
+    fn recipient_key_ids(&mut self, _ciphertext: Ciphertext) -> Result<Option<Vec<String>>> {
+        // age does not store recipients in ciphertext
+        Ok(None)
     }
 
     fn keys_public(&mut self) -> Result<Vec<Key>> {
-        Ok(raw::public_keys(&mut self.context)?
-            .into_iter()
-            .map(|key| {
-                Key::Gpg(proto::gpg::Key {
-                    fingerprint: key.0,
-                    user_ids:    key.1,
-                })
-            })
-            .collect())
+        let mut keys = raw::private_keys(&self.config)?;
+        for key in raw::public_keys(&self.config)? {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        Ok(keys.into_iter().map(proto::age::Key::into_key).collect())
     }
 
     fn keys_private(&mut self) -> Result<Vec<Key>> {
-        Ok(raw::private_keys(&mut self.context)?
+        Ok(raw::private_keys(&self.config)?
             .into_iter()
-            .map(|key| {
-                Key::Gpg(proto::gpg::Key {
-                    fingerprint: key.0,
-                    user_ids:    key.1,
-                })
-            })
+            .map(proto::age::Key::into_key)
             .collect())
     }
 
+    fn key_ids(&mut self, key: &Key) -> Result<Vec<String>> {
+        Ok(vec![key.fingerprint(false)])
+    }
+
     fn import_key(&mut self, key: &[u8]) -> Result<()> {
-        raw::import_key(&mut self.context, key)
+        raw::import_key(&self.config, key)
     }
 
     fn export_key(&mut self, key: Key) -> Result<Vec<u8>> {
-        raw::export_key(&mut self.context, &key.fingerprint(false))
+        Ok(format!("{}\n", key.fingerprint(false)).into_bytes())
+    }
+
+    fn sign_detached(&mut self, _key: &Key, _data: &[u8]) -> Result<Vec<u8>> {
+        Err(Err::Signatures.into())
+    }
+
+    fn verify_detached_file(&mut self, _path: &Path, _signature: &Path) -> Result<Vec<String>> {
+        Err(Err::Signatures.into())
     }
 
     fn supports_proto(&self, proto: Proto) -> bool {
-        proto == Proto::Gpg
+        proto == Proto::Age
     }
 }
 
-/// GPGME context error.
+/// Build list of probable age identity file paths.
+fn identity_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::with_capacity(2);
+
+    // Path used by passage
+    if let Ok(path) = shellexpand::full("~/.passage/identities") {
+        paths.push(PathBuf::from(path.as_ref()));
+    }
+
+    // Path in the prs configuration directory
+    if let Some(dir) = dirs_next::config_dir() {
+        paths.push(dir.join("prs").join("age").join("identities"));
+    }
+
+    paths
+}
+
+/// Find age identity file path.
+///
+/// Uses `PASSWORD_STORE_AGE_IDENTITIES_FILE` if set. Otherwise the first
+/// existing probable path is used, falling back to the prs configuration
+/// directory.
+fn find_identity_path() -> Option<PathBuf> {
+    // Take path from environment variable
+    if let Some(path) = env::var_os(IDENTITY_ENV).filter(|p| !p.is_empty()) {
+        return Some(path.into());
+    }
+
+    let paths = identity_paths();
+    paths
+        .iter()
+        .find(|p| p.is_file())
+        .or_else(|| paths.last())
+        .cloned()
+}
+
+/// rage context error.
 #[derive(Debug, Error)]
 pub enum Err {
-    #[error("failed to obtain GPGME cryptography context")]
-    Context(#[source] gpgme::Error),
+    #[error("failed to determine age identity file path")]
+    NoIdentityPath,
+
+    #[error("age does not support signatures")]
+    Signatures,
 }
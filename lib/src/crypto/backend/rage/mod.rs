@@ -1,25 +1,34 @@
-//! Crypto backend using `age` for encryption.
+//! Crypto backend using the rage library for age.
 
 pub mod context;
 pub mod raw;
 
-/// `age` configuration
+use std::path::PathBuf;
+
+/// age config.
 pub struct Config {
-    /// Input file to encrypt or decrypt
-    input_file:      String,
-    /// Recipient file to encrypt with (self-encryption)
-    recipients_file: Vec<String>,
-    /// Identity file used in decrypttion
-    identity_file: Vec<String>,
+    /// Identity file to decrypt with.
+    pub identity_file: PathBuf,
+
+    /// Keyring file listing known public recipients.
+    pub keyring_file: PathBuf,
 }
 
 impl Config {
-    /// Create an `age` `Config` struct
-    pub fn from(input_file: String, recipients_file: Vec<String>, identity_file: Vec<String>) -> Self {
+    /// Construct with given identity file.
+    ///
+    /// The keyring file is placed next to the identity file.
+    ///
+    /// - `identity_file`: path to age identity file
+    #[must_use]
+    pub fn from(identity_file: PathBuf) -> Self {
+        let keyring_file = identity_file.with_file_name(KEYRING_FILE_NAME);
         Self {
-            input_file,
-            recipients_file,
-            identity_file
+            identity_file,
+            keyring_file,
         }
     }
 }
+
+/// File name of the keyring listing known public recipients.
+const KEYRING_FILE_NAME: &str = "recipients";
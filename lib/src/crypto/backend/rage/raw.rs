@@ -1,329 +1,332 @@
-#![allow(unused)]
-//! Raw interface to `age`.
-
-// use age::{cli_common::UiCallbacks, plugin, armor::ArmoredReader};
-use age::{
-    armor::{ArmoredReader, ArmoredWriter, Format},
-    cli_common::{
-        file_io, read_identities, read_or_generate_passphrase, read_secret, Passphrase, UiCallbacks,
-    },
-    plugin, Identity, IdentityFile, Recipient,
-};
+//! Raw interface to the rage library.
+//!
+//! This provides the most basic and bare functions to interface with the age
+//! protocol.
 
-use super::Config;
-use anyhow::Result;
 use std::{
-    convert::TryFrom,
-    fs, io,
-    io::{BufRead, BufReader, Read, Write},
-    iter,
-    path::PathBuf,
+    fs,
+    io::{self, Read, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::Path,
 };
-use thiserror::Error;
-// use zeroize::Zeroize;
-
-use crate::{Ciphertext, Plaintext, STORE_UMASK};
-
-/// Encrypt a buffer with the `age` protocol, and return `Ciphertext`
-pub fn encrypt(config: &Config, plaintext: Plaintext) -> Result<Ciphertext> {
-    // recipient: Vec<String>,
-    let ciphertext = {
-        // recipient
-        let encryptor = age::Encryptor::with_recipients(read_recipients(config.recipients_file)?);
-
-        // let mut input = file_io::InputReader::new(Some(input_file))?;
-        // input_file: String,
-
-        let mut ciphertext = vec![];
-        let mut writer = encryptor
-            .wrap_output(ArmoredWriter::wrap_output(
-                &mut ciphertext,
-                Format::AsciiArmor,
-            )?)
-            .map_err(Err::Encrypt)?;
-
-        writer.write_all(plaintext.unsecure_ref())?;
-        writer.finish().and_then(|armor| armor.finish())?;
-
-        ciphertext
-    };
-    // let encryptor = age::Encryptor::with_recipients(vec![Box::new(pubkey)]);
-    // let mut writer = encryptor.wrap_output(&mut ciphertext)?;
-    // writer.finish()?;
-
-    Ok(Ciphertext::from(ciphertext))
-}
-
-/// Encrypt a buffer with the `age` protocol, and write it to a file
-pub fn encrypt_to_file(config: &Config) -> Result<(), Err> {
-    // recipient: Vec<String>,
-    // recipient.is_empty()
-    if config.recipients_file.is_empty() {
-        return Err(Err::MissingRecipients);
-    }
-
-    // recipient
-    let encryptor = age::Encryptor::with_recipients(read_recipients(config)?);
-    let mut input = file_io::InputReader::new(Some(config.input_file))?;
-
-    // let (format, output_format) =
-    // (Format::AsciiArmor, file_io::OutputFormat::Text);
-    // (Format::Binary, file_io::OutputFormat::Binary)
 
-    let output = file_io::OutputWriter::new(
-        Some(format!("{}.age", config.input_file)),
-        file_io::OutputFormat::Text,
-        0o666 - (0o666 & *STORE_UMASK),
-    )?;
+use age::{armor::ArmoredReader, cli_common::read_identities, DecryptError, Identity};
+use anyhow::Result;
+use secrecy::ExposeSecret;
+use thiserror::Error;
 
-    let mut output = encryptor
-        .wrap_output(ArmoredWriter::wrap_output(output, Format::AsciiArmor)?)
+use super::Config;
+use crate::crypto::proto::age::{self as proto, Key};
+use crate::{Ciphertext, Plaintext};
+
+/// Prefix of a native age secret key.
+const SECRET_KEY_PREFIX: &str = "AGE-SECRET-KEY-";
+
+/// Encrypt plaintext for the given recipients.
+///
+/// - `recipients`: list of recipients to encrypt for
+/// - `plaintext`: plaintext to encrypt
+///
+/// # Panics
+///
+/// Panics if list of recipients is empty.
+pub fn encrypt(recipients: &[&str], plaintext: &Plaintext) -> Result<Ciphertext> {
+    assert!(
+        !recipients.is_empty(),
+        "attempting to encrypt secret for empty list of recipients"
+    );
+
+    let recipients = recipients
+        .iter()
+        .map(|r| parse_recipient(r))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut ciphertext = vec![];
+    let mut writer = age::Encryptor::with_recipients(recipients)
+        .wrap_output(&mut ciphertext)
         .map_err(Err::Encrypt)?;
+    writer
+        .write_all(plaintext.unsecure_ref())
+        .map_err(Err::Io)?;
+    writer.finish().map_err(Err::Io)?;
 
-    let map_io_errors = |e: io::Error| match e.kind() {
-        io::ErrorKind::BrokenPipe => Err::BrokenPipe(e),
-        _ => e.into(),
-    };
-
-    io::copy(&mut input, &mut output).map_err(map_io_errors)?;
-    output
-        .finish()
-        .and_then(|armor| armor.finish())
-        .map_err(map_io_errors)?;
-
-    Ok(())
+    Ok(Ciphertext::from(ciphertext))
 }
 
-/// Decrypt a `Ciphertext` buffer and return a `Plaintext` buffer
-pub fn decrypt(key: age::ssh::Identity, ciphertext: Ciphertext) -> Result<Plaintext> {
-    let plaintext = {
-        let decryptor = match age::Decryptor::new(&ciphertext.unsecure_ref()[..])? {
-            age::Decryptor::Recipients(d) => d,
-            _ => unreachable!(),
-        };
+/// Decrypt ciphertext.
+///
+/// Both binary and ASCII armored ciphertext is supported.
+///
+/// - `config`: age config
+/// - `ciphertext`: ciphertext to decrypt
+pub fn decrypt(config: &Config, ciphertext: &Ciphertext) -> Result<Plaintext> {
+    let identities = load_identities(config)?;
 
-        let mut plaintext = vec![];
-        let mut reader = decryptor.decrypt(iter::once(&key as &dyn age::Identity))?;
-        reader.read_to_end(&mut plaintext);
+    let mut plaintext = vec![];
+    decrypt_reader(&identities, ciphertext)?
+        .read_to_end(&mut plaintext)
+        .map_err(Err::Io)?;
 
-        plaintext
-    };
     Ok(Plaintext::from(plaintext))
 }
 
-pub fn decrypt_to_file(config: &Config) -> Result<(), Err> {
-    match age::Decryptor::new(ArmoredReader::new(file_io::InputReader::new(Some(
-        config.input_file,
-    ))?))
-    .map_err(Err::Decrypt)?
-    {
-        age::Decryptor::Recipients(decryptor) => {
-            let identities = read_identities(
-                config.identity_file,
-                |e| Err::IdentityNotFound(e),
-                |e, k| Err::UnsupportedKey(e, k),
-            )?;
-
-            if identities.is_empty() {
-                return Err(Err::MissingIdentities);
-            }
-
-            // Get file stem, returning base by removing '.age' extension
-            let output = PathBuf::from(config.input_file)
-                .file_stem()
-                .ok_or(Err::PathBufError("gaining file stem".to_string()))?
-                .to_str()
-                .ok_or(Err::PathBufError(
-                    "converting file to string slice".to_string(),
-                ))?
-                .to_string();
-
-            decryptor
-                .decrypt(identities.iter().map(|i| i.as_ref() as &dyn Identity))
-                .map_err(|e| e.into())
-                .and_then(|input| write_output(input, Some(output)));
-        },
-        age::Decryptor::Passphrase(decryptor) => unreachable!(),
+/// Check whether we can decrypt ciphertext.
+///
+/// This checks whether any of our identities can unwrap the file key, the
+/// payload itself is not decrypted.
+///
+/// - `config`: age config
+/// - `ciphertext`: ciphertext to check
+pub fn can_decrypt(config: &Config, ciphertext: &Ciphertext) -> Result<bool> {
+    let identities = load_identities(config)?;
+
+    match decrypt_reader(&identities, ciphertext) {
+        Ok(_) => Ok(true),
+        Err(Err::Decrypt(DecryptError::NoMatchingKeys)) => Ok(false),
+        Err(err) => Err(err.into()),
     }
-
-    Ok(())
 }
 
-fn write_output<R: io::Read>(mut input: R, output: Option<String>) -> Result<(), Err> {
-    let mut output = file_io::OutputWriter::new(
-        output,
-        file_io::OutputFormat::Unknown,
-        0o666 - (0o666 & *STORE_UMASK),
-    )?;
-    io::copy(&mut input, &mut output)?;
+/// Get all public keys from the keyring.
+///
+/// Returns an empty list if the keyring file does not exist.
+pub fn public_keys(config: &Config) -> Result<Vec<Key>> {
+    if !config.keyring_file.is_file() {
+        return Ok(vec![]);
+    }
 
-    Ok(())
+    Ok(proto::parse_recipients(
+        &fs::read_to_string(&config.keyring_file).map_err(Err::Io)?,
+    ))
 }
 
-pub fn can_decrypt1(key: age::ssh::Identity, ciphertext: Ciphertext) -> Result<bool> {
-    let decryptor = match age::Decryptor::new(&ciphertext.unsecure_ref()[..])? {
-        age::Decryptor::Recipients(d) => d,
-        _ => unreachable!(),
-    };
-
-    match decryptor.decrypt(iter::once(&key as &dyn age::Identity)) {
-        Ok(_) => Ok(true),
-        Err(age::DecryptError::NoMatchingKeys) => Ok(false),
-        Err(_) => Ok(true),
+/// Get public keys for all native identities in the identity file.
+///
+/// Returns an empty list if the identity file does not exist.
+pub fn private_keys(config: &Config) -> Result<Vec<Key>> {
+    if !config.identity_file.is_file() {
+        return Ok(vec![]);
     }
-}
 
-pub fn can_decrypt(config: &Config) -> Result<bool, Err> {
-    let mut recipients: Vec<Box<dyn age::Recipient>> = vec![];
+    let file = age::IdentityFile::from_file(path_string(&config.identity_file)?).map_err(Err::Io)?;
+    Ok(file
+        .into_identities()
+        .iter()
+        .map(|identity| Key {
+            recipient: identity.to_public().to_string(),
+            comment: None,
+        })
+        .collect())
+}
 
-    for fname in config.recipients_file {
-        let f = fs::File::open(&fname)?;
-        let buf = BufReader::new(f);
-        if let Err(e) = read_recipients_list(&fname, buf, &mut recipients) {
-            return Err(e);
+/// Import the given public recipients into the keyring.
+///
+/// Recipients that are already known are skipped.
+pub fn import_key(config: &Config, key: &[u8]) -> Result<()> {
+    let keys = proto::parse_recipients(std::str::from_utf8(key).map_err(|_| Err::InvalidData)?);
+    for key in &keys {
+        if key.recipient.starts_with(SECRET_KEY_PREFIX) {
+            return Err(Err::ImportSecret.into());
         }
+        parse_recipient(&key.recipient)?;
     }
 
-    Ok(true)
-}
-
-fn parse_recipient(s: String, recipients: &mut Vec<Box<dyn age::Recipient>>) -> Result<(), Err> {
-    if let Ok(pk) = s.parse::<age::x25519::Recipient>() {
-        recipients.push(Box::new(pk));
-    } else if let Some(pk) = { s.parse::<age::ssh::Recipient>().ok().map(Box::new) } {
-        recipients.push(pk);
-    } else {
-        return Err(Err::InvalidRecipient(s));
+    let mut keyring = public_keys(config)?;
+    for key in keys {
+        if !keyring.contains(&key) {
+            keyring.push(key);
+        }
     }
 
-    Ok(())
+    write_private_file(
+        &config.keyring_file,
+        proto::format_recipients(&keyring).as_bytes(),
+        false,
+    )
 }
 
-fn read_recipients_list<R: BufRead>(
-    filename: &str,
-    buf: R,
-    recipients: &mut Vec<Box<dyn age::Recipient>>,
-) -> Result<(), Err> {
-    for (idx, line) in buf.lines().enumerate() {
-        let line = line?;
-
-        // Empty lines             comments, skip
-        if line.is_empty() || line.find('#') == Some(0) {
-            continue;
-        } else if parse_recipient(line, recipients).is_err() {
-            // Return a line number in place of the line
-            return Err(Err::InvalidData(format!(
-                "recipients file {} contains non-recipient data on line {}",
-                filename,
-                idx + 1
-            )));
-        }
-    }
+/// Generate a new native age identity.
+///
+/// The identity is appended to the identity file, which is created if it does
+/// not exist. Returns the public key of the new identity.
+pub fn generate_identity(config: &Config) -> Result<Key> {
+    let identity = age::x25519::Identity::generate();
+    let key = Key {
+        recipient: identity.to_public().to_string(),
+        comment: None,
+    };
 
-    Ok(())
+    let contents = format!(
+        "# public key: {}\n{}\n",
+        key.recipient,
+        identity.to_string().expose_secret()
+    );
+    write_private_file(&config.identity_file, contents.as_bytes(), true)?;
+
+    Ok(key)
 }
 
-pub fn read_recipients(config: &Config) -> Result<Vec<Box<dyn age::Recipient>>, Err> {
-    let mut recipients: Vec<Box<dyn age::Recipient>> = vec![];
-
-    // recipient_strings: Vec<String>,
-    // for arg in recipient_strings {
-    //     parse_recipient(arg, &mut recipients)?;
-    // }
-
-    // read_recipients_list(&fname, buf, &mut recipients)?;
-    for fname in config.recipients_file {
-        let f = fs::File::open(&fname)?;
-        let buf = BufReader::new(f);
-        for (idx, line) in buf.lines().enumerate() {
-            let line = line?;
-            if line.is_empty() || line.find('#') == Some(0) {
-                continue;
-            } else if parse_recipient(line, &mut recipients).is_err() {
-                return Err(Err::InvalidData(format!(
-                    "recipients file {} contains non-recipient data on line {}",
-                    fname,
-                    idx + 1
-                )));
-            }
-        }
-    }
+/// Load identities from the configured identity file.
+fn load_identities(config: &Config) -> Result<Vec<Box<dyn Identity>>, Err> {
+    let identities = read_identities(
+        vec![path_string(&config.identity_file)?],
+        Err::IdentityNotFound,
+        |path, _| Err::UnsupportedKey(path),
+    )?;
 
-    Ok(recipients)
+    if identities.is_empty() {
+        return Err(Err::MissingIdentities);
+    }
+    Ok(identities)
 }
 
-pub fn public_keys() {
-    todo!();
+/// Construct a reader for decrypted ciphertext using the given identities.
+fn decrypt_reader<'a>(
+    identities: &[Box<dyn Identity>],
+    ciphertext: &'a Ciphertext,
+) -> Result<impl Read + 'a, Err> {
+    match age::Decryptor::new(ArmoredReader::new(ciphertext.unsecure_ref()))
+        .map_err(Err::Decrypt)?
+    {
+        age::Decryptor::Recipients(decryptor) => decryptor
+            .decrypt(identities.iter().map(AsRef::as_ref))
+            .map_err(Err::Decrypt),
+        age::Decryptor::Passphrase(_) => Err(Err::Passphrase),
+    }
 }
 
-pub fn private_keys() {
-    todo!();
+/// Parse a recipient string into any supported age recipient.
+fn parse_recipient(recipient: &str) -> Result<Box<dyn age::Recipient>, Err> {
+    if let Ok(pk) = recipient.parse::<age::x25519::Recipient>() {
+        return Ok(Box::new(pk));
+    }
+    if let Ok(pk) = recipient.parse::<age::ssh::Recipient>() {
+        return Ok(Box::new(pk));
+    }
+    Err(Err::InvalidRecipient(recipient.into()))
 }
 
-pub fn import_key() {
-    todo!();
-}
+/// Write to a file only readable by the current user, creating parent
+/// directories.
+///
+/// - `append`: append to the file, truncate it otherwise
+fn write_private_file(path: &Path, contents: &[u8], append: bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(Err::Io)?;
+    }
 
-pub fn export_key() {
-    todo!();
+    let mut file = fs::OpenOptions::new()
+        .mode(0o600)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .create(true)
+        .open(path)
+        .map_err(Err::Io)?;
+    file.write_all(contents).map_err(|err| Err::Io(err).into())
 }
 
-pub fn supports_proto() {
-    todo!();
+/// Get given path as string.
+fn path_string(path: &Path) -> Result<String, Err> {
+    path.to_str()
+        .map(Into::into)
+        .ok_or_else(|| Err::InvalidPath(path.into()))
 }
 
-/// Age binary error.
+/// rage library error.
 #[derive(Debug, Error)]
 pub enum Err {
-    /// `age` encryption failure
     #[error("failed to encrypt plaintext")]
     Encrypt(#[source] age::EncryptError),
 
-    /// Invalid recipients for `age` file
-    #[error("invalid recipient {0} for age encryption")]
+    #[error("failed to decrypt ciphertext")]
+    Decrypt(#[source] DecryptError),
+
+    #[error("failed to decrypt ciphertext, passphrase encrypted secrets are not supported")]
+    Passphrase,
+
+    #[error("invalid age recipient: {0}")]
     InvalidRecipient(String),
 
-    /// No recipients for `age` file
-    #[error("missing recipients for encryption of age file")]
-    MissingRecipients,
+    #[error("failed to import key, key data is not valid UTF-8")]
+    InvalidData,
 
-    /// No identity for `age` file
-    #[error("missing identities for decryption of age file")]
-    MissingIdentities,
+    #[error("failed to import key, refusing to import age secret key into keyring")]
+    ImportSecret,
 
-    /// `age` identity doesn't have a passphrase
-    #[error("{0} is encrypted without a passphrase")]
-    IdentityEncryptedWithoutPassphrase(String),
+    #[error("no age identities found in identity file")]
+    MissingIdentities,
 
-    /// `age` identity not found
-    #[error("age identity not found: {0}")]
+    #[error("age identity file not found: {0}")]
     IdentityNotFound(String),
 
-    /// Unsupported key for `age` encryption
-    #[error("{0} encryption has an unsupported key: {1:?}")]
-    UnsupportedKey(String, age::ssh::UnsupportedKey),
+    #[error("age identity file contains unsupported SSH key: {0}")]
+    UnsupportedKey(String),
 
-    /// Failure to decrypt `age` file
-    #[error("failed to decrypt ciphertext")]
-    Decrypt(#[source] age::DecryptError),
+    #[error("age file path is not valid UTF-8: {0:?}")]
+    InvalidPath(std::path::PathBuf),
 
-    /// Invalid data when reading age file
-    #[error("invalid data found within age file: {0}")]
-    InvalidData(String),
+    #[error("failed to read or write age data")]
+    Io(#[source] io::Error),
+}
 
-    /// General IO error
-    #[error("IO error: {0}")]
-    IO(#[from] io::Error),
+impl From<DecryptError> for Err {
+    fn from(err: DecryptError) -> Self {
+        Self::Decrypt(err)
+    }
+}
 
-    /// General age decrypt error
-    #[error("age decrypt error: {0}")]
-    RecipientDecrypt(#[from] age::DecryptError),
+impl From<io::Error> for Err {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
 
-    /// Broken pipe when writing output of encrypted age file
-    #[error("broken pipe when writing output: {0}")]
-    BrokenPipe(#[source] io::Error),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_recipient_rejects_invalid_entries() {
+        let recipient = age::x25519::Identity::generate().to_public().to_string();
+        assert!(parse_recipient(&recipient).is_ok());
+        assert!(parse_recipient(
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIDGkEvu4bbpPpLMfPOJtB2ZQm8aXYBlVk7sfYXJOmwvj"
+        )
+        .is_ok());
+
+        for invalid in ["", "not a recipient", "age1invalid", &recipient[1..]] {
+            assert!(
+                matches!(parse_recipient(invalid), Err(Err::InvalidRecipient(_))),
+                "accepted {invalid:?}"
+            );
+        }
+    }
 
-    /// Error when dealing with `PathBuf` conversions
-    #[error("pathbuf manipulation error: {0}")]
-    PathBufError(String),
+    #[test]
+    fn import_key_validates_recipients_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            identity_file: dir.path().join("identities"),
+            keyring_file:  dir.path().join("keyring"),
+        };
+        let recipient = age::x25519::Identity::generate().to_public().to_string();
+
+        // Nothing is imported if any entry is invalid
+        let invalid = format!("# Alice\n{recipient}\n\nnot a recipient\n");
+        assert!(import_key(&config, invalid.as_bytes()).is_err());
+        assert!(public_keys(&config).unwrap().is_empty());
+
+        let secret = "AGE-SECRET-KEY-1QQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQQ";
+        let err = import_key(&config, secret.as_bytes()).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Err::ImportSecret)));
+
+        let valid = format!("# Alice\n{recipient}\n");
+        import_key(&config, valid.as_bytes()).unwrap();
+        import_key(&config, valid.as_bytes()).unwrap();
+        let keys = public_keys(&config).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].recipient, recipient);
+        assert_eq!(keys[0].comment.as_deref(), Some("Alice"));
+    }
 }
@@ -0,0 +1,185 @@
+//! Provides rPGP library context adapter.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use pgp::SignedSecretKey;
+use secstr::SecUtf8;
+use thiserror::Error;
+
+use super::{raw, Config};
+use crate::{
+    crypto::{proto, Config as CryptoConfig, Err as CryptoErr, IsContext, Key, Proto},
+    vendor::shellexpand,
+    Ciphertext, Plaintext, Recipients,
+};
+
+/// Environment variable to override the keyring directory with.
+const KEYRING_ENV: &str = "PASSWORD_STORE_RPGP_KEYRING";
+
+/// Environment variable GnuPG uses for its home directory.
+const GNUPGHOME_ENV: &str = "GNUPGHOME";
+
+/// GnuPG public keyring file names, in order of preference.
+const GNUPG_KEYRING_FILES: [&str; 2] = ["pubring.kbx", "pubring.gpg"];
+
+/// Create rPGP context.
+pub fn context(_config: &CryptoConfig) -> Result<Context, Err> {
+    let mut config = Config::from(find_keyring_dir().ok_or(Err::NoKeyringDir)?);
+    config.gnupg_keyring = find_gnupg_keyring();
+    Ok(Context::from(config))
+}
+
+/// rPGP context.
+pub struct Context {
+    /// rPGP config.
+    config: Config,
+
+    /// Passphrases entered for secret keys, by fingerprint.
+    passphrases: HashMap<String, SecUtf8>,
+}
+
+impl Context {
+    /// Construct context from rPGP config.
+    fn from(config: Config) -> Self {
+        Self {
+            config,
+            passphrases: HashMap::new(),
+        }
+    }
+}
+
+impl IsContext for Context {
+    fn encrypt(&mut self, recipients: &Recipients, plaintext: Plaintext) -> Result<Ciphertext> {
+        let recipients: Vec<String> = recipients
+            .keys()
+            .iter()
+            .map(|key| key.fingerprint(false))
+            .collect();
+        let recipients: Vec<&str> = recipients.iter().map(String::as_str).collect();
+        raw::encrypt(&self.config, &recipients, &plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Ciphertext) -> Result<Plaintext> {
+        let passphrases = &mut self.passphrases;
+        raw::decrypt(&self.config, &ciphertext, &mut |key| {
+            prompt_passphrase(passphrases, key)
+        })
+    }
+
+    fn can_decrypt(&mut self, ciphertext: Ciphertext) -> Result<bool> {
+        raw::can_decrypt(&self.config, &ciphertext)
+    }
+
+    fn recipient_key_ids(&mut self, ciphertext: Ciphertext) -> Result<Option<Vec<String>>> {
+        Ok(Some(proto::gpg::recipient_key_ids(ciphertext.unsecure_ref())?))
+    }
+
+    fn keys_public(&mut self) -> Result<Vec<Key>> {
+        Ok(raw::public_keys(&self.config)?
+            .into_iter()
+            .map(proto::gpg::Key::into_key)
+            .collect())
+    }
+
+    fn keys_private(&mut self) -> Result<Vec<Key>> {
+        Ok(raw::private_keys(&self.config)?
+            .into_iter()
+            .map(proto::gpg::Key::into_key)
+            .collect())
+    }
+
+    fn key_ids(&mut self, key: &Key) -> Result<Vec<String>> {
+        raw::key_ids(&self.config, &key.fingerprint(false))
+    }
+
+    fn import_key(&mut self, key: &[u8]) -> Result<()> {
+        raw::import_key(&self.config, key)
+    }
+
+    fn export_key(&mut self, key: Key) -> Result<Vec<u8>> {
+        raw::export_key(&self.config, &key.fingerprint(false))
+    }
+
+    fn sign_detached(&mut self, key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+        let passphrases = &mut self.passphrases;
+        raw::sign_detached(&self.config, &key.fingerprint(false), data, &mut |key| {
+            prompt_passphrase(passphrases, key)
+        })
+    }
+
+    fn verify_detached_file(&mut self, path: &Path, signature: &Path) -> Result<Vec<String>> {
+        raw::verify_detached(
+            &self.config,
+            &fs::read(path).map_err(CryptoErr::ReadFile)?,
+            &fs::read(signature).map_err(CryptoErr::ReadFile)?,
+        )
+    }
+
+    fn supports_proto(&self, proto: Proto) -> bool {
+        proto == Proto::Gpg
+    }
+}
+
+/// Prompt for the passphrase of a protected secret key on the TTY.
+///
+/// Passphrases are remembered for the lifetime of the context.
+fn prompt_passphrase(
+    passphrases: &mut HashMap<String, SecUtf8>,
+    key: &SignedSecretKey,
+) -> Result<String> {
+    let fingerprint = raw::fingerprint(key);
+    if let Some(passphrase) = passphrases.get(&fingerprint) {
+        return Ok(passphrase.unsecure().into());
+    }
+
+    let passphrase = rpassword::prompt_password(format!(
+        "Enter passphrase for GPG key {}: ",
+        &fingerprint[fingerprint.len().saturating_sub(16)..]
+    ))
+    .map_err(Err::Passphrase)?;
+    passphrases.insert(fingerprint, SecUtf8::from(passphrase.as_str()));
+    Ok(passphrase)
+}
+
+/// Find keyring directory path.
+///
+/// Uses `PASSWORD_STORE_RPGP_KEYRING` if set, otherwise uses the prs
+/// configuration directory.
+fn find_keyring_dir() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(KEYRING_ENV).filter(|p| !p.is_empty()) {
+        return Some(path.into());
+    }
+
+    dirs_next::config_dir().map(|dir| dir.join("prs").join("rpgp"))
+}
+
+/// Find GnuPG public keyring path.
+///
+/// Uses the GnuPG home directory from `GNUPGHOME` or `~/.gnupg`. Returns
+/// `None` if no keyring exists.
+fn find_gnupg_keyring() -> Option<PathBuf> {
+    let home: PathBuf = match env::var_os(GNUPGHOME_ENV).filter(|p| !p.is_empty()) {
+        Some(path) => path.into(),
+        None => PathBuf::from(shellexpand::full("~/.gnupg").ok()?.as_ref()),
+    };
+
+    GNUPG_KEYRING_FILES
+        .iter()
+        .map(|file| home.join(file))
+        .find(|path| path.is_file())
+}
+
+/// rPGP context error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to determine rPGP keyring directory")]
+    NoKeyringDir,
+
+    #[error("failed to read passphrase for secret key")]
+    Passphrase(#[source] std::io::Error),
+}
@@ -0,0 +1,100 @@
+//! Read-only access to GnuPG public keyrings.
+//!
+//! Supports the keybox format (`pubring.kbx`) used since GnuPG 2.1, and the
+//! legacy keyring format (`pubring.gpg`) which is a plain list of OpenPGP
+//! packets.
+
+use std::{convert::TryInto, fs, path::Path};
+
+use anyhow::Result;
+use pgp::{
+    packet::{Packet, PacketParser},
+    Deserializable, SignedPublicKey,
+};
+use thiserror::Error;
+
+/// Keybox blob type for the first (header) blob.
+const BLOB_TYPE_HEADER: u8 = 1;
+
+/// Keybox blob type for OpenPGP key blobs.
+const BLOB_TYPE_OPENPGP: u8 = 2;
+
+/// Magic in the keybox header blob.
+const KEYBOX_MAGIC: &[u8] = b"KBXf";
+
+/// Read all public keys from the GnuPG keyring at the given path.
+///
+/// Keys that fail to parse are skipped.
+pub fn read_public_keys(path: &Path) -> Result<Vec<SignedPublicKey>> {
+    let data = fs::read(path).map_err(Err::Read)?;
+
+    if !is_keybox(&data) {
+        return Ok(parse_keys(&data));
+    }
+
+    let mut keys = Vec::new();
+    for keyblock in keybox_keyblocks(&data)? {
+        keys.extend(parse_keys(keyblock));
+    }
+    Ok(keys)
+}
+
+/// Check whether the given data is in keybox format.
+fn is_keybox(data: &[u8]) -> bool {
+    data.len() >= 12 && data[4] == BLOB_TYPE_HEADER && &data[8..12] == KEYBOX_MAGIC
+}
+
+/// Collect the OpenPGP keyblocks from all blobs in the given keybox.
+fn keybox_keyblocks(data: &[u8]) -> Result<Vec<&[u8]>, Err> {
+    let mut keyblocks = Vec::new();
+    let mut pos = 0;
+
+    while pos + 6 <= data.len() {
+        let blob_len = read_u32(data, pos)? as usize;
+        if blob_len < 6 || pos + blob_len > data.len() {
+            return Err(Err::Malformed);
+        }
+        let blob = &data[pos..pos + blob_len];
+
+        // Keyblock offset and length are relative to the blob start
+        if blob[4] == BLOB_TYPE_OPENPGP {
+            let offset = read_u32(blob, 8)? as usize;
+            let len = read_u32(blob, 12)? as usize;
+            keyblocks.push(blob.get(offset..offset + len).ok_or(Err::Malformed)?);
+        }
+
+        pos += blob_len;
+    }
+
+    Ok(keyblocks)
+}
+
+/// Read a big endian `u32` at the given position.
+fn read_u32(data: &[u8], pos: usize) -> Result<u32, Err> {
+    data.get(pos..pos + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u32::from_be_bytes)
+        .ok_or(Err::Malformed)
+}
+
+/// Parse all valid public keys from binary OpenPGP data.
+///
+/// GnuPG stores trust packets alongside keys which rPGP does not accept as part
+/// of a key, these and packets that fail to parse are skipped.
+fn parse_keys(data: &[u8]) -> Vec<SignedPublicKey> {
+    let packets = PacketParser::new(data)
+        .filter(|packet| !matches!(packet, Ok(Packet::Trust(_) | Packet::Marker(_)) | Err(_)));
+    SignedPublicKey::from_packets(packets.peekable())
+        .filter_map(Result::ok)
+        .collect()
+}
+
+/// Keybox error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to read GnuPG keyring")]
+    Read(#[source] std::io::Error),
+
+    #[error("failed to parse GnuPG keybox, malformed blob")]
+    Malformed,
+}
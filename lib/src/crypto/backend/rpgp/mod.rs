@@ -0,0 +1,35 @@
+//! Crypto backend using the pure-Rust rPGP library for GPG.
+//!
+//! This backend does not require GnuPG to be installed. Public and secret keys
+//! are read from a keyring directory, public keys are also read from the GnuPG
+//! keyring if available.
+
+pub mod context;
+pub mod keybox;
+pub mod raw;
+
+use std::path::PathBuf;
+
+/// rPGP config.
+pub struct Config {
+    /// Keyring directory holding public and secret keys.
+    pub keyring_dir: PathBuf,
+
+    /// GnuPG public keyring to read additional public keys from.
+    ///
+    /// This may be a keybox (`pubring.kbx`) or legacy keyring (`pubring.gpg`).
+    pub gnupg_keyring: Option<PathBuf>,
+}
+
+impl Config {
+    /// Construct with given keyring directory.
+    ///
+    /// - `keyring_dir`: path to keyring directory
+    #[must_use]
+    pub const fn from(keyring_dir: PathBuf) -> Self {
+        Self {
+            keyring_dir,
+            gnupg_keyring: None,
+        }
+    }
+}
@@ -0,0 +1,738 @@
+//! Raw interface to the rPGP library.
+//!
+//! This provides the most basic and bare functions to interface with the GPG
+//! protocol without GnuPG.
+
+use std::{
+    fs,
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{SubsecRound, Utc};
+use data_encoding::HEXUPPER;
+use pgp::{
+    composed::signed_key::{from_reader_many, PublicOrSecret},
+    crypto::{hash::HashAlgorithm, sym::SymmetricKeyAlgorithm},
+    packet::{
+        PublicKeyEncryptedSessionKey, SignatureConfig, SignatureType, Subpacket, SubpacketData,
+        SymEncryptedProtectedData,
+    },
+    ser::Serialize,
+    types::{KeyId, PublicKeyTrait, SecretKeyTrait},
+    ArmorOptions, Deserializable, Edata, Esk, Message, SignedPublicKey, SignedSecretKey,
+    StandaloneSignature,
+};
+use thiserror::Error;
+
+use super::{keybox, Config};
+use crate::{
+    crypto::{proto::gpg::Key, util},
+    Ciphertext, Plaintext,
+};
+
+/// Symmetric algorithm used to encrypt secrets.
+const SYMMETRIC_ALGORITHM: SymmetricKeyAlgorithm = SymmetricKeyAlgorithm::AES256;
+
+/// Hash algorithm used for signatures.
+const HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::SHA2_256;
+
+/// Keys loaded from the keyring directory and GnuPG keyring.
+#[derive(Default)]
+pub struct Keyring {
+    /// All public keys, including those of secret keys.
+    public: Vec<SignedPublicKey>,
+
+    /// All secret keys.
+    secret: Vec<SignedSecretKey>,
+}
+
+impl Keyring {
+    /// Add public key if not yet known.
+    fn add_public(&mut self, key: SignedPublicKey) {
+        let fp = fingerprint(&key);
+        if !self
+            .public
+            .iter()
+            .any(|other| util::fingerprints_equal(fingerprint(other), &fp))
+        {
+            self.public.push(key);
+        }
+    }
+
+    /// Add secret key, also adds its public key.
+    fn add_secret(&mut self, key: SignedSecretKey) {
+        self.add_public(SignedPublicKey::from(key.clone()));
+        self.secret.push(key);
+    }
+
+    /// Find public key by fingerprint.
+    fn find_public(&self, fp: &str) -> Option<&SignedPublicKey> {
+        self.public
+            .iter()
+            .find(|key| util::fingerprints_equal(fingerprint(*key), fp))
+    }
+
+    /// Find secret key by fingerprint.
+    fn find_secret(&self, fp: &str) -> Option<&SignedSecretKey> {
+        self.secret
+            .iter()
+            .find(|key| util::fingerprints_equal(fingerprint(*key), fp))
+    }
+}
+
+/// Encrypt plaintext for the given recipients.
+///
+/// Produces binary ciphertext, like GnuPG does for password store secrets.
+///
+/// - `config`: rPGP config
+/// - `recipients`: list of recipient fingerprints to encrypt for
+/// - `plaintext`: plaintext to encrypt
+///
+/// # Panics
+///
+/// Panics if list of recipients is empty.
+pub fn encrypt(config: &Config, recipients: &[&str], plaintext: &Plaintext) -> Result<Ciphertext> {
+    assert!(
+        !recipients.is_empty(),
+        "attempting to encrypt secret for empty list of recipients"
+    );
+
+    let keyring = load_keyring(config)?;
+    let mut rng = rand::thread_rng();
+    let session_key = SYMMETRIC_ALGORITHM.new_session_key(&mut rng);
+
+    // Encrypt session key for each recipient
+    let esk = recipients
+        .iter()
+        .map(|fp| {
+            let key = keyring
+                .find_public(fp)
+                .ok_or_else(|| Err::UnknownKey((*fp).into()))?;
+            encrypt_session_key(&mut rng, key, &session_key)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Encrypt literal data with session key
+    let data = Message::new_literal_bytes("", plaintext.unsecure_ref())
+        .to_bytes()
+        .map_err(Err::Encrypt)?;
+    let edata = Edata::SymEncryptedProtectedData(
+        SymEncryptedProtectedData::encrypt_seipdv1(
+            &mut rng,
+            SYMMETRIC_ALGORITHM,
+            &session_key,
+            &data,
+        )
+        .map_err(Err::Encrypt)?,
+    );
+
+    Ok(Ciphertext::from(
+        Message::Encrypted { esk, edata }
+            .to_bytes()
+            .map_err(Err::Encrypt)?,
+    ))
+}
+
+/// Decrypt ciphertext.
+///
+/// Both binary and ASCII armored ciphertext is supported.
+///
+/// - `config`: rPGP config
+/// - `ciphertext`: ciphertext to decrypt
+/// - `passphrase`: called to obtain the passphrase for a protected secret key
+pub fn decrypt(
+    config: &Config,
+    ciphertext: &Ciphertext,
+    passphrase: &mut dyn FnMut(&SignedSecretKey) -> Result<String>,
+) -> Result<Plaintext> {
+    let keyring = load_keyring(config)?;
+    let message = parse_message(ciphertext.unsecure_ref())?;
+    let pkesks = session_key_packets(&message);
+
+    for key in &keyring.secret {
+        let Some(protected) = match_secret_key(&pkesks, key) else {
+            continue;
+        };
+        let password = if protected {
+            passphrase(key)?
+        } else {
+            String::new()
+        };
+
+        let (message, _) = message
+            .decrypt(|| password.clone(), &[key])
+            .map_err(Err::Decrypt)?;
+        let content = message
+            .get_content()
+            .map_err(Err::Decrypt)?
+            .ok_or(Err::NoContent)?;
+        return Ok(Plaintext::from(content));
+    }
+
+    Err(Err::NoSecretKey.into())
+}
+
+/// Check whether we can decrypt ciphertext.
+///
+/// This checks whether any of our secret keys is a recipient of the
+/// ciphertext, the payload itself is not decrypted.
+///
+/// - `config`: rPGP config
+/// - `ciphertext`: ciphertext to check
+pub fn can_decrypt(config: &Config, ciphertext: &Ciphertext) -> Result<bool> {
+    let keyring = load_keyring(config)?;
+    let message = parse_message(ciphertext.unsecure_ref())?;
+    let pkesks = session_key_packets(&message);
+
+    Ok(keyring
+        .secret
+        .iter()
+        .any(|key| match_secret_key(&pkesks, key).is_some()))
+}
+
+/// Get all public keys from the keyring.
+///
+/// - `config`: rPGP config
+pub fn public_keys(config: &Config) -> Result<Vec<Key>> {
+    Ok(load_keyring(config)?.public.iter().map(to_key).collect())
+}
+
+/// Get all private/secret keys from the keyring.
+///
+/// - `config`: rPGP config
+pub fn private_keys(config: &Config) -> Result<Vec<Key>> {
+    Ok(load_keyring(config)?
+        .secret
+        .into_iter()
+        .map(|key| to_key(&SignedPublicKey::from(key)))
+        .collect())
+}
+
+/// Import the given key(s) into the keyring directory.
+///
+/// Accepts binary and ASCII armored public and secret keys. Each key is stored
+/// in its own file, named after its fingerprint.
+///
+/// - `config`: rPGP config
+/// - `key`: key data to import
+pub fn import_key(config: &Config, key: &[u8]) -> Result<()> {
+    let (keys, _) = from_reader_many(key).map_err(Err::Import)?;
+
+    fs::create_dir_all(&config.keyring_dir).map_err(Err::Keyring)?;
+
+    let mut imported = 0;
+    for key in keys {
+        match key.map_err(Err::Import)? {
+            PublicOrSecret::Public(key) => {
+                let data = key
+                    .to_armored_bytes(ArmorOptions::default())
+                    .map_err(Err::Import)?;
+                let path = key_path(config, &fingerprint(&key), false);
+                write_key_file(&path, &data, false)?;
+            },
+            PublicOrSecret::Secret(key) => {
+                let data = key
+                    .to_armored_bytes(ArmorOptions::default())
+                    .map_err(Err::Import)?;
+                let path = key_path(config, &fingerprint(&key), true);
+                write_key_file(&path, &data, true)?;
+            },
+        }
+        imported += 1;
+    }
+
+    if imported == 0 {
+        return Err(Err::NoKeys.into());
+    }
+
+    Ok(())
+}
+
+/// Get the key IDs of the key with the given fingerprint and its subkeys.
+///
+/// - `config`: rPGP config
+/// - `fingerprint`: fingerprint of the key
+pub fn key_ids(config: &Config, fingerprint: &str) -> Result<Vec<String>> {
+    let keyring = load_keyring(config)?;
+    let key = keyring
+        .find_public(fingerprint)
+        .ok_or_else(|| Err::UnknownKey(fingerprint.into()))?;
+    Ok(std::iter::once(key.key_id())
+        .chain(key.public_subkeys.iter().map(PublicKeyTrait::key_id))
+        .map(|id| HEXUPPER.encode(id.as_ref()))
+        .collect())
+}
+
+/// Export the public key with the given fingerprint as ASCII armored data.
+///
+/// - `config`: rPGP config
+/// - `fingerprint`: fingerprint of key to export
+pub fn export_key(config: &Config, fingerprint: &str) -> Result<Vec<u8>> {
+    let keyring = load_keyring(config)?;
+    let key = keyring
+        .find_public(fingerprint)
+        .ok_or_else(|| Err::UnknownKey(fingerprint.into()))?;
+    Ok(key
+        .to_armored_bytes(ArmorOptions::default())
+        .map_err(Err::Export)?)
+}
+
+/// Create an ASCII armored detached signature for data.
+///
+/// Signs with the newest signing subkey, falling back to the primary key.
+///
+/// - `config`: rPGP config
+/// - `fingerprint`: fingerprint of the secret key to sign with
+/// - `data`: data to sign
+/// - `passphrase`: called to obtain the passphrase for a protected secret key
+pub fn sign_detached(
+    config: &Config,
+    fingerprint: &str,
+    data: &[u8],
+    passphrase: &mut dyn FnMut(&SignedSecretKey) -> Result<String>,
+) -> Result<Vec<u8>> {
+    let keyring = load_keyring(config)?;
+    let key = keyring
+        .find_secret(fingerprint)
+        .ok_or_else(|| Err::UnknownKey(fingerprint.into()))?;
+
+    let subkey = key
+        .secret_subkeys
+        .iter()
+        .filter(|subkey| {
+            subkey.key.is_signing_key()
+                && subkey.signatures.iter().any(|sig| sig.key_flags().sign())
+        })
+        .max_by_key(|subkey| *subkey.key.created_at());
+
+    let protected = subkey.map_or_else(
+        || key.primary_key.secret_params().is_encrypted(),
+        |subkey| subkey.key.secret_params().is_encrypted(),
+    );
+    let password = if protected {
+        passphrase(key)?
+    } else {
+        String::new()
+    };
+
+    let signature = match subkey {
+        Some(subkey) => create_signature(&subkey.key, password, data),
+        None => create_signature(&key.primary_key, password, data),
+    }?;
+    Ok(signature
+        .to_armored_bytes(ArmorOptions::default())
+        .map_err(Err::Sign)?)
+}
+
+/// Verify a detached signature for data.
+///
+/// Returns the primary key fingerprints of all valid signatures. Signatures
+/// made by a subkey must have a valid binding to their primary key.
+///
+/// - `config`: rPGP config
+/// - `data`: signed data
+/// - `signature`: binary or ASCII armored detached signature
+pub fn verify_detached(config: &Config, data: &[u8], signature: &[u8]) -> Result<Vec<String>> {
+    let keyring = load_keyring(config)?;
+    let (signatures, _) = StandaloneSignature::from_reader_many(signature).map_err(Err::Parse)?;
+
+    Ok(signatures
+        .filter_map(Result::ok)
+        .filter_map(|signature| {
+            keyring
+                .public
+                .iter()
+                .find(|key| is_signed_by(&signature, key, data))
+        })
+        .map(fingerprint)
+        .collect())
+}
+
+/// Load all keys from the keyring directory and the GnuPG keyring.
+///
+/// Files that don't contain valid keys are skipped.
+fn load_keyring(config: &Config) -> Result<Keyring, Err> {
+    let mut keyring = Keyring::default();
+
+    if config.keyring_dir.is_dir() {
+        let entries = fs::read_dir(&config.keyring_dir).map_err(Err::Keyring)?;
+        for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+            let hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_none_or(|name| name.starts_with('.'));
+            if hidden || !path.is_file() {
+                continue;
+            }
+
+            let file = fs::File::open(&path).map_err(Err::Keyring)?;
+            let Ok((keys, _)) = from_reader_many(file) else {
+                continue;
+            };
+            for key in keys.filter_map(Result::ok) {
+                match key {
+                    PublicOrSecret::Public(key) => keyring.add_public(key),
+                    PublicOrSecret::Secret(key) => keyring.add_secret(key),
+                }
+            }
+        }
+    }
+
+    if let Some(path) = config.gnupg_keyring.as_ref().filter(|p| p.is_file()) {
+        for key in keybox::read_public_keys(path).map_err(Err::GnupgKeyring)? {
+            keyring.add_public(key);
+        }
+    }
+
+    Ok(keyring)
+}
+
+/// Parse a binary or ASCII armored message.
+fn parse_message(ciphertext: &[u8]) -> Result<Message, Err> {
+    // Binary OpenPGP data always has the high bit of the first byte set
+    let binary = ciphertext.first().is_some_and(|b| b & 0x80 != 0);
+    if binary {
+        Message::from_bytes(ciphertext)
+    } else {
+        Message::from_armor_single(ciphertext).map(|(message, _)| message)
+    }
+    .map_err(Err::Parse)
+}
+
+/// Get the public key encrypted session key packets from a message.
+fn session_key_packets(message: &Message) -> Vec<&PublicKeyEncryptedSessionKey> {
+    match message {
+        Message::Encrypted { esk, .. } => esk
+            .iter()
+            .filter_map(|esk| match esk {
+                Esk::PublicKeyEncryptedSessionKey(pkesk) => Some(pkesk),
+                Esk::SymKeyEncryptedSessionKey(_) => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Check whether the secret key or any of its subkeys is a recipient.
+///
+/// Returns `None` if the key is not a recipient. Otherwise returns whether the
+/// matching (sub)key is protected by a passphrase.
+fn match_secret_key(
+    pkesks: &[&PublicKeyEncryptedSessionKey],
+    key: &SignedSecretKey,
+) -> Option<bool> {
+    if pkesks
+        .iter()
+        .any(|pkesk| pkesk.match_identity(&key.primary_key))
+    {
+        return Some(key.primary_key.secret_params().is_encrypted());
+    }
+
+    key.secret_subkeys
+        .iter()
+        .find(|subkey| pkesks.iter().any(|pkesk| pkesk.match_identity(*subkey)))
+        .map(|subkey| subkey.key.secret_params().is_encrypted())
+}
+
+/// Encrypt the session key for the given recipient key.
+///
+/// Uses the newest subkey that may be used for encryption, falling back to the
+/// primary key.
+fn encrypt_session_key(
+    rng: &mut rand::rngs::ThreadRng,
+    key: &SignedPublicKey,
+    session_key: &[u8],
+) -> Result<Esk, Err> {
+    let subkey = key
+        .public_subkeys
+        .iter()
+        .filter(|subkey| {
+            subkey.key.is_encryption_key()
+                && subkey.signatures.iter().any(|sig| {
+                    let flags = sig.key_flags();
+                    flags.encrypt_comms() || flags.encrypt_storage()
+                })
+        })
+        .max_by_key(|subkey| *subkey.key.created_at());
+
+    let pkesk = match subkey {
+        Some(subkey) => PublicKeyEncryptedSessionKey::from_session_key_v3(
+            rng,
+            session_key,
+            SYMMETRIC_ALGORITHM,
+            subkey,
+        ),
+        None if key.is_encryption_key() => PublicKeyEncryptedSessionKey::from_session_key_v3(
+            rng,
+            session_key,
+            SYMMETRIC_ALGORITHM,
+            key,
+        ),
+        None => return Err(Err::NoEncryptionKey(fingerprint(key))),
+    }
+    .map_err(Err::Encrypt)?;
+
+    Ok(Esk::PublicKeyEncryptedSessionKey(pkesk))
+}
+
+/// Create a binary detached signature for data with the given secret key.
+fn create_signature(
+    key: &impl SecretKeyTrait,
+    password: String,
+    data: &[u8],
+) -> Result<StandaloneSignature, Err> {
+    let mut config = SignatureConfig::v4(SignatureType::Binary, key.algorithm(), HASH_ALGORITHM);
+    config.hashed_subpackets = vec![
+        Subpacket::regular(SubpacketData::IssuerFingerprint(key.fingerprint())),
+        Subpacket::regular(SubpacketData::SignatureCreationTime(
+            Utc::now().trunc_subsecs(0),
+        )),
+    ];
+    config.unhashed_subpackets = vec![Subpacket::regular(SubpacketData::Issuer(key.key_id()))];
+
+    let signature = config.sign(key, || password, data).map_err(Err::Sign)?;
+    Ok(StandaloneSignature::new(signature))
+}
+
+/// Check whether a signature over data is made by the key or one of its
+/// subkeys.
+fn is_signed_by(signature: &StandaloneSignature, key: &SignedPublicKey, data: &[u8]) -> bool {
+    let issuers = signature.signature.issuer();
+    let issued_by = |id: &KeyId| issuers.is_empty() || issuers.contains(&id);
+
+    if issued_by(&key.key_id()) && signature.verify(key, data).is_ok() {
+        return true;
+    }
+
+    key.public_subkeys.iter().any(|subkey| {
+        issued_by(&subkey.key_id())
+            && subkey.verify(&key.primary_key).is_ok()
+            && signature.verify(subkey, data).is_ok()
+    })
+}
+
+/// Get the uppercase hexadecimal fingerprint of a key.
+pub(super) fn fingerprint(key: &impl PublicKeyTrait) -> String {
+    HEXUPPER.encode(key.fingerprint().as_bytes())
+}
+
+/// Transform into GPG key.
+fn to_key(key: &SignedPublicKey) -> Key {
+    Key {
+        fingerprint: fingerprint(key),
+        user_ids:    key
+            .details
+            .users
+            .iter()
+            .map(|user| String::from_utf8_lossy(user.id.id()).into_owned())
+            .collect(),
+    }
+}
+
+/// Path of the keyring file for the key with the given fingerprint.
+fn key_path(config: &Config, fingerprint: &str, secret: bool) -> PathBuf {
+    let suffix = if secret { ".secret.asc" } else { ".asc" };
+    config.keyring_dir.join(format!("{fingerprint}{suffix}"))
+}
+
+/// Write a key file, secret key files are only readable by the user.
+fn write_key_file(path: &Path, contents: &[u8], secret: bool) -> Result<(), Err> {
+    let mode = if secret { 0o600 } else { 0o644 };
+    let mut file = fs::OpenOptions::new()
+        .mode(mode)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(Err::Keyring)?;
+    file.write_all(contents).map_err(Err::Keyring)
+}
+
+/// rPGP error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to encrypt plaintext")]
+    Encrypt(#[source] pgp::errors::Error),
+
+    #[error("failed to decrypt ciphertext")]
+    Decrypt(#[source] pgp::errors::Error),
+
+    #[error("failed to parse ciphertext")]
+    Parse(#[source] pgp::errors::Error),
+
+    #[error("failed to decrypt ciphertext, message has no content")]
+    NoContent,
+
+    #[error("failed to decrypt ciphertext, no matching secret key in keyring")]
+    NoSecretKey,
+
+    #[error("no key in keyring for fingerprint: {0}")]
+    UnknownKey(String),
+
+    #[error("key cannot be used for encryption: {0}")]
+    NoEncryptionKey(String),
+
+    #[error("failed to import key")]
+    Import(#[source] pgp::errors::Error),
+
+    #[error("failed to import key, no keys found in key data")]
+    NoKeys,
+
+    #[error("failed to export key")]
+    Export(#[source] pgp::errors::Error),
+
+    #[error("failed to sign data")]
+    Sign(#[source] pgp::errors::Error),
+
+    #[error("failed to read or write keyring")]
+    Keyring(#[source] std::io::Error),
+
+    #[error("failed to read GnuPG keyring")]
+    GnupgKeyring(#[source] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use pgp::{crypto::ecc_curve::ECCCurve, KeyType, SecretKeyParamsBuilder, SubkeyParamsBuilder};
+
+    use super::*;
+
+    /// Generate a secret key with an encryption subkey, primary key signs.
+    fn generate_key(user_id: &str, passphrase: Option<&str>) -> SignedSecretKey {
+        let mut rng = rand::thread_rng();
+        let subkey = SubkeyParamsBuilder::default()
+            .key_type(KeyType::ECDH(ECCCurve::Curve25519))
+            .can_encrypt(true)
+            .passphrase(passphrase.map(Into::into))
+            .build()
+            .unwrap();
+        let password = passphrase.unwrap_or_default().to_string();
+        SecretKeyParamsBuilder::default()
+            .key_type(KeyType::EdDSALegacy)
+            .can_certify(true)
+            .can_sign(true)
+            .primary_user_id(user_id.into())
+            .passphrase(passphrase.map(Into::into))
+            .subkey(subkey)
+            .build()
+            .unwrap()
+            .generate(&mut rng)
+            .unwrap()
+            .sign(&mut rng, || password)
+            .unwrap()
+    }
+
+    /// Create a keyring in a temporary directory holding the given key.
+    fn keyring_with(key: &SignedSecretKey) -> (tempfile::TempDir, Config) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::from(dir.path().join("keyring"));
+        let armored = key.to_armored_bytes(ArmorOptions::default()).unwrap();
+        import_key(&config, &armored).unwrap();
+        (dir, config)
+    }
+
+    /// Passphrase callback that must not be called.
+    fn no_passphrase(_: &SignedSecretKey) -> Result<String> {
+        panic!("asked passphrase for unprotected key");
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let key = generate_key("Alice <alice@example.com>", None);
+        let (_dir, config) = keyring_with(&key);
+        let fp = fingerprint(&key);
+
+        let plaintext = Plaintext::from("secret\nline two");
+        let ciphertext = encrypt(&config, &[&fp], &plaintext).unwrap();
+        assert!(can_decrypt(&config, &ciphertext).unwrap());
+        let decrypted = decrypt(&config, &ciphertext, &mut no_passphrase).unwrap();
+        assert!(decrypted == plaintext);
+
+        // Cannot decrypt without the secret key
+        let other = generate_key("Bob <bob@example.com>", None);
+        let (_other_dir, other_config) = keyring_with(&other);
+        assert!(!can_decrypt(&other_config, &ciphertext).unwrap());
+        assert!(decrypt(&other_config, &ciphertext, &mut no_passphrase).is_err());
+    }
+
+    #[test]
+    fn decrypt_protected_key_asks_passphrase() {
+        let key = generate_key("Alice <alice@example.com>", Some("hunter2"));
+        let (_dir, config) = keyring_with(&key);
+        let fp = fingerprint(&key);
+
+        let plaintext = Plaintext::from("secret");
+        let ciphertext = encrypt(&config, &[&fp], &plaintext).unwrap();
+
+        let mut asked = 0;
+        let decrypted = decrypt(&config, &ciphertext, &mut |_| {
+            asked += 1;
+            Ok("hunter2".into())
+        })
+        .unwrap();
+        assert_eq!(asked, 1);
+        assert!(decrypted == plaintext);
+
+        assert!(decrypt(&config, &ciphertext, &mut |_| Ok("wrong".into())).is_err());
+    }
+
+    #[test]
+    fn sign_verify_detached() {
+        let key = generate_key("Alice <alice@example.com>", None);
+        let (_dir, config) = keyring_with(&key);
+        let fp = fingerprint(&key);
+
+        let data = b"0123456789ABCDEF\n";
+        let signature = sign_detached(&config, &fp, data, &mut no_passphrase).unwrap();
+        assert_eq!(verify_detached(&config, data, &signature).unwrap(), [fp]);
+
+        // Tampered data or unknown signer yields no valid signatures
+        assert!(verify_detached(&config, b"0123456789ABCDEE\n", &signature)
+            .unwrap()
+            .is_empty());
+        let other = generate_key("Bob <bob@example.com>", None);
+        let (_other_dir, other_config) = keyring_with(&other);
+        assert!(verify_detached(&other_config, data, &signature)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn import_export_key() {
+        let key = generate_key("Alice <alice@example.com>", None);
+        let (_dir, config) = keyring_with(&key);
+        let fp = fingerprint(&key);
+
+        assert!(key_path(&config, &fp, true).is_file());
+        let private: Vec<_> = private_keys(&config)
+            .unwrap()
+            .into_iter()
+            .map(|key| key.fingerprint)
+            .collect();
+        assert_eq!(private, [fp.clone()]);
+        assert_eq!(key_ids(&config, &fp).unwrap().len(), 2);
+
+        // Exported public key imports into other keyring, without secret
+        let exported = export_key(&config, &fp).unwrap();
+        assert!(exported.starts_with(b"-----BEGIN PGP PUBLIC KEY BLOCK-----"));
+        let other = tempfile::tempdir().unwrap();
+        let other_config = Config::from(other.path().to_path_buf());
+        import_key(&other_config, &exported).unwrap();
+        assert!(key_path(&other_config, &fp, false).is_file());
+        let public = public_keys(&other_config).unwrap();
+        assert_eq!(public.len(), 1);
+        assert_eq!(public[0].fingerprint, fp);
+        assert_eq!(public[0].user_ids, ["Alice <alice@example.com>"]);
+        assert!(private_keys(&other_config).unwrap().is_empty());
+
+        // Encrypting for the imported public key works
+        let ciphertext = encrypt(&other_config, &[&fp], &Plaintext::from("secret")).unwrap();
+        assert!(can_decrypt(&config, &ciphertext).unwrap());
+
+        assert!(import_key(&other_config, b"").is_err());
+        assert!(export_key(&other_config, "0000").is_err());
+    }
+}
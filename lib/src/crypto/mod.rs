@@ -3,8 +3,8 @@
 //! This module provides an interface to all cryptography features that are used
 //! in prs.
 //!
-//! It supports multiple cryptography protocols (e.g. GPG) and multiple backends
-//! (e.g. GPGME, GnuPG). The list of supported protocols and backends may be
+//! It supports multiple cryptography protocols (e.g. GPG, age) and multiple
+//! backends (e.g. GPGME, GnuPG, rPGP, rage). The list of supported protocols and backends may be
 //! extended in the future.
 
 pub mod backend;
@@ -13,12 +13,21 @@ pub mod recipients;
 pub mod store;
 pub mod util;
 
-use std::{collections::HashMap, fmt, fs, io::Write, os::unix::fs::OpenOptionsExt, path::Path};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
 use thiserror::Error;
 
-use crate::{Ciphertext, Plaintext, Recipients, STORE_UMASK};
+use crate::{Ciphertext, Plaintext, Recipients};
+
+/// Key ID of hidden recipients, in key IDs ciphertext is encrypted for.
+///
+/// See [`IsContext::recipient_key_ids`].
+pub const WILDCARD_KEY_ID: &str = "0000000000000000";
 
 /// Crypto protocol.
 ///
@@ -29,6 +38,9 @@ use crate::{Ciphertext, Plaintext, Recipients, STORE_UMASK};
 pub enum Proto {
     /// GPG crypto.
     Gpg,
+
+    /// age crypto.
+    Age,
 }
 
 impl Proto {
@@ -37,6 +49,16 @@ impl Proto {
     pub const fn name(&self) -> &str {
         match self {
             Self::Gpg => "GPG",
+            Self::Age => "age",
+        }
+    }
+
+    /// Get the file suffix used for secrets encrypted with this protocol.
+    #[must_use]
+    pub const fn secret_suffix(&self) -> &'static str {
+        match self {
+            Self::Gpg => crate::store::SECRET_SUFFIX,
+            Self::Age => crate::store::SECRET_SUFFIX_AGE,
         }
     }
 }
@@ -50,6 +72,11 @@ pub struct Config {
 
     /// Use TTY for password input with GPG.
     pub gpg_tty: bool,
+
+    /// Identity file to decrypt with when using age.
+    ///
+    /// Uses the default identity file location if not set.
+    pub age_identity: Option<PathBuf>,
 }
 
 impl Config {
@@ -59,6 +86,7 @@ impl Config {
         Self {
             proto,
             gpg_tty: false,
+            age_identity: None,
         }
     }
 }
@@ -72,6 +100,10 @@ pub enum Key {
     /// An GPG key.
     #[cfg(feature = "_crypto-gpg")]
     Gpg(proto::gpg::Key),
+
+    /// An age key.
+    #[cfg(feature = "_crypto-age")]
+    Age(proto::age::Key),
 }
 
 impl Key {
@@ -81,6 +113,8 @@ impl Key {
         match self {
             #[cfg(feature = "_crypto-gpg")]
             Key::Gpg(_) => Proto::Gpg,
+            #[cfg(feature = "_crypto-age")]
+            Key::Age(_) => Proto::Age,
         }
     }
 
@@ -90,6 +124,8 @@ impl Key {
         match self {
             #[cfg(feature = "_crypto-gpg")]
             Key::Gpg(key) => key.fingerprint(short),
+            #[cfg(feature = "_crypto-age")]
+            Key::Age(key) => key.fingerprint(short),
         }
     }
 
@@ -99,6 +135,8 @@ impl Key {
         match self {
             #[cfg(feature = "_crypto-gpg")]
             Key::Gpg(key) => key.display_user(),
+            #[cfg(feature = "_crypto-age")]
+            Key::Age(key) => key.display_user(),
         }
     }
 }
@@ -138,6 +176,16 @@ pub fn context(config: &Config) -> Result<Context, Err> {
                 backend::gnupg_bin::context::context(config)
                     .map_err(|err| Err::Context(err.into()))?,
             )));
+            #[cfg(feature = "backend-rpgp")]
+            return Ok(Context::from(Box::new(
+                backend::rpgp::context::context(config).map_err(|err| Err::Context(err.into()))?,
+            )));
+        },
+        Proto::Age => {
+            #[cfg(feature = "backend-age")]
+            return Ok(Context::from(Box::new(
+                backend::rage::context::context(config).map_err(|err| Err::Context(err.into()))?,
+            )));
         },
     }
 
@@ -159,6 +207,9 @@ impl Context {
 
 impl IsContext for Context {
     fn encrypt(&mut self, recipients: &Recipients, plaintext: Plaintext) -> Result<Ciphertext> {
+        if recipients.keys().is_empty() {
+            return Err(Err::NoRecipients.into());
+        }
         self.context.encrypt(recipients, plaintext)
     }
 
@@ -170,6 +221,10 @@ impl IsContext for Context {
         self.context.can_decrypt(ciphertext)
     }
 
+    fn recipient_key_ids(&mut self, ciphertext: Ciphertext) -> Result<Option<Vec<String>>> {
+        self.context.recipient_key_ids(ciphertext)
+    }
+
     fn keys_public(&mut self) -> Result<Vec<Key>> {
         self.context.keys_public()
     }
@@ -178,6 +233,10 @@ impl IsContext for Context {
         self.context.keys_private()
     }
 
+    fn key_ids(&mut self, key: &Key) -> Result<Vec<String>> {
+        self.context.key_ids(key)
+    }
+
     fn import_key(&mut self, key: &[u8]) -> Result<()> {
         self.context.import_key(key)
     }
@@ -186,6 +245,14 @@ impl IsContext for Context {
         self.context.export_key(key)
     }
 
+    fn sign_detached(&mut self, key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+        self.context.sign_detached(key, data)
+    }
+
+    fn verify_detached_file(&mut self, path: &Path, signature: &Path) -> Result<Vec<String>> {
+        self.context.verify_detached_file(path, signature)
+    }
+
     fn supports_proto(&self, proto: Proto) -> bool {
         self.context.supports_proto(proto)
     }
@@ -206,17 +273,9 @@ pub trait IsContext {
         plaintext: Plaintext,
         path: &Path,
     ) -> Result<()> {
-        let mut file = fs::OpenOptions::new()
-            .mode(0o666 - (0o666 & *STORE_UMASK))
-            .write(true)
-            .create(true)
-            .open(&path)?;
-
-        file.write_all(self.encrypt(recipients, plaintext)?.unsecure_ref())
+        let ciphertext = self.encrypt(recipients, plaintext)?;
+        crate::util::fs::write_atomic(path, ciphertext.unsecure_ref())
             .map_err(|err| Err::WriteFile(err).into())
-
-        // fs::write(path, self.encrypt(recipients, plaintext)?.unsecure_ref())
-        //     .map_err(|err| Err::WriteFile(err).into())
     }
 
     /// Decrypt ciphertext.
@@ -235,6 +294,17 @@ pub trait IsContext {
         self.can_decrypt(fs::read(path).map_err(Err::ReadFile)?.into())
     }
 
+    /// Obtain the key IDs ciphertext is encrypted for.
+    ///
+    /// Returns `None` if the protocol does not expose the recipients of
+    /// ciphertext. Hidden recipients have the [`WILDCARD_KEY_ID`] key ID.
+    fn recipient_key_ids(&mut self, ciphertext: Ciphertext) -> Result<Option<Vec<String>>>;
+
+    /// Obtain the key IDs ciphertext from file is encrypted for.
+    fn recipient_key_ids_file(&mut self, path: &Path) -> Result<Option<Vec<String>>> {
+        self.recipient_key_ids(fs::read(path).map_err(Err::ReadFile)?.into())
+    }
+
     /// Obtain all public keys from keychain.
     fn keys_public(&mut self) -> Result<Vec<Key>>;
 
@@ -264,6 +334,11 @@ pub trait IsContext {
             .collect())
     }
 
+    /// Obtain the key IDs of the given key and its subkeys from keychain.
+    ///
+    /// Ciphertext encrypted for the key refers to it by one of these IDs.
+    fn key_ids(&mut self, key: &Key) -> Result<Vec<String>>;
+
     /// Import the given key from bytes into keychain.
     fn import_key(&mut self, key: &[u8]) -> Result<()>;
 
@@ -277,19 +352,20 @@ pub trait IsContext {
 
     /// Export the given key from the keychain to a file.
     fn export_key_file(&mut self, key: Key, path: &Path) -> Result<()> {
-        let mut file = fs::OpenOptions::new()
-            .mode(0o666 - (0o666 & *STORE_UMASK))
-            .write(true)
-            .create(true)
-            .open(&path)?;
-
-        file.write_all(&self.export_key(key)?)
+        crate::util::fs::write_atomic(path, &self.export_key(key)?)
             .map_err(|err| Err::WriteFile(err).into())
-
-        // fs::write(path, self.export_key(key)?).map_err(|err|
-        // Err::WriteFile(err).into())
     }
 
+    /// Create an ASCII armored detached signature for data with the given
+    /// secret key.
+    fn sign_detached(&mut self, key: &Key, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verify a detached signature file for the given file.
+    ///
+    /// Returns the primary key fingerprints of all valid signatures. Signatures
+    /// that are invalid or made by unknown keys are ignored.
+    fn verify_detached_file(&mut self, path: &Path, signature: &Path) -> Result<Vec<String>>;
+
     /// Check whether this context supports the given protocol.
     fn supports_proto(&self, proto: Proto) -> bool;
 }
@@ -316,18 +392,18 @@ impl ContextPool {
     ///
     /// This will initialize the context if no context is loaded for the given
     /// proto yet. This may error..
-    pub fn get_mut<'a>(&'a mut self, config: &'a Config) -> Result<&'a mut Context> {
-        Ok(self
-            .contexts
-            .entry(config.proto)
-            .or_insert(context(config)?))
+    pub fn get_mut(&mut self, config: &Config) -> Result<&mut Context> {
+        Ok(match self.contexts.entry(config.proto) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(context(config)?),
+        })
     }
 }
 
 /// Crypto error.
 #[derive(Debug, Error)]
 pub enum Err {
-    #[error("failed to obtain GPGME cryptography context")]
+    #[error("failed to obtain cryptography context")]
     Context(#[source] anyhow::Error),
 
     #[error("failed to built context, protocol not supportd: {:?}", _0)]
@@ -341,6 +417,9 @@ pub enum Err {
 
     #[error("fingerprint does not match public key in keychain")]
     UnknownFingerprint,
+
+    #[error("failed to encrypt, no recipients configured for this protocol")]
+    NoRecipients,
 }
 
 /// Prelude for common crypto traits.
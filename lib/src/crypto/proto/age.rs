@@ -0,0 +1,184 @@
+//! Crypto age protocol.
+
+/// Represents an age key.
+///
+/// This is the public recipient part of an age identity, such as a native
+/// `age1...` X25519 recipient or an SSH public key.
+#[derive(Clone)]
+pub struct Key {
+    /// Recipient string.
+    pub recipient: String,
+
+    /// Optional displayable comment, stored on the line above the recipient.
+    pub comment: Option<String>,
+}
+
+impl Key {
+    /// Key fingerprint.
+    ///
+    /// The recipient itself is used as fingerprint. The short variant only
+    /// holds the last 16 characters of the key material.
+    #[must_use]
+    pub fn fingerprint(&self, short: bool) -> String {
+        let recipient = self.recipient.trim();
+        if short {
+            // SSH recipients are formatted as `<type> <key> [comment]`
+            let material = recipient.split_whitespace().nth(1).unwrap_or(recipient);
+            material[material.len().saturating_sub(16)..].into()
+        } else {
+            recipient.into()
+        }
+    }
+
+    /// Key displayable user data.
+    #[must_use]
+    pub fn display_user(&self) -> String {
+        self.comment
+            .clone()
+            .unwrap_or_else(|| self.recipient.trim().into())
+    }
+
+    /// Transform into generic key.
+    #[must_use]
+    pub const fn into_key(self) -> crate::crypto::Key {
+        crate::crypto::Key::Age(self)
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.recipient.trim() == other.recipient.trim()
+    }
+}
+
+/// Parse keys from the contents of an age recipients file.
+///
+/// Empty lines are skipped. A `#` comment directly above a recipient is
+/// attached to it as displayable comment, other comments are ignored.
+#[must_use]
+pub fn parse_recipients(contents: &str) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut comment = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() {
+            comment = None;
+        } else if let Some(c) = line.strip_prefix('#') {
+            comment = Some(c.trim().to_string()).filter(|c| !c.is_empty());
+        } else {
+            keys.push(Key {
+                recipient: line.into(),
+                comment: comment.take(),
+            });
+        }
+    }
+
+    keys
+}
+
+/// Format keys into the contents of an age recipients file.
+///
+/// This is the inverse of [`parse_recipients`].
+#[must_use]
+pub fn format_recipients(keys: &[Key]) -> String {
+    let mut contents = String::new();
+    for key in keys {
+        if let Some(comment) = &key.comment {
+            contents.push_str("# ");
+            contents.push_str(comment);
+            contents.push('\n');
+        }
+        contents.push_str(key.recipient.trim());
+        contents.push('\n');
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT_A: &str = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+    const RECIPIENT_B: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIDGkEvu4bbpPpLMfPOJtB2ZQm8aXYBlVk7sfYXJOmwvj alice";
+
+    #[test]
+    fn parse_comments_and_blank_lines() {
+        let contents = [
+            "# Alice",
+            RECIPIENT_A,
+            "",
+            "# file header, not attached",
+            "",
+            "  # Bob  ",
+            &format!("  {RECIPIENT_B}  "),
+            "#",
+            RECIPIENT_A,
+            "# trailing",
+        ]
+        .join("\n");
+        let keys = parse_recipients(&contents);
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].recipient, RECIPIENT_A);
+        assert_eq!(keys[0].comment.as_deref(), Some("Alice"));
+        assert_eq!(keys[1].recipient, RECIPIENT_B);
+        assert_eq!(keys[1].comment.as_deref(), Some("Bob"));
+        assert_eq!(keys[2].recipient, RECIPIENT_A);
+        assert_eq!(keys[2].comment, None);
+
+        assert!(parse_recipients("").is_empty());
+        assert!(parse_recipients("\n  \n# only a comment\n").is_empty());
+    }
+
+    #[test]
+    fn parse_keeps_invalid_entries() {
+        // Entries are validated by the backend when used, not when parsed, so
+        // they are never dropped silently
+        let keys = parse_recipients("not a recipient\nAGE-SECRET-KEY-1XYZ\n");
+        let recipients: Vec<_> = keys.iter().map(|key| key.recipient.as_str()).collect();
+        assert_eq!(recipients, ["not a recipient", "AGE-SECRET-KEY-1XYZ"]);
+    }
+
+    #[test]
+    fn format_parse_round_trip() {
+        let keys = vec![
+            Key {
+                recipient: RECIPIENT_A.into(),
+                comment:   Some("Alice <alice@example.com>".into()),
+            },
+            Key {
+                recipient: format!(" {RECIPIENT_B} "),
+                comment:   None,
+            },
+        ];
+        let contents = format_recipients(&keys);
+        assert_eq!(
+            contents,
+            format!("# Alice <alice@example.com>\n{RECIPIENT_A}\n{RECIPIENT_B}\n")
+        );
+
+        let parsed = parse_recipients(&contents);
+        assert!(parsed == keys);
+        assert_eq!(parsed[0].comment, keys[0].comment);
+        assert_eq!(parsed[1].comment, None);
+        assert_eq!(format_recipients(&parsed), contents);
+        assert_eq!(format_recipients(&[]), "");
+    }
+
+    #[test]
+    fn short_fingerprint() {
+        let key = Key {
+            recipient: RECIPIENT_B.into(),
+            comment:   None,
+        };
+        assert_eq!(key.fingerprint(false), RECIPIENT_B);
+        assert_eq!(key.fingerprint(true), "YBlVk7sfYXJOmwvj");
+
+        let key = Key {
+            recipient: RECIPIENT_A.into(),
+            comment:   None,
+        };
+        assert_eq!(key.fingerprint(true), "2kg5sfn9aqmcac8p");
+    }
+}
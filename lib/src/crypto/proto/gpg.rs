@@ -1,5 +1,28 @@
 //! Crypto GPG protocol.
 
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use data_encoding::{BASE64, HEXUPPER};
+use thiserror::Error;
+
+use crate::crypto::{self, util};
+
+/// Key ID used in session key packets for hidden recipients.
+pub use crate::crypto::WILDCARD_KEY_ID;
+
+/// OpenPGP packet tag: public-key encrypted session key.
+const TAG_PKESK: u8 = 1;
+
+/// OpenPGP packet tag: symmetric-key encrypted session key.
+const TAG_SKESK: u8 = 3;
+
+/// OpenPGP packet tag: marker packet.
+const TAG_MARKER: u8 = 10;
+
+/// Header of ASCII armored OpenPGP messages.
+const ARMOR_HEADER: &str = "-----BEGIN PGP MESSAGE-----";
+
 /// Represents a GPG key.
 #[derive(Clone)]
 pub struct Key {
@@ -41,3 +64,308 @@ impl PartialEq for Key {
         self.fingerprint.trim().to_uppercase() == other.fingerprint.trim().to_uppercase()
     }
 }
+
+/// Get the key IDs a GPG message is encrypted for.
+///
+/// This parses the public-key encrypted session key packets at the start of the
+/// message, the payload is not decrypted. Key IDs are returned as 16 character
+/// uppercase hexadecimal strings, and usually identify an encryption subkey
+/// rather than the primary key. Hidden recipients have the
+/// [`WILDCARD_KEY_ID`](WILDCARD_KEY_ID) key ID.
+///
+/// Both binary and ASCII armored messages are supported.
+pub fn recipient_key_ids(ciphertext: &[u8]) -> Result<Vec<String>, Err> {
+    if ciphertext.starts_with(ARMOR_HEADER.as_bytes()) {
+        return parse_key_ids(&dearmor(ciphertext)?);
+    }
+    parse_key_ids(ciphertext)
+}
+
+/// Get the key IDs a GPG encrypted file is encrypted for.
+///
+/// See [`recipient_key_ids`](recipient_key_ids).
+pub fn recipient_key_ids_file(path: &Path) -> Result<Vec<String>> {
+    Ok(recipient_key_ids(
+        &fs::read(path).map_err(crypto::Err::ReadFile)?,
+    )?)
+}
+
+/// Check whether any of the given key IDs is a recipient.
+///
+/// - `recipients`: recipient key IDs of a message, see
+///   [`recipient_key_ids`](recipient_key_ids)
+/// - `key_ids`: key IDs of our secret keys and subkeys
+///
+/// Hidden recipients can't be matched, so this assumes `true` if there is any.
+#[must_use]
+pub fn has_recipient(recipients: &[String], key_ids: &[String]) -> bool {
+    recipients.iter().any(|recipient| {
+        recipient == WILDCARD_KEY_ID
+            || key_ids
+                .iter()
+                .any(|id| util::fingerprints_equal(id, recipient))
+    })
+}
+
+/// Parse session key packets from a binary message, collecting key IDs.
+fn parse_key_ids(mut data: &[u8]) -> Result<Vec<String>, Err> {
+    let mut key_ids = vec![];
+
+    while let Some((tag, body, rest)) = next_packet(data)? {
+        match tag {
+            TAG_PKESK => key_ids.push(pkesk_key_id(body)?),
+            TAG_SKESK | TAG_MARKER => {},
+            // Session key packets always precede the encrypted data
+            _ => break,
+        }
+        data = rest;
+    }
+
+    Ok(key_ids)
+}
+
+/// A packet tag, packet body and the remaining data.
+type Packet<'a> = (u8, &'a [u8], &'a [u8]);
+
+/// Split the next packet from the given data.
+///
+/// Returns the packet tag, the packet body and the remaining data. Returns
+/// `None` if there is no more data, or if the packet has an indeterminate or
+/// partial length, which only occurs for data packets.
+fn next_packet(data: &[u8]) -> Result<Option<Packet<'_>>, Err> {
+    let Some((ctb, data)) = data.split_first() else {
+        return Ok(None);
+    };
+    if ctb & 0x80 == 0 {
+        return Err(Err::Malformed);
+    }
+
+    let (tag, len, data) = if ctb & 0x40 == 0 {
+        // Old format packet
+        let tag = (ctb >> 2) & 0x0f;
+        let (len, data) = match ctb & 0x03 {
+            0 => read_len(data, 1)?,
+            1 => read_len(data, 2)?,
+            2 => read_len(data, 4)?,
+            _ => return Ok(None),
+        };
+        (tag, len, data)
+    } else {
+        // New format packet
+        let tag = ctb & 0x3f;
+        let (len, data) = match data.first().ok_or(Err::Malformed)? {
+            0..=191 => read_len(data, 1)?,
+            192..=223 => {
+                let bytes = data.get(..2).ok_or(Err::Malformed)?;
+                let len = ((bytes[0] as usize - 192) << 8) + bytes[1] as usize + 192;
+                (len, &data[2..])
+            },
+            255 => read_len(&data[1..], 4)?,
+            _ => return Ok(None),
+        };
+        (tag, len, data)
+    };
+
+    if data.len() < len {
+        return Err(Err::Malformed);
+    }
+    Ok(Some((tag, &data[..len], &data[len..])))
+}
+
+/// Read a big endian length of the given number of bytes.
+fn read_len(data: &[u8], bytes: usize) -> Result<(usize, &[u8]), Err> {
+    let len = data
+        .get(..bytes)
+        .ok_or(Err::Malformed)?
+        .iter()
+        .fold(0, |len, b| (len << 8) | *b as usize);
+    Ok((len, &data[bytes..]))
+}
+
+/// Get the key ID from a public-key encrypted session key packet body.
+fn pkesk_key_id(body: &[u8]) -> Result<String, Err> {
+    match body.first() {
+        Some(3) => Ok(HEXUPPER.encode(body.get(1..9).ok_or(Err::Malformed)?)),
+        Some(version) => Err(Err::UnsupportedVersion(*version)),
+        None => Err(Err::Malformed),
+    }
+}
+
+/// Decode the body of an ASCII armored message.
+fn dearmor(ciphertext: &[u8]) -> Result<Vec<u8>, Err> {
+    let armor = std::str::from_utf8(ciphertext).map_err(|_| Err::Malformed)?;
+
+    // Skip armor headers up to the first empty line, stop at checksum or footer
+    let body: String = armor
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.is_empty())
+        .take_while(|line| !line.starts_with('=') && !line.starts_with("-----"))
+        .collect();
+    if body.is_empty() {
+        return Err(Err::Malformed);
+    }
+
+    BASE64.decode(body.as_bytes()).map_err(|_| Err::Malformed)
+}
+
+/// GPG protocol error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to parse GPG message, malformed packet")]
+    Malformed,
+
+    #[error("failed to parse GPG message, unsupported session key packet version {0}")]
+    UnsupportedVersion(u8),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Key IDs used in test messages.
+    const KEY_A: [u8; 8] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF];
+    const KEY_B: [u8; 8] = [0xFE, 0xDC, 0xBA, 0x98, 0x76, 0x54, 0x32, 0x10];
+
+    /// Build a version 3 public-key encrypted session key packet body.
+    fn pkesk_body(key_id: [u8; 8]) -> Vec<u8> {
+        let mut body = vec![3];
+        body.extend_from_slice(&key_id);
+        body.extend_from_slice(&[1, 0x00, 0x08, 0xAA]);
+        body
+    }
+
+    /// Build a new format packet with a one byte length.
+    fn new_packet(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0xC0 | tag, body.len() as u8];
+        packet.extend_from_slice(body);
+        packet
+    }
+
+    /// Build an old format packet with a one byte length.
+    fn old_packet(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x80 | (tag << 2), body.len() as u8];
+        packet.extend_from_slice(body);
+        packet
+    }
+
+    /// Build a message for the given recipients, followed by an encrypted
+    /// data packet with a partial length.
+    fn message(key_ids: &[[u8; 8]]) -> Vec<u8> {
+        let mut message = new_packet(TAG_MARKER, b"PGP");
+        for (i, key_id) in key_ids.iter().enumerate() {
+            // Mix old and new packet formats
+            if i % 2 == 0 {
+                message.extend(new_packet(TAG_PKESK, &pkesk_body(*key_id)));
+            } else {
+                message.extend(old_packet(TAG_PKESK, &pkesk_body(*key_id)));
+            }
+        }
+        message.extend_from_slice(&[0xD2, 0xE1, 0x01, 0x02]);
+        message
+    }
+
+    /// ASCII armor the given binary message.
+    fn armor(message: &[u8]) -> Vec<u8> {
+        format!(
+            "{ARMOR_HEADER}\nVersion: test\n\n{}\n=AAAA\n-----END PGP MESSAGE-----\n",
+            BASE64.encode(message)
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn key_ids_of_multiple_recipients() {
+        let message = message(&[KEY_A, KEY_B, [0; 8]]);
+        let expected = vec![
+            "0123456789ABCDEF".to_string(),
+            "FEDCBA9876543210".to_string(),
+            WILDCARD_KEY_ID.to_string(),
+        ];
+        assert_eq!(recipient_key_ids(&message).unwrap(), expected);
+        assert_eq!(recipient_key_ids(&armor(&message)).unwrap(), expected);
+    }
+
+    #[test]
+    fn key_ids_stop_at_data_packet() {
+        // Two and four byte new format lengths, then a data packet
+        let mut message = vec![0xC0 | TAG_PKESK, 0xFF, 0, 0, 0, 13];
+        message.extend(pkesk_body(KEY_A));
+        message.extend(new_packet(18, &[0; 4]));
+        message.extend(new_packet(TAG_PKESK, &pkesk_body(KEY_B)));
+        assert_eq!(recipient_key_ids(&message).unwrap(), vec![
+            "0123456789ABCDEF".to_string()
+        ]);
+
+        let mut message = vec![0xC0 | TAG_PKESK, 0xC0, 0x00];
+        message.extend(pkesk_body(KEY_B));
+        message.extend(vec![0; 192 - 13]);
+        assert_eq!(recipient_key_ids(&message).unwrap(), vec![
+            "FEDCBA9876543210".to_string()
+        ]);
+
+        // Indeterminate old format length
+        assert_eq!(
+            recipient_key_ids(&[0x80 | (18 << 2) | 3, 1, 2]).unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(recipient_key_ids(&[]).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn truncated_packets_are_malformed() {
+        let message = message(&[KEY_A]);
+        for len in [4, 6, 10, 17] {
+            assert!(
+                matches!(recipient_key_ids(&message[..len]), Err(Err::Malformed)),
+                "length {len}"
+            );
+        }
+
+        // Missing or short length bytes
+        assert!(recipient_key_ids(&[0xC1]).is_err());
+        assert!(recipient_key_ids(&[0xC1, 0xC5]).is_err());
+        assert!(recipient_key_ids(&[0xC1, 0xFF, 0, 0]).is_err());
+        assert!(recipient_key_ids(&[0x85, 0]).is_err());
+
+        // Short session key packet body, not a packet, unsupported version
+        assert!(recipient_key_ids(&new_packet(TAG_PKESK, &[3, 1, 2])).is_err());
+        assert!(recipient_key_ids(&new_packet(TAG_PKESK, &[])).is_err());
+        assert!(recipient_key_ids(&[0x00, 0x01]).is_err());
+        assert!(matches!(
+            recipient_key_ids(&new_packet(TAG_PKESK, &[6, 0])),
+            Err(Err::UnsupportedVersion(6))
+        ));
+    }
+
+    #[test]
+    fn malformed_armor() {
+        let header = format!("{ARMOR_HEADER}\n\n");
+        for armor in [
+            format!("{header}!!!not base64!!!\n-----END PGP MESSAGE-----\n"),
+            format!("{header}-----END PGP MESSAGE-----\n"),
+            format!("{ARMOR_HEADER}\nVersion: test\n"),
+            format!("{header}{}", BASE64.encode(&[0x00, 0x01])),
+        ] {
+            assert!(recipient_key_ids(armor.as_bytes()).is_err(), "{armor}");
+        }
+
+        let mut invalid_utf8 = header.into_bytes();
+        invalid_utf8.extend_from_slice(&[0xFF, 0xFE]);
+        assert!(recipient_key_ids(&invalid_utf8).is_err());
+    }
+
+    #[test]
+    fn has_recipient_matches_key_ids() {
+        let recipients = vec!["0123456789ABCDEF".to_string()];
+        assert!(has_recipient(&recipients, &["0123456789abcdef".into()]));
+        assert!(!has_recipient(&recipients, &["FEDCBA9876543210".into()]));
+        assert!(!has_recipient(&recipients, &[]));
+        assert!(!has_recipient(&[], &["0123456789ABCDEF".into()]));
+
+        // Hidden recipients may be any key
+        assert!(has_recipient(&[WILDCARD_KEY_ID.into()], &[
+            "FEDCBA9876543210".into()
+        ]));
+    }
+}
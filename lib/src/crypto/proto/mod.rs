@@ -1,4 +1,6 @@
 //! Crypto protocols.
 
+#[cfg(feature = "_crypto-age")]
+pub mod age;
 #[cfg(feature = "_crypto-gpg")]
 pub mod gpg;
@@ -71,7 +71,11 @@ impl Recipients {
 /// Check whether the given recipients contain any key that we have a secret key
 /// in our keychain for.
 pub fn contains_own_secret_key(recipients: &Recipients) -> Result<bool> {
-    let secrets = Recipients::from(crypto::context(&crate::CONFIG)?.keys_private()?);
+    let proto = match recipients.keys().first() {
+        Some(key) => key.proto(),
+        None => return Ok(false),
+    };
+    let secrets = Recipients::from(crypto::context(&crypto::Config::from(proto))?.keys_private()?);
     Ok(recipients
         .keys()
         .iter()
@@ -1,23 +1,46 @@
 //! Helpers to use recipients with password store.
+//!
+//! Like pass, recipients may be configured per directory. The recipients for a
+//! secret are those of the nearest directory, walking up to the store root,
+//! that has a GPG IDs or age recipients file.
+//!
+//! A store may hold secrets of multiple protocols side by side. Each secret is
+//! encrypted for the recipients of its own protocol in its recipients
+//! directory, so a directory can have both a GPG IDs and age recipients file.
+//!
+//! Like pass, GPG IDs files may be signed to prevent anyone with write access
+//! to the store from silently adding recipients. If signing keys are
+//! configured, each GPG IDs and age recipients file must have a valid detached
+//! signature made by one of them whenever it is loaded. Age recipients files
+//! are covered as well, otherwise dropping one into a directory would switch
+//! it to age with unverified recipients.
 
 use std::{
-    fs,
-    io::Write,
-    os::unix::fs::OpenOptionsExt,
+    collections::HashSet,
+    env, fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::Result;
 use thiserror::Error;
+use walkdir::WalkDir;
 
-use super::{
-    prelude::*, recipients::Recipients, util, Config, ContextPool, Key, Proto, STORE_UMASK,
-};
-use crate::Store;
+use super::{prelude::*, recipients::Recipients, util, Config, ContextPool, Key, Proto};
+use crate::{transaction::Transaction, Store};
 
 /// Password store GPG IDs file.
 const STORE_GPG_IDS_FILE: &str = ".gpg-id";
 
+/// Suffix of the detached signature file for a recipients file.
+const STORE_SIG_SUFFIX: &str = ".sig";
+
+/// Environment variable listing fingerprints of keys trusted to sign
+/// recipients files.
+const STORE_SIGNING_KEY_ENV: &str = "PASSWORD_STORE_SIGNING_KEY";
+
+/// Password store age recipients file.
+const STORE_AGE_RECIPIENTS_FILE: &str = ".age-recipients";
+
 /// Password store public key directory.
 const STORE_PUB_KEY_DIR: &str = ".public-keys/";
 
@@ -27,6 +50,107 @@ pub fn store_gpg_ids_file(store: &Store) -> PathBuf {
     store.root.join(STORE_GPG_IDS_FILE)
 }
 
+/// Get the fingerprints of keys trusted to sign recipients files.
+///
+/// Like pass, these are set through `PASSWORD_STORE_SIGNING_KEY` as a
+/// whitespace separated list. GPG IDs and age recipients files are only signed
+/// and verified if any is set.
+#[must_use]
+pub fn store_signing_keys() -> Vec<String> {
+    env::var(STORE_SIGNING_KEY_ENV)
+        .map(|keys| keys.split_whitespace().map(Into::into).collect())
+        .unwrap_or_default()
+}
+
+/// Get the age recipients file for a store.
+#[must_use]
+pub fn store_age_recipients_file(store: &Store) -> PathBuf {
+    store.root.join(STORE_AGE_RECIPIENTS_FILE)
+}
+
+/// Get the crypto protocol used by a store.
+///
+/// This is the protocol new secrets in the store root are encrypted with, see
+/// [`store_proto_for`].
+#[must_use]
+pub fn store_proto(store: &Store) -> Proto {
+    proto_in(&store.root)
+}
+
+/// Get the crypto protocol used for the given path in a store.
+///
+/// For an existing secret this is the protocol it is encrypted with, detected
+/// from its suffix or header. For a path with a known secret suffix this is
+/// the protocol of that suffix. Otherwise the protocol new secrets are
+/// encrypted with in the nearest recipients directory is used, see
+/// [`store_recipients_dir`].
+#[must_use]
+pub fn store_proto_for(store: &Store, path: &Path) -> Proto {
+    let path = store.root.join(path);
+    if path.is_file() {
+        return util::detect_proto(&path);
+    }
+    util::proto_from_suffix(&path).unwrap_or_else(|| proto_in(&store_recipients_dir(store, &path)))
+}
+
+/// Get the crypto protocol new secrets are encrypted with in the given
+/// directory.
+///
+/// This is age if the directory has an age recipients file, even if it also
+/// has a GPG IDs file. This allows gradually migrating a GPG store to age. GPG
+/// is used otherwise.
+fn proto_in(dir: &Path) -> Proto {
+    if dir.join(STORE_AGE_RECIPIENTS_FILE).is_file() {
+        Proto::Age
+    } else {
+        Proto::Gpg
+    }
+}
+
+/// Check whether the given directory has a recipients file.
+fn has_recipients_file(dir: &Path) -> bool {
+    dir.join(STORE_GPG_IDS_FILE).is_file() || dir.join(STORE_AGE_RECIPIENTS_FILE).is_file()
+}
+
+/// Find the directory holding the recipients for the given path in a store.
+///
+/// Walks up from the given path, or its parent if it is not a directory, to the
+/// nearest directory having a GPG IDs or age recipients file. Relative paths
+/// are relative to the store root. Falls back to the store root.
+#[must_use]
+pub fn store_recipients_dir(store: &Store, path: &Path) -> PathBuf {
+    let path = store.root.join(path);
+    let start = if path.is_dir() {
+        path.as_path()
+    } else {
+        path.parent().unwrap_or(&path)
+    };
+
+    start
+        .ancestors()
+        .take_while(|dir| dir.starts_with(&store.root))
+        .find(|dir| has_recipients_file(dir))
+        .map_or_else(|| store.root.clone(), Path::to_path_buf)
+}
+
+/// List all directories in a store that have their own recipients.
+///
+/// Always includes the store root. Hidden directories are skipped.
+#[must_use]
+pub fn store_recipients_dirs(store: &Store) -> Vec<PathBuf> {
+    let mut dirs = vec![store.root.clone()];
+    dirs.extend(
+        WalkDir::new(&store.root)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| e.file_type().is_dir() && !crate::store::is_hidden_subdir(e))
+            .filter_map(Result::ok)
+            .map(walkdir::DirEntry::into_path)
+            .filter(|dir| has_recipients_file(dir)),
+    );
+    dirs
+}
+
 /// Get the public keys directory for a store.
 #[must_use]
 pub fn store_public_keys_dir(store: &Store) -> PathBuf {
@@ -35,8 +159,17 @@ pub fn store_public_keys_dir(store: &Store) -> PathBuf {
 
 /// Read GPG fingerprints from store.
 pub fn store_read_gpg_fingerprints(store: &Store) -> Result<Vec<String>> {
-    let path = store_gpg_ids_file(store);
+    read_gpg_fingerprints_in(&store.root)
+}
+
+/// Read GPG fingerprints from the GPG IDs file in the given directory.
+///
+/// Errors if signing keys are configured and the file is not signed by any of
+/// them, see [`store_signing_keys`].
+fn read_gpg_fingerprints_in(dir: &Path) -> Result<Vec<String>> {
+    let path = dir.join(STORE_GPG_IDS_FILE);
     if path.is_file() {
+        verify_recipients_file(&path)?;
         read_fingerprints(path)
     } else {
         Ok(vec![])
@@ -50,7 +183,151 @@ pub fn store_write_gpg_fingerprints<S: AsRef<str>>(
     store: &Store,
     fingerprints: &[S],
 ) -> Result<()> {
-    write_fingerprints(store_gpg_ids_file(store), fingerprints)
+    let mut tx = store.transaction();
+    write_gpg_fingerprints_in(&mut tx, &store.root, fingerprints)?;
+    tx.commit()
+}
+
+/// Write GPG fingerprints to the GPG IDs file in the given directory.
+///
+/// Overwrites any existing file. Signs the file if signing keys are
+/// configured, see [`store_signing_keys`].
+fn write_gpg_fingerprints_in<S: AsRef<str>>(
+    tx: &mut Transaction,
+    dir: &Path,
+    fingerprints: &[S],
+) -> Result<()> {
+    let path = dir.join(STORE_GPG_IDS_FILE);
+    let contents = format_fingerprints(fingerprints);
+    tx.write(&path, contents.as_bytes())?;
+    sign_recipients_file(tx, &path, contents.as_bytes())
+}
+
+/// Get the signature file for a recipients file.
+///
+/// This is `.gpg-id.sig` for a GPG IDs file, like pass uses.
+fn recipients_sig_file(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(STORE_SIG_SUFFIX);
+    path.with_file_name(file_name)
+}
+
+/// Sign the contents of a recipients file if signing keys are configured.
+///
+/// Signs with the first configured signing key we have the secret key for.
+fn sign_recipients_file(tx: &mut Transaction, path: &Path, contents: &[u8]) -> Result<()> {
+    let signers = store_signing_keys();
+    if signers.is_empty() {
+        return Ok(());
+    }
+
+    let mut context = super::context(&crate::CONFIG)?;
+    let key = context
+        .keys_private()
+        .map_err(Err::SignRecipients)?
+        .into_iter()
+        .find(|key| {
+            signers
+                .iter()
+                .any(|fp| util::fingerprints_equal(key.fingerprint(false), fp))
+        })
+        .ok_or(Err::NoSigningKey)?;
+
+    let signature = context
+        .sign_detached(&key, contents)
+        .map_err(Err::SignRecipients)?;
+    tx.write(&recipients_sig_file(path), &signature)
+}
+
+/// Verify the signature of a recipients file if signing keys are configured.
+///
+/// The file must have a valid detached signature made by one of the signing
+/// keys, like pass requires.
+fn verify_recipients_file(path: &Path) -> Result<()> {
+    verify_recipients_file_with(path, &store_signing_keys())
+}
+
+/// Verify the signature of a recipients file against the given signing keys.
+///
+/// Always succeeds if no signing keys are given.
+fn verify_recipients_file_with(path: &Path, signers: &[String]) -> Result<()> {
+    if signers.is_empty() {
+        return Ok(());
+    }
+
+    let signature = recipients_sig_file(path);
+    if !signature.is_file() {
+        return Err(Err::UnsignedRecipients(path.into()).into());
+    }
+
+    let mut context = super::context(&crate::CONFIG)?;
+    let trusted = context
+        .verify_detached_file(path, &signature)
+        .map_err(Err::VerifyRecipients)?
+        .iter()
+        .any(|fp| {
+            signers
+                .iter()
+                .any(|signer| util::fingerprints_equal(fp, signer))
+        });
+    if !trusted {
+        return Err(Err::UntrustedRecipients(path.into()).into());
+    }
+
+    Ok(())
+}
+
+/// Read age recipient keys from store.
+#[cfg(feature = "_crypto-age")]
+pub fn store_read_age_recipients(store: &Store) -> Result<Vec<Key>> {
+    read_age_recipients_in(&store.root)
+}
+
+/// Read age recipient keys from the age recipients file in the given
+/// directory.
+///
+/// Errors if signing keys are configured and the file is not signed by any of
+/// them, see [`store_signing_keys`].
+#[cfg(feature = "_crypto-age")]
+fn read_age_recipients_in(dir: &Path) -> Result<Vec<Key>> {
+    let path = dir.join(STORE_AGE_RECIPIENTS_FILE);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    verify_recipients_file(&path)?;
+
+    Ok(
+        super::proto::age::parse_recipients(&fs::read_to_string(path).map_err(Err::ReadFile)?)
+            .into_iter()
+            .map(super::proto::age::Key::into_key)
+            .collect(),
+    )
+}
+
+/// Write age recipient keys to a store.
+///
+/// Overwrites any existing file.
+#[cfg(feature = "_crypto-age")]
+pub fn store_write_age_recipients(store: &Store, keys: &[super::proto::age::Key]) -> Result<()> {
+    let mut tx = store.transaction();
+    write_age_recipients_in(&mut tx, &store.root, keys)?;
+    tx.commit()
+}
+
+/// Write age recipient keys to the age recipients file in the given directory.
+///
+/// Overwrites any existing file. Signs the file if signing keys are
+/// configured, see [`store_signing_keys`].
+#[cfg(feature = "_crypto-age")]
+fn write_age_recipients_in(
+    tx: &mut Transaction,
+    dir: &Path,
+    keys: &[super::proto::age::Key],
+) -> Result<()> {
+    let path = dir.join(STORE_AGE_RECIPIENTS_FILE);
+    let contents = super::proto::age::format_recipients(keys);
+    tx.write(&path, contents.as_bytes())?;
+    sign_recipients_file(tx, &path, contents.as_bytes())
 }
 
 /// Read fingerprints from the given file.
@@ -63,31 +340,38 @@ fn read_fingerprints<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
         .collect())
 }
 
-/// Write fingerprints to the given file.
-fn write_fingerprints<P: AsRef<Path>, S: AsRef<str>>(path: P, fingerprints: &[S]) -> Result<()> {
-    let mut file = fs::OpenOptions::new()
-        .mode(0o666 - (0o666 & *STORE_UMASK))
-        .truncate(true)
-        .write(true)
-        .create(true)
-        .open(&path)?;
-
-    file.write_all(
-        fingerprints
-            .iter()
-            .map(AsRef::as_ref)
-            .collect::<Vec<_>>()
-            .join("\n")
-            .as_bytes(),
-    )
-    .map_err(|err| Err::WriteFile(err).into())
+/// Format fingerprints as fingerprints file contents.
+fn format_fingerprints<S: AsRef<str>>(fingerprints: &[S]) -> String {
+    fingerprints
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 /// Load the keys for the given store.
 ///
-/// This will try to load the keys for all configured protocols, and errors if
-/// it fails.
+/// This loads the keys for the protocol used by the store, see
+/// [`store_proto`], and errors if it fails.
 pub fn store_load_keys(store: &Store) -> Result<Vec<Key>> {
+    load_keys_in(&store.root, store_proto(store))
+}
+
+/// Load the keys for the given path in a store.
+///
+/// This loads the keys of the nearest recipients directory, see
+/// [`store_recipients_dir`], for the protocol used at the path, see
+/// [`store_proto_for`].
+pub fn store_load_keys_for(store: &Store, path: &Path) -> Result<Vec<Key>> {
+    load_keys_in(
+        &store_recipients_dir(store, path),
+        store_proto_for(store, path),
+    )
+}
+
+/// Load the keys for the given protocol from the recipient files in the given
+/// directory.
+fn load_keys_in(dir: &Path, proto: Proto) -> Result<Vec<Key>> {
     let mut keys = Vec::new();
 
     // TODO: what to do if ids file does not exist?
@@ -95,13 +379,19 @@ pub fn store_load_keys(store: &Store) -> Result<Vec<Key>> {
     // TODO: what to do if key listed in file is not found, attempt to install?
 
     // Load GPG keys
-    // TODO: do not crash here if GPG ids file is not found!
-    let fingerprints = store_read_gpg_fingerprints(store)?;
+    if proto == Proto::Gpg {
+        let fingerprints = read_gpg_fingerprints_in(dir)?;
+        if !fingerprints.is_empty() {
+            let mut context = super::context(&crate::CONFIG)?;
+            let fingerprints: Vec<_> = fingerprints.iter().map(String::as_str).collect();
+            keys.extend(context.find_public_keys(&fingerprints)?);
+        }
+    }
 
-    if !fingerprints.is_empty() {
-        let mut context = super::context(&crate::CONFIG)?;
-        let fingerprints: Vec<_> = fingerprints.iter().map(String::as_str).collect();
-        keys.extend(context.find_public_keys(&fingerprints)?);
+    // Load age keys, recipients are self-contained and don't need a keychain
+    #[cfg(feature = "_crypto-age")]
+    if proto == Proto::Age {
+        keys.extend(read_age_recipients_in(dir)?);
     }
 
     // NEWPROTO: if a new proto is added, keys for a store should be loaded here
@@ -109,6 +399,33 @@ pub fn store_load_keys(store: &Store) -> Result<Vec<Key>> {
     Ok(keys)
 }
 
+/// Load the keys of all recipient directories in the given store.
+///
+/// This includes the keys of all protocols. Keys used in multiple directories
+/// are only listed once.
+pub fn store_load_all_keys(store: &Store) -> Result<Vec<Key>> {
+    let mut keys: Vec<Key> = Vec::new();
+    for dir in store_recipients_dirs(store) {
+        let gpg_keys = if dir.join(STORE_GPG_IDS_FILE).is_file() {
+            load_keys_in(&dir, Proto::Gpg)?
+        } else {
+            vec![]
+        };
+        let age_keys = if dir.join(STORE_AGE_RECIPIENTS_FILE).is_file() {
+            load_keys_in(&dir, Proto::Age)?
+        } else {
+            vec![]
+        };
+
+        for key in gpg_keys.into_iter().chain(age_keys) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
 /// Load the recipients for the given store.
 ///
 /// This will try to load the recipient keys for all configured protocols, and
@@ -117,20 +434,74 @@ pub fn store_load_recipients(store: &Store) -> Result<Recipients> {
     Ok(Recipients::from(store_load_keys(store)?))
 }
 
+/// Load the recipients for the given path in a store.
+///
+/// This loads the recipients of the nearest recipients directory, see
+/// [`store_recipients_dir`].
+pub fn store_load_recipients_for(store: &Store, path: &Path) -> Result<Recipients> {
+    Ok(Recipients::from(store_load_keys_for(store, path)?))
+}
+
 /// Save the keys for the given store.
 ///
 /// This overwrites any existing recipient keys.
 pub fn store_save_keys(store: &Store, keys: &[Key]) -> Result<()> {
+    store_save_keys_in(store, &store.root, keys)
+}
+
+/// Save the keys for the given directory in a store.
+///
+/// This overwrites any existing recipient keys in that directory for the
+/// protocols of the given keys, and creates the recipient files if they don't
+/// exist yet. Recipients of other protocols are left untouched. If no keys are
+/// given, the recipients for the protocol new secrets in the directory are
+/// encrypted with are cleared. Public key files are synced with the recipients
+/// of all directories in the store.
+pub fn store_save_keys_in(store: &Store, dir: &Path, keys: &[Key]) -> Result<()> {
+    let mut tx = store.transaction();
+    store_save_keys_in_transaction(&mut tx, dir, keys)?;
+    tx.commit()
+}
+
+/// Save the keys for the given directory in a store as part of a transaction.
+///
+/// See [`store_save_keys_in`].
+pub fn store_save_keys_in_transaction(
+    tx: &mut Transaction,
+    dir: &Path,
+    keys: &[Key],
+) -> Result<()> {
+    let store = tx.store();
+    let saves_proto = |proto: Proto| {
+        keys.iter().any(|key| key.proto() == proto) || (keys.is_empty() && proto_in(dir) == proto)
+    };
+
     // Save GPG keys
-    let gpg_fingerprints: Vec<_> = keys
-        .iter()
-        .filter(|key| key.proto() == Proto::Gpg)
-        .map(|key| key.fingerprint(false))
-        .collect();
-    store_write_gpg_fingerprints(store, &gpg_fingerprints)?;
+    if saves_proto(Proto::Gpg) {
+        let gpg_fingerprints: Vec<_> = keys
+            .iter()
+            .filter(|key| key.proto() == Proto::Gpg)
+            .map(|key| key.fingerprint(false))
+            .collect();
+        write_gpg_fingerprints_in(tx, dir, &gpg_fingerprints)?;
+    }
 
-    // Sync public keys for all proto's
-    store_sync_public_key_files(store, keys)?;
+    // Save age keys
+    #[cfg(feature = "_crypto-age")]
+    if saves_proto(Proto::Age) {
+        let age_keys: Vec<_> = keys
+            .iter()
+            .filter_map(|key| match key {
+                Key::Age(key) => Some(key.clone()),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            })
+            .collect();
+        write_age_recipients_in(tx, dir, &age_keys)?;
+    }
+
+    // Sync public keys for all proto's and directories
+    sync_public_key_files(tx, &store_load_all_keys(store)?)?;
 
     // TODO: import missing keys to system?
 
@@ -144,6 +515,13 @@ pub fn store_save_recipients(store: &Store, recipients: &Recipients) -> Result<(
     store_save_keys(store, recipients.keys())
 }
 
+/// Save the recipients for the given directory in a store.
+///
+/// This overwrites any existing recipient keys in that directory.
+pub fn store_save_recipients_in(store: &Store, dir: &Path, recipients: &Recipients) -> Result<()> {
+    store_save_keys_in(store, dir, recipients.keys())
+}
+
 /// Sync public key files in store with selected recipients.
 ///
 /// - Removes obsolete keys that are not a selected recipient
@@ -153,9 +531,17 @@ pub fn store_save_recipients(store: &Store, recipients: &Recipients) -> Result<(
 /// key files themselves don't specify what protocol they use. All public key
 /// files and keys must therefore be taken into consideration all at once.
 pub fn store_sync_public_key_files(store: &Store, keys: &[Key]) -> Result<()> {
+    let mut tx = store.transaction();
+    sync_public_key_files(&mut tx, keys)?;
+    tx.commit()
+}
+
+/// Sync public key files in store with selected recipients as part of a
+/// transaction.
+fn sync_public_key_files(tx: &mut Transaction, keys: &[Key]) -> Result<()> {
     // Get public keys directory, ensure it exists
-    let dir = store_public_keys_dir(store);
-    fs::create_dir_all(&dir).map_err(Err::SyncKeyFiles)?;
+    let dir = store_public_keys_dir(tx.store());
+    tx.create_dir_all(&dir)?;
 
     // List key files in keys directory
     let files: Vec<(PathBuf, String)> = dir
@@ -175,15 +561,16 @@ pub fn store_sync_public_key_files(store: &Store, keys: &[Key]) -> Result<()> {
         .iter()
         .filter(|(_, fp)| !util::keys_contain_fingerprint(keys, fp))
     {
-        fs::remove_file(path).map_err(Err::SyncKeyFiles)?;
+        tx.remove(path)?;
     }
 
-    // Add missing keys
+    // Add missing keys, age recipients are self-contained and have no key file
     let mut contexts = ContextPool::empty();
     for (key, fp) in keys
         .iter()
+        .filter(|k| k.proto() != Proto::Age)
         .map(|k| (k, k.fingerprint(false)))
-        .filter(|(_, fp)| !files.iter().any(|(_, other)| fp == other))
+        .filter(|(_, fp)| !files.iter().any(|(_, other)| util::fingerprints_equal(fp, other)))
     {
         // Lazy load compatible context
         let proto = key.proto();
@@ -192,7 +579,7 @@ pub fn store_sync_public_key_files(store: &Store, keys: &[Key]) -> Result<()> {
 
         // Export public key to disk
         let path = dir.join(&fp);
-        context.export_key_file(key.clone(), &path)?;
+        tx.write(&path, &context.export_key(key.clone())?)?;
     }
 
     // NEWPROTO: if a new proto is added, public keys should be synced here
@@ -201,6 +588,8 @@ pub fn store_sync_public_key_files(store: &Store, keys: &[Key]) -> Result<()> {
 }
 
 /// Import keys from store that are missing in the keychain.
+///
+/// This considers the recipients of all directories in the store.
 pub fn import_missing_keys_from_store(store: &Store) -> Result<Vec<ImportResult>> {
     // Get public keys directory, ensure it exists
     let dir = store_public_keys_dir(store);
@@ -212,8 +601,18 @@ pub fn import_missing_keys_from_store(store: &Store) -> Result<Vec<ImportResult>
     let mut contexts = ContextPool::empty();
     let mut results = Vec::new();
 
+    // Collect GPG fingerprints from all recipient directories
+    let mut gpg_fingerprints = Vec::new();
+    let mut seen = HashSet::new();
+    for recipients_dir in store_recipients_dirs(store) {
+        for fingerprint in read_gpg_fingerprints_in(&recipients_dir)? {
+            if seen.insert(util::format_fingerprint(&fingerprint)) {
+                gpg_fingerprints.push(fingerprint);
+            }
+        }
+    }
+
     // Check for missing GPG keys based on fingerprint, import them
-    let gpg_fingerprints = store_read_gpg_fingerprints(store)?;
     for fingerprint in gpg_fingerprints {
         let context = contexts.get_mut(&crate::CONFIG)?;
         if context.get_public_key(&fingerprint).is_err() {
@@ -247,8 +646,20 @@ pub trait StoreRecipients {
     /// Load recipients from given store.
     fn load(store: &Store) -> Result<Recipients>;
 
+    /// Load recipients for the given path in a store.
+    ///
+    /// Uses the recipients of the nearest recipients directory.
+    fn load_for(store: &Store, path: &Path) -> Result<Recipients>;
+
     /// Save recipients to given store.
     fn save(&self, store: &Store) -> Result<()>;
+
+    /// Save recipients to the given directory in a store.
+    fn save_in(&self, store: &Store, dir: &Path) -> Result<()>;
+
+    /// Save recipients to the given directory in a store as part of a
+    /// transaction.
+    fn save_in_transaction(&self, tx: &mut Transaction, dir: &Path) -> Result<()>;
 }
 
 impl StoreRecipients for Recipients {
@@ -257,21 +668,223 @@ impl StoreRecipients for Recipients {
         store_load_recipients(store)
     }
 
+    /// Load recipients for the given path in a store.
+    fn load_for(store: &Store, path: &Path) -> Result<Recipients> {
+        store_load_recipients_for(store, path)
+    }
+
     /// Save recipients to given store.
     fn save(&self, store: &Store) -> Result<()> {
         store_save_recipients(store, self)
     }
+
+    /// Save recipients to the given directory in a store.
+    fn save_in(&self, store: &Store, dir: &Path) -> Result<()> {
+        store_save_recipients_in(store, dir, self)
+    }
+
+    /// Save recipients to the given directory in a store as part of a
+    /// transaction.
+    fn save_in_transaction(&self, tx: &mut Transaction, dir: &Path) -> Result<()> {
+        store_save_keys_in_transaction(tx, dir, self.keys())
+    }
 }
 
 /// Store crypto error.
 #[derive(Debug, Error)]
 pub enum Err {
-    #[error("failed to write to file")]
-    WriteFile(#[source] std::io::Error),
-
     #[error("failed to read from file")]
     ReadFile(#[source] std::io::Error),
 
     #[error("failed to sync public key files")]
     SyncKeyFiles(#[source] std::io::Error),
+
+    #[error("failed to sign recipients file")]
+    SignRecipients(#[source] anyhow::Error),
+
+    #[error("failed to sign recipients file, no secret key for any signing key")]
+    NoSigningKey,
+
+    #[error("failed to verify signature of recipients file")]
+    VerifyRecipients(#[source] anyhow::Error),
+
+    #[error("failed to verify recipients, recipients file is not signed: {}", _0.display())]
+    UnsignedRecipients(PathBuf),
+
+    #[error(
+        "failed to verify recipients, recipients file is not signed by a trusted signing key: {}",
+        _0.display()
+    )]
+    UntrustedRecipients(PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Create a store in a new temporary directory with the given recipients
+    /// files.
+    ///
+    /// The directory is removed when the returned guard is dropped.
+    fn temp_store(files: &[&str]) -> (TempDir, Store) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store {
+            root: dir.path().into(),
+        };
+        for file in files {
+            let path = store.root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        (dir, store)
+    }
+
+    #[test]
+    fn nested_recipients_override_root() {
+        let (_dir, store) = temp_store(&[
+            ".gpg-id",
+            "work/.gpg-id",
+            "work/team/secret.gpg",
+            "shared/.age-recipients",
+        ]);
+        let root = &store.root;
+
+        for (path, dir) in [
+            ("work/secret.gpg", "work"),
+            ("work/team/secret.gpg", "work"),
+            ("work/team/new/secret.gpg", "work"),
+            ("work", "work"),
+            ("work/team", "work"),
+            ("shared/secret.age", "shared"),
+        ] {
+            assert_eq!(
+                store_recipients_dir(&store, Path::new(path)),
+                root.join(dir),
+                "path {path}"
+            );
+        }
+        assert_eq!(
+            store_recipients_dir(&store, &root.join("work/secret.gpg")),
+            root.join("work")
+        );
+    }
+
+    #[test]
+    fn recipients_fall_back_to_root() {
+        let (_dir, store) = temp_store(&[".gpg-id", "work/.gpg-id", "personal/mail/secret.gpg"]);
+        let root = &store.root;
+
+        for path in [
+            "secret.gpg",
+            "personal/mail/secret.gpg",
+            "personal",
+            "workshop/secret.gpg",
+        ] {
+            assert_eq!(
+                store_recipients_dir(&store, Path::new(path)),
+                *root,
+                "path {path}"
+            );
+        }
+
+        // Without any recipients file the root is used as well
+        let (_dir, store) = temp_store(&["work/secret.gpg"]);
+        assert_eq!(
+            store_recipients_dir(&store, Path::new("work/secret.gpg")),
+            store.root
+        );
+    }
+
+    #[test]
+    fn list_recipients_dirs() {
+        let (_dir, store) = temp_store(&[
+            "work/.gpg-id",
+            "work/team/.age-recipients",
+            "personal/secret.gpg",
+            ".hidden/.gpg-id",
+        ]);
+        let root = &store.root;
+
+        let mut dirs = store_recipients_dirs(&store);
+        dirs.sort();
+        assert_eq!(dirs, [
+            root.clone(),
+            root.join("work"),
+            root.join("work/team")
+        ]);
+    }
+
+    #[test]
+    fn save_keys_in_recipients_dir() {
+        let (_dir, store) = temp_store(&[".gpg-id", "work/secret.gpg"]);
+        let root = &store.root;
+
+        // Saving creates a recipients file in the given directory only
+        store_save_keys_in(&store, &root.join("work"), &[]).unwrap();
+        assert!(root.join("work/.gpg-id").is_file());
+        assert!(!root.join(".public-keys").read_dir().unwrap().any(|_| true));
+        assert_eq!(
+            store_recipients_dir(&store, Path::new("work/secret.gpg")),
+            root.join("work")
+        );
+        assert_eq!(store_recipients_dir(&store, Path::new("secret.gpg")), *root);
+    }
+
+    #[test]
+    fn recipients_sig_file_next_to_file() {
+        assert_eq!(
+            recipients_sig_file(Path::new("/store/work/.gpg-id")),
+            Path::new("/store/work/.gpg-id.sig")
+        );
+        assert_eq!(
+            recipients_sig_file(Path::new("/store/work/.age-recipients")),
+            Path::new("/store/work/.age-recipients.sig")
+        );
+    }
+
+    #[test]
+    fn verify_unsigned_recipients_files() {
+        let (_dir, store) = temp_store(&[".gpg-id", "work/.age-recipients", "work/.gpg-id.sig"]);
+        let signers = ["0123456789ABCDEF".to_string()];
+
+        for file in [".gpg-id", "work/.age-recipients"] {
+            let path = store.root.join(file);
+
+            // Nothing to verify without signing keys
+            assert!(verify_recipients_file_with(&path, &[]).is_ok());
+
+            // A signature of another recipients file does not count
+            let err = verify_recipients_file_with(&path, &signers).unwrap_err();
+            assert!(
+                matches!(err.downcast_ref(), Some(Err::UnsignedRecipients(p)) if *p == path),
+                "{file} verified without signature: {err:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "_crypto-age")]
+    #[test]
+    fn save_age_keys_in_recipients_dir() {
+        let (_dir, store) = temp_store(&[".gpg-id"]);
+        let root = &store.root;
+        let key = crate::crypto::proto::age::Key {
+            recipient: "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p".into(),
+            comment:   None,
+        };
+
+        store_save_keys_in(&store, &root.join("work"), &[key.clone().into_key()]).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("work/.age-recipients")).unwrap(),
+            format!("{}\n", key.recipient)
+        );
+        assert!(!root.join(".age-recipients").exists());
+        assert!(!root.join("work/.gpg-id").exists());
+        assert_eq!(fs::read_to_string(root.join(".gpg-id")).unwrap(), "");
+
+        assert_eq!(store_proto_for(&store, Path::new("work/new")), Proto::Age);
+        assert_eq!(store_proto_for(&store, Path::new("new")), Proto::Gpg);
+        assert!(store_load_keys_for(&store, Path::new("work/new")).unwrap() == [key.into_key()]);
+    }
 }
@@ -1,8 +1,16 @@
 //! Common crypto utilities.
 
+use std::{fs, io::Read, path::Path};
+
 use anyhow::Result;
 
-use super::{prelude::*, Config, Key};
+use super::{prelude::*, Config, Key, Proto};
+
+/// Header of age encrypted files.
+const AGE_HEADER: &[u8] = b"age-encryption.org/";
+
+/// Header of ASCII armored age encrypted files.
+const AGE_ARMOR_HEADER: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
 
 /// Format fingerprint in consistent format.
 ///
@@ -27,3 +35,127 @@ pub fn keys_contain_fingerprint<S: AsRef<str>>(keys: &[Key], fingerprint: S) ->
 pub fn has_private_key(config: &Config) -> Result<bool> {
     Ok(!super::context(config)?.keys_private()?.is_empty())
 }
+
+/// Get the crypto protocol for a secret file based on its suffix.
+///
+/// Returns `None` if the suffix is not known.
+#[must_use]
+pub fn proto_from_suffix(path: &Path) -> Option<Proto> {
+    let name = path.file_name()?.to_str()?;
+    [Proto::Gpg, Proto::Age]
+        .iter()
+        .copied()
+        .find(|proto| name.ends_with(proto.secret_suffix()))
+}
+
+/// Get the crypto protocol for ciphertext based on its header.
+///
+/// Anything that doesn't look like age is assumed to be GPG.
+#[must_use]
+pub fn proto_from_header(ciphertext: &[u8]) -> Proto {
+    if ciphertext.starts_with(AGE_HEADER) || ciphertext.starts_with(AGE_ARMOR_HEADER) {
+        Proto::Age
+    } else {
+        Proto::Gpg
+    }
+}
+
+/// Detect the crypto protocol used for the given secret file.
+///
+/// Uses the file suffix if known, and falls back to reading the file header.
+/// Defaults to GPG if the file cannot be read.
+#[must_use]
+pub fn detect_proto(path: &Path) -> Proto {
+    if let Some(proto) = proto_from_suffix(path) {
+        return proto;
+    }
+
+    let mut header = Vec::with_capacity(AGE_ARMOR_HEADER.len());
+    let read = fs::File::open(path).and_then(|file| {
+        file.take(AGE_ARMOR_HEADER.len() as u64)
+            .read_to_end(&mut header)
+    });
+    match read {
+        Ok(_) => proto_from_header(&header),
+        Err(_) => Proto::Gpg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start of an age binary file.
+    const AGE_BINARY: &[u8] = b"age-encryption.org/v1\n-> X25519 abc\n";
+
+    /// Start of an armored age file.
+    const AGE_ARMORED: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----\nYWdlLWVuY3J5cHRpb24=\n";
+
+    /// Start of a binary OpenPGP message, a new format PKESK packet.
+    const GPG_BINARY: &[u8] = &[0xC1, 0x0C, 0x03, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD];
+
+    /// Start of an armored OpenPGP message.
+    const GPG_ARMORED: &[u8] = b"-----BEGIN PGP MESSAGE-----\n\nhQEMA0ZmZmZmZmZm\n";
+
+    #[test]
+    fn proto_of_header() {
+        assert_eq!(proto_from_header(AGE_BINARY), Proto::Age);
+        assert_eq!(proto_from_header(AGE_ARMORED), Proto::Age);
+        assert_eq!(proto_from_header(GPG_BINARY), Proto::Gpg);
+        assert_eq!(proto_from_header(GPG_ARMORED), Proto::Gpg);
+    }
+
+    #[test]
+    fn proto_of_ambiguous_header() {
+        // Anything not clearly age is assumed to be GPG
+        for header in [
+            &b""[..],
+            b"age",
+            b"age-encryption.org",
+            b" age-encryption.org/v1",
+            b"-----BEGIN AGE",
+            b"-----BEGIN AGE ENCRYPTED FILE",
+            b"AGE-ENCRYPTION.ORG/v1",
+            b"plain text secret",
+        ] {
+            assert_eq!(proto_from_header(header), Proto::Gpg, "header {header:?}");
+        }
+    }
+
+    #[test]
+    fn proto_of_suffix() {
+        assert_eq!(proto_from_suffix(Path::new("a/b.gpg")), Some(Proto::Gpg));
+        assert_eq!(proto_from_suffix(Path::new("a/b.age")), Some(Proto::Age));
+        assert_eq!(
+            proto_from_suffix(Path::new("a.age/b.gpg")),
+            Some(Proto::Gpg)
+        );
+        assert_eq!(proto_from_suffix(Path::new("a/b")), None);
+        assert_eq!(proto_from_suffix(Path::new("a/b.gpg.txt")), None);
+        assert_eq!(proto_from_suffix(Path::new("")), None);
+    }
+
+    #[test]
+    fn detect_proto_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &[u8]| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            path
+        };
+
+        // Without known suffix the header is used
+        assert_eq!(detect_proto(&write("age", AGE_BINARY)), Proto::Age);
+        assert_eq!(detect_proto(&write("age-armored", AGE_ARMORED)), Proto::Age);
+        assert_eq!(detect_proto(&write("gpg", GPG_BINARY)), Proto::Gpg);
+        assert_eq!(detect_proto(&write("gpg-armored", GPG_ARMORED)), Proto::Gpg);
+        assert_eq!(detect_proto(&write("empty", b"")), Proto::Gpg);
+        assert_eq!(detect_proto(&write("short", b"age-enc")), Proto::Gpg);
+        assert_eq!(detect_proto(&dir.path().join("missing")), Proto::Gpg);
+
+        // A known suffix takes precedence over the header
+        assert_eq!(detect_proto(&write("secret.gpg", AGE_BINARY)), Proto::Gpg);
+        assert_eq!(detect_proto(&write("secret.age", GPG_BINARY)), Proto::Age);
+        assert_eq!(detect_proto(&dir.path().join("missing.age")), Proto::Age);
+    }
+}
@@ -0,0 +1,413 @@
+//! Find secrets by name, field or URL.
+//!
+//! Names are matched fuzzily and ranked, so the best matching secrets come
+//! first. Field and URL queries match against the fields of secrets, which are
+//! only known through a search index, see [`crate::index`]. Without an index,
+//! URL queries only match secret names.
+
+use std::{cmp::Reverse, convert::TryFrom};
+
+use crate::{
+    index::{Index, IndexEntry},
+    Secret, Store,
+};
+
+/// Second-level labels registered below country code top-level domains, such
+/// as `co.uk`.
+const SECOND_LEVEL_LABELS: [&str; 10] = [
+    "ac", "co", "com", "edu", "go", "gov", "ne", "net", "or", "org",
+];
+
+/// Characters after which a name match starts a word.
+const WORD_BOUNDARIES: [char; 6] = ['/', '-', '_', '.', ' ', '@'];
+
+/// Score of each matched character in a fuzzy match.
+const SCORE_MATCH: i64 = 16;
+
+/// Bonus for a matched character directly following the previous one.
+const BONUS_CONSECUTIVE: i64 = 12;
+
+/// Bonus for a matched character at the start of a word.
+const BONUS_BOUNDARY: i64 = 10;
+
+/// Maximum penalty for a gap between matched characters.
+const MAX_GAP_PENALTY: i64 = 8;
+
+/// Score of a secret having a URL field with the queried host.
+const SCORE_URL_HOST: i64 = 400;
+
+/// Score of a secret having a URL field with the queried domain.
+const SCORE_URL_DOMAIN: i64 = 300;
+
+/// Score of a secret name component naming the queried domain.
+const SCORE_NAME_DOMAIN: i64 = 200;
+
+/// Score of a secret name component naming the queried domain without suffix.
+const SCORE_NAME_LABEL: i64 = 100;
+
+/// Query to find secrets with.
+///
+/// All given parts must match.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    /// Fuzzy secret name query.
+    pub name: Option<String>,
+
+    /// Field filters.
+    pub fields: Vec<FieldFilter>,
+
+    /// URL query, matched by registrable domain.
+    pub url: Option<String>,
+}
+
+/// Filter on a secret field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldFilter {
+    /// Field key, matched case-insensitively.
+    pub key: String,
+
+    /// Text the field value must contain case-insensitively, any value matches
+    /// if not set.
+    pub value: Option<String>,
+}
+
+/// A secret matching a query.
+#[derive(Debug, Clone)]
+pub struct Match {
+    /// Matched secret.
+    pub secret: Secret,
+
+    /// Match score, higher is better.
+    pub score: i64,
+}
+
+impl Query {
+    /// Parse a free form query, as given to secret selection.
+    ///
+    /// A query with a URL scheme is a URL query, anything else is a name
+    /// query.
+    #[must_use]
+    pub fn parse(query: Option<&str>) -> Self {
+        match query.map(str::trim).filter(|query| !query.is_empty()) {
+            Some(query) if query.contains("://") => Self {
+                url: Some(query.into()),
+                ..Self::default()
+            },
+            query => Self {
+                name: query.map(Into::into),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Check whether the given secret name matches the name query literally,
+    /// rather than only fuzzily.
+    ///
+    /// Literal matches contain the name query case-insensitively. Names always
+    /// match literally if there is no name query.
+    #[must_use]
+    pub fn is_literal_match(&self, name: &str) -> bool {
+        self.name
+            .as_ref()
+            .is_none_or(|query| name.to_lowercase().contains(&query.to_lowercase()))
+    }
+
+    /// Check whether this query matches on secret fields, which requires a
+    /// search index to be fully answered.
+    #[must_use]
+    pub const fn uses_fields(&self) -> bool {
+        !self.fields.is_empty() || self.url.is_some()
+    }
+}
+
+impl FieldFilter {
+    /// Parse a field filter in `KEY=VALUE` or `KEY` format.
+    #[must_use]
+    pub fn parse(filter: &str) -> Self {
+        match filter.split_once('=') {
+            Some((key, value)) => Self {
+                key:   key.trim().into(),
+                value: Some(value.trim().into()),
+            },
+            None => Self {
+                key:   filter.trim().into(),
+                value: None,
+            },
+        }
+    }
+
+    /// Score the given index entry against this filter.
+    ///
+    /// Sensitive fields have no indexed value, and only match a filter without
+    /// value.
+    fn score(&self, entry: &IndexEntry) -> Option<i64> {
+        let value = self.value.as_ref().map(|value| value.to_lowercase());
+        entry
+            .fields
+            .iter()
+            .filter(|field| field.key.eq_ignore_ascii_case(&self.key))
+            .filter_map(|field| match (&value, &field.value) {
+                (None, _) => Some(SCORE_MATCH),
+                (Some(query), Some(value)) if value.to_lowercase() == *query =>
+                    Some(3 * SCORE_MATCH),
+                (Some(query), Some(value)) if value.to_lowercase().contains(query) =>
+                    Some(SCORE_MATCH),
+                (Some(_), _) => None,
+            })
+            .max()
+    }
+}
+
+/// Find secrets in the given store matching the given query.
+///
+/// Field and URL filters use entries from the given search index. Secrets
+/// without an index entry never match field filters. Results are sorted by
+/// descending score, then by name.
+#[must_use]
+pub fn find(store: &Store, index: Option<&Index>, query: &Query) -> Vec<Match> {
+    let url = query
+        .url
+        .as_deref()
+        .and_then(|url| Some((host(url)?, registrable_domain(url)?)));
+    if query.url.is_some() && url.is_none() {
+        return vec![];
+    }
+
+    let mut matches: Vec<Match> = store
+        .secret_iter()
+        .filter_map(|secret| {
+            let entry = index.and_then(|index| index.get(&secret.name));
+            let mut score = 0;
+
+            if let Some(name) = &query.name {
+                score += fuzzy_score(&secret.name, name)?;
+            }
+            for filter in &query.fields {
+                score += filter.score(entry?)?;
+            }
+            if let Some((host, domain)) = &url {
+                score += url_score(&secret.name, entry, host, domain)?;
+            }
+
+            Some(Match { secret, score })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        Reverse(a.score)
+            .cmp(&Reverse(b.score))
+            .then_with(|| a.secret.name.cmp(&b.secret.name))
+    });
+    matches
+}
+
+/// Fuzzily match the given pattern against the given text.
+///
+/// Returns `None` if the pattern characters do not appear in order in the
+/// text, case-insensitively. Otherwise returns a score that prefers
+/// consecutive matches, matches at the start of words and shorter texts.
+#[must_use]
+pub fn fuzzy_score(text: &str, pattern: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let length_penalty = i64::try_from(text.len() / 4).unwrap_or(i64::MAX);
+
+    let Some(first) = pattern.first() else {
+        return Some(-length_penalty);
+    };
+
+    // Try each occurrence of the first character as start, keep the best
+    text.iter()
+        .enumerate()
+        .filter(|(_, c)| *c == first)
+        .filter_map(|(start, _)| score_from(&text, &pattern, start))
+        .max()
+        .map(|score| score - length_penalty)
+}
+
+/// Score a greedy fuzzy match of the pattern in the text, with the first
+/// pattern character matched at `start`.
+fn score_from(text: &[char], pattern: &[char], start: usize) -> Option<i64> {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    let mut pos = start;
+
+    for c in pattern {
+        let i = pos + text.get(pos..)?.iter().position(|t| t == c)?;
+
+        score += SCORE_MATCH;
+        match prev {
+            Some(prev) if prev + 1 == i => score += BONUS_CONSECUTIVE,
+            Some(prev) => {
+                score -= i64::try_from(i - prev - 1)
+                    .unwrap_or(MAX_GAP_PENALTY)
+                    .min(MAX_GAP_PENALTY);
+            },
+            None => {},
+        }
+        if i == 0 || WORD_BOUNDARIES.contains(&text[i - 1]) {
+            score += BONUS_BOUNDARY;
+        }
+
+        prev = Some(i);
+        pos = i + 1;
+    }
+
+    Some(score)
+}
+
+/// Score a secret against a URL query, given as host and registrable domain.
+///
+/// Matches URLs in indexed fields first, then secret name components such as
+/// `web/login.example.com` or `web/example`.
+fn url_score(name: &str, entry: Option<&IndexEntry>, host: &str, domain: &str) -> Option<i64> {
+    let field_score = entry
+        .into_iter()
+        .flat_map(|entry| entry.urls.iter())
+        .filter_map(|url| {
+            if self::host(url)? == host {
+                Some(SCORE_URL_HOST)
+            } else if registrable_domain(url)? == domain {
+                Some(SCORE_URL_DOMAIN)
+            } else {
+                None
+            }
+        })
+        .max();
+
+    let label = domain.split('.').next().unwrap_or(domain);
+    let name_score = name
+        .split('/')
+        .filter_map(|component| {
+            let component = component.to_lowercase();
+            if registrable_domain(&component)
+                .is_some_and(|d| d == domain && component.contains('.'))
+            {
+                Some(SCORE_NAME_DOMAIN)
+            } else if component == label {
+                Some(SCORE_NAME_LABEL)
+            } else {
+                None
+            }
+        })
+        .max();
+
+    field_score.into_iter().chain(name_score).max()
+}
+
+/// Get the lowercase host of the given URL.
+///
+/// The URL may omit the scheme. Returns `None` if there is no valid host.
+#[must_use]
+pub fn host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = url.split(&['/', '?', '#'][..]).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    // Keep bracketed IPv6 addresses whole, strip the port otherwise
+    let host = if host.starts_with('[') {
+        &host[..=host.find(']')?]
+    } else {
+        host.split(':').next()?
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+
+    let valid = host
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '.' | '_' | '[' | ']' | ':'));
+    (!host.is_empty() && valid).then_some(host)
+}
+
+/// Get the registrable domain of the given URL, such as `example.co.uk` for
+/// `https://login.example.co.uk/path`.
+///
+/// Known second-level labels below country code domains are kept, other
+/// subdomains are stripped. IP addresses are returned as is.
+#[must_use]
+pub fn registrable_domain(url: &str) -> Option<String> {
+    let host = host(url)?;
+    if host.starts_with('[') || host.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Some(host);
+    }
+
+    let labels: Vec<&str> = host.split('.').filter(|label| !label.is_empty()).collect();
+    let keep = match labels.as_slice() {
+        [.., second, tld]
+            if labels.len() > 2 && tld.len() == 2 && SECOND_LEVEL_LABELS.contains(second) =>
+            3,
+        _ => 2,
+    };
+    Some(labels[labels.len().saturating_sub(keep)..].join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_ranks_closer_matches_first() {
+        assert!(fuzzy_score("web/github", "gh").is_some());
+        assert!(fuzzy_score("web/github", "hg").is_none());
+        assert!(fuzzy_score("web/github", "git") > fuzzy_score("web/gitea-internal", "git"));
+        assert!(fuzzy_score("web/github", "github") > fuzzy_score("work/git-hub-old", "github"));
+        assert!(fuzzy_score("mail/work", "work") > fuzzy_score("mail/homework", "work"));
+        assert!(fuzzy_score("WEB/GitHub", "github").is_some());
+    }
+
+    #[test]
+    fn registrable_domain_of_urls() {
+        assert_eq!(
+            registrable_domain("https://login.example.com/path?q=1").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            registrable_domain("user@www.example.co.uk:8443").as_deref(),
+            Some("example.co.uk")
+        );
+        assert_eq!(
+            registrable_domain("Example.ORG.").as_deref(),
+            Some("example.org")
+        );
+        assert_eq!(
+            registrable_domain("http://10.0.0.1:80/").as_deref(),
+            Some("10.0.0.1")
+        );
+        assert_eq!(
+            registrable_domain("localhost").as_deref(),
+            Some("localhost")
+        );
+        assert_eq!(registrable_domain("not a url"), None);
+        assert_eq!(
+            host("https://Login.Example.com/").as_deref(),
+            Some("login.example.com")
+        );
+    }
+
+    #[test]
+    fn parse_queries() {
+        assert_eq!(
+            Query::parse(Some("https://example.com")).url.as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(Query::parse(Some(" mail ")).name.as_deref(), Some("mail"));
+        assert!(Query::parse(Some("")).name.is_none());
+        assert_eq!(FieldFilter::parse("user = alice"), FieldFilter {
+            key:   "user".into(),
+            value: Some("alice".into()),
+        });
+        assert_eq!(FieldFilter::parse("otp").value, None);
+    }
+
+    #[test]
+    fn literal_matches() {
+        let query = Query::parse(Some("Hub"));
+        assert!(query.is_literal_match("web/github"));
+        assert!(query.is_literal_match("HUB"));
+        assert!(!query.is_literal_match("web/git-hosting-ui-board"));
+        assert!(Query::parse(None).is_literal_match("web/github"));
+        assert!(Query::parse(Some("https://github.com")).is_literal_match("web/gh"));
+    }
+}
@@ -3,6 +3,7 @@
 #![allow(clippy::module_name_repetitions)]
 
 use std::{
+    collections::HashMap,
     ffi::OsStr,
     path::Path,
     process::{Command, ExitStatus, Output},
@@ -28,6 +29,12 @@ pub(crate) const BIN_NAME: &str = "git.exe";
 /// The git FETCH_HEAD file.
 const GIT_FETCH_HEAD_FILE: &str = ".git/FETCH_HEAD";
 
+/// Commit signature status for a good signature.
+const GIT_SIGNATURE_GOOD: &str = "G";
+
+/// Commit signature status for a good signature with unknown validity.
+const GIT_SIGNATURE_GOOD_UNTRUSTED: &str = "U";
+
 /// Invoke git init.
 pub(crate) fn git_init(repo: &Path) -> Result<()> {
     git(repo, &["init", "-q"], false)
@@ -50,17 +57,33 @@ pub(crate) fn git_add_all(repo: &Path) -> Result<()> {
     git(repo, &["add", "."], false)
 }
 
+/// Git unstage all staged changes, keeping the working tree.
+pub(crate) fn git_reset(repo: &Path) -> Result<()> {
+    git(repo, ["reset", "-q"], false)
+}
+
 /// Invoke git commit.
-pub(crate) fn git_commit(repo: &Path, msg: &str, commit_empty: bool) -> Result<()> {
+///
+/// Signs the commit with the given key if set.
+pub(crate) fn git_commit(
+    repo: &Path,
+    msg: &str,
+    commit_empty: bool,
+    sign_key: Option<&str>,
+) -> Result<()> {
     // Quit if no changes and we don't allow empty commit
     if !commit_empty && !git_has_changes(repo)? {
         return Ok(());
     }
 
+    let gpg_sign = sign_key.map(|key| format!("--gpg-sign={key}"));
     let mut args = vec!["commit", "-q", "--no-edit", "-m", msg];
     if commit_empty {
         args.push("--allow-empty");
     }
+    if let Some(gpg_sign) = &gpg_sign {
+        args.push(gpg_sign);
+    }
     git(repo, &args, false)
 }
 
@@ -84,8 +107,27 @@ pub(crate) fn git_push(
 
 /// Invoke git pull.
 pub(crate) fn git_pull(repo: &Path) -> Result<()> {
+    // Merge divergent branches unless configured, required by newer git
+    let mut args = vec!["pull", "-q"];
+    if !git_config_is_set(repo, "pull.rebase")? {
+        args.push("--no-rebase");
+    }
+
     // TODO: do not set -q flag if in verbose mode?
-    git(repo, &["pull", "-q"], true)
+    git(repo, &args, true)
+}
+
+/// Invoke git merge.
+///
+/// Fast-forwards if possible. Signs the merge commit with the given key if set.
+pub(crate) fn git_merge(repo: &Path, reference: &str, sign_key: Option<&str>) -> Result<()> {
+    let gpg_sign = sign_key.map(|key| format!("--gpg-sign={key}"));
+    let mut args = vec!["merge", "-q", "--no-edit"];
+    if let Some(gpg_sign) = &gpg_sign {
+        args.push(gpg_sign);
+    }
+    args.push(reference);
+    git(repo, &args, false)
 }
 
 /// Invoke git fetch.
@@ -216,6 +258,224 @@ pub(crate) fn git_ref_hash<S: AsRef<str>>(repo: &Path, reference: S) -> Result<S
     Ok(hash)
 }
 
+/// List commits in the given revision range with the fingerprint of their
+/// signer.
+///
+/// The signer is the primary key fingerprint of a good signature, it is `None`
+/// if a commit is unsigned or its signature is bad or cannot be checked.
+pub(crate) fn git_commit_signers(
+    repo: &Path,
+    range: &str,
+) -> Result<Vec<(String, Option<String>)>> {
+    Ok(
+        git_stdout_ok(repo, ["log", "--format=%H %G? %GP", range], false)?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let commit = fields.next()?.to_string();
+                let signer = match (fields.next(), fields.next()) {
+                    (Some(GIT_SIGNATURE_GOOD | GIT_SIGNATURE_GOOD_UNTRUSTED), Some(fp)) =>
+                        Some(fp.into()),
+                    _ => None,
+                };
+                Some((commit, signer))
+            })
+            .collect(),
+    )
+}
+
+/// Field separator in custom git log formats.
+const LOG_FIELD_SEPARATOR: char = '\x1f';
+
+/// Record separator in custom git log formats.
+const LOG_RECORD_SEPARATOR: char = '\x1e';
+
+/// A commit touching a path, from `git log`.
+pub(crate) struct GitLogEntry {
+    /// Commit hash.
+    pub(crate) commit: String,
+
+    /// Author name.
+    pub(crate) author: String,
+
+    /// Author time in seconds since the Unix epoch.
+    pub(crate) time: u64,
+
+    /// Commit message summary.
+    pub(crate) summary: String,
+
+    /// Change status letter, such as `A`, `M`, `D` or `R`.
+    pub(crate) status: char,
+
+    /// Path in this commit, relative to the repository root.
+    pub(crate) path: String,
+}
+
+/// List commits touching the given path, newest first.
+///
+/// Follows renames, the path of each entry is the path in that commit.
+pub(crate) fn git_log_follow(repo: &Path, path: &Path) -> Result<Vec<GitLogEntry>> {
+    let (record, sep) = (LOG_RECORD_SEPARATOR, LOG_FIELD_SEPARATOR);
+    let format = format!("--format={record}%H{sep}%an{sep}%at{sep}%s");
+    let output = git_stdout_ok(
+        repo,
+        [
+            OsStr::new("log"),
+            OsStr::new("--follow"),
+            OsStr::new("--name-status"),
+            OsStr::new(&format),
+            OsStr::new("--"),
+            path.as_os_str(),
+        ],
+        false,
+    )?;
+
+    Ok(output
+        .split(LOG_RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut lines = record.lines().filter(|line| !line.trim().is_empty());
+            let mut fields = lines.next()?.splitn(4, LOG_FIELD_SEPARATOR);
+            let commit = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let time = fields.next()?.parse().ok()?;
+            let summary = fields.next().unwrap_or_default().to_string();
+
+            // Name status line, the last field is the path in this commit
+            let status_line = lines.next()?;
+            let status = status_line.chars().next()?;
+            let path = status_line.rsplit('\t').next()?.to_string();
+
+            Some(GitLogEntry {
+                commit,
+                author,
+                time,
+                summary,
+                status,
+                path,
+            })
+        })
+        .collect())
+}
+
+/// Get the time each path was last changed, from `git log`.
+///
+/// Maps paths relative to the repository root to the author time of the newest
+/// commit touching them, in seconds since the Unix epoch. Renames are not
+/// followed.
+pub(crate) fn git_last_changed(repo: &Path) -> Result<HashMap<String, u64>> {
+    let format = format!("--format={LOG_RECORD_SEPARATOR}%at");
+    let output = git_stdout_ok(
+        repo,
+        [
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--name-only",
+            "--no-renames",
+            &format,
+        ],
+        false,
+    )?;
+
+    // Commits are listed newest first, keep the first time seen for each path
+    let mut changed = HashMap::new();
+    for record in output.split(LOG_RECORD_SEPARATOR) {
+        let mut lines = record.lines().filter(|line| !line.trim().is_empty());
+        let Some(time) = lines.next().and_then(|time| time.trim().parse().ok()) else {
+            continue;
+        };
+        for path in lines {
+            changed.entry(path.to_string()).or_insert(time);
+        }
+    }
+    Ok(changed)
+}
+
+/// Get the contents of a file at the given revision.
+///
+/// The path must be relative to the repository root.
+pub(crate) fn git_show_blob(repo: &Path, rev: &str, path: &str) -> Result<Vec<u8>> {
+    let output = git_output(repo, ["show", &format!("{rev}:{path}")], false)?;
+    cmd_assert_status(output.status)?;
+    Ok(output.stdout)
+}
+
+/// Resolve the given revision to a full commit hash.
+///
+/// Returns `None` if the revision does not exist.
+pub(crate) fn git_rev_parse_commit(repo: &Path, rev: &str) -> Result<Option<String>> {
+    let output = git_output(
+        repo,
+        [
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{rev}^{{commit}}"),
+        ],
+        false,
+    )?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        std::str::from_utf8(&output.stdout)
+            .map_err(|err| Err::GitCli(err.into()))?
+            .trim()
+            .into(),
+    ))
+}
+
+/// Check whether commit `ancestor` is an ancestor of, or equal to, `commit`.
+pub(crate) fn git_is_ancestor(repo: &Path, ancestor: &str, commit: &str) -> Result<bool> {
+    let output = git_output(
+        repo,
+        ["merge-base", "--is-ancestor", ancestor, commit],
+        false,
+    )?;
+
+    // Exit code 1 means it is not an ancestor
+    if output.status.code() == Some(1) {
+        return Ok(false);
+    }
+    cmd_assert_status(output.status)?;
+    Ok(true)
+}
+
+/// Check whether a git config value is set, regardless of its type.
+pub(crate) fn git_config_is_set(repo: &Path, key: &str) -> Result<bool> {
+    let output = git_output(repo, ["config", "--get", key], false)?;
+
+    // Exit code 1 means the key is not set
+    if output.status.code() == Some(1) {
+        return Ok(false);
+    }
+    cmd_assert_status(output.status)?;
+    Ok(true)
+}
+
+/// Get a boolean git config value.
+///
+/// Returns `None` if the value is not set.
+pub(crate) fn git_config_bool(repo: &Path, key: &str) -> Result<Option<bool>> {
+    let output = git_output(repo, ["config", "--bool", "--get", key], false)?;
+
+    // Exit code 1 means the key is not set
+    if output.status.code() == Some(1) {
+        return Ok(None);
+    }
+    cmd_assert_status(output.status)?;
+
+    let value = std::str::from_utf8(&output.stdout)
+        .map_err(|err| Err::GitCli(err.into()))?
+        .trim();
+    Ok(Some(value == "true"))
+}
+
+/// Set a git config value in the local repository config.
+pub(crate) fn git_config_set(repo: &Path, key: &str, value: &str) -> Result<()> {
+    git(repo, ["config", "--local", key, value], false)
+}
+
 /// Get system time the repository was last pulled.
 /// See: https://stackoverflow.com/a/9229377/1000145 (stat -c %Y .git/FETCH_HEAD)
 pub(crate) fn git_last_pull_time(repo: &Path) -> Result<SystemTime> {
@@ -0,0 +1,556 @@
+//! Password health checks.
+//!
+//! Audits secrets for weak passwords, passwords reused across secrets,
+//! passwords that have not changed for a long time and missing common fields.
+//!
+//! Password strength is estimated in bits of entropy, taking common words,
+//! keyboard patterns, sequences, repeats and years into account. Reuse is
+//! detected by a keyed hash of each password, plaintexts are never kept.
+
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
+    convert::TryFrom,
+    fmt,
+    hash::BuildHasher,
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+
+use crate::{index::URL_KEYS, SecretBody};
+
+/// Common passwords and words, most common first.
+#[rustfmt::skip]
+const COMMON_WORDS: &[&str] = &[
+    "password", "qwerty", "dragon", "baseball", "football", "letmein", "monkey", "abc", "mustang",
+    "michael", "shadow", "master", "jennifer", "jordan", "superman", "harley", "hunter", "trustno",
+    "ranger", "buster", "thomas", "tigger", "robert", "soccer", "batman", "test", "pass", "killer",
+    "hockey", "george", "charlie", "andrew", "michelle", "love", "sunshine", "jessica", "asshole",
+    "pepper", "daniel", "access", "joshua", "maggie", "starwars", "silver", "william", "dallas",
+    "yankees", "hello", "amanda", "orange", "biteme", "freedom", "computer", "secret", "whatever",
+    "nicole", "ginger", "cheese", "summer", "winter", "spring", "autumn", "flower", "princess",
+    "iloveyou", "welcome", "login", "admin", "administrator", "root", "user", "guest", "default",
+    "changeme", "secure", "internet", "pokemon", "banana", "apple", "chocolate", "cookie", "money",
+    "matrix", "google", "samsung", "zaq", "qazwsx", "passw", "passwd", "temp", "music", "purple",
+    "family", "friend", "monday", "november", "december",
+];
+
+/// Keyboard rows, for patterns like `qwerty` or `asdf`.
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// First year recognized as a year pattern.
+const FIRST_YEAR: u32 = 1900;
+
+/// Last year recognized as a year pattern.
+const LAST_YEAR: u32 = 2039;
+
+/// A character class, with a predicate and its number of characters.
+type CharClass = (fn(&char) -> bool, usize);
+
+/// ASCII character classes counted in the character pool of a password.
+const CHAR_CLASSES: [CharClass; 4] = [
+    (char::is_ascii_lowercase, 26),
+    (char::is_ascii_uppercase, 26),
+    (char::is_ascii_digit, 10),
+    (char::is_ascii_punctuation, 33),
+];
+
+/// Minimum length of a pattern in a password.
+const MIN_PATTERN_LEN: usize = 3;
+
+/// Maximum password length to search patterns in, longer passwords are only
+/// estimated by their characters.
+const MAX_PATTERN_SEARCH_LEN: usize = 128;
+
+/// Field keys of which one must be set for a secret to have a user.
+pub const USER_KEYS: [&str; 4] = ["user", "username", "login", "email"];
+
+/// Policy to audit secrets against.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// Minimum password entropy in bits, not checked if `None`.
+    pub min_entropy: Option<f64>,
+
+    /// Maximum time since a secret was last changed, not checked if `None`.
+    pub max_age: Option<Duration>,
+
+    /// Whether to check for passwords reused across secrets.
+    pub reuse: bool,
+
+    /// Whether to check for missing user and URL fields.
+    pub fields: bool,
+}
+
+/// Audit report.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    /// Number of audited secrets.
+    pub secrets: usize,
+
+    /// Secrets with issues, sorted by name.
+    pub findings: Vec<Finding>,
+}
+
+/// Issues found in a single secret.
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    /// Secret name.
+    pub name: String,
+
+    /// Issues found.
+    pub issues: Vec<Issue>,
+}
+
+/// Issue found in a secret.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// Password entropy is below the policy minimum.
+    Weak {
+        /// Estimated entropy in bits.
+        entropy: f64,
+
+        /// Policy minimum in bits.
+        minimum: f64,
+    },
+
+    /// Password is also used in other secrets.
+    Reused {
+        /// Names of the other secrets.
+        secrets: Vec<String>,
+    },
+
+    /// Secret was not changed within the policy maximum age.
+    Old {
+        /// Days since the secret was last changed.
+        days: u64,
+
+        /// Policy maximum in days.
+        maximum: u64,
+    },
+
+    /// Secret has none of the fields for a user or URL.
+    MissingField {
+        /// The missing field.
+        field: &'static str,
+    },
+
+    /// Secret could not be decrypted or parsed.
+    Unreadable {
+        /// The error.
+        error: String,
+    },
+}
+
+/// Auditor checking secrets against a policy.
+///
+/// Check each secret, then finish to get the report. Reused passwords are
+/// reported when finishing.
+pub struct Auditor {
+    /// The policy.
+    policy: Policy,
+
+    /// Hasher for passwords, to detect reuse.
+    hasher: ReuseHasher,
+
+    /// Names of secrets by password hash.
+    passwords: HashMap<u64, Vec<String>>,
+
+    /// Number of checked secrets.
+    secrets: usize,
+
+    /// Issues by secret name.
+    issues: BTreeMap<String, Vec<Issue>>,
+}
+
+impl Auditor {
+    /// Construct a new auditor for the given policy.
+    #[must_use]
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            hasher: ReuseHasher::new(),
+            passwords: HashMap::new(),
+            secrets: 0,
+            issues: BTreeMap::new(),
+        }
+    }
+
+    /// Check a secret with the given name and body.
+    ///
+    /// `changed` is the time the secret was last changed, the age is not
+    /// checked if unknown.
+    pub fn check(&mut self, name: &str, body: &SecretBody, changed: Option<SystemTime>) {
+        self.secrets += 1;
+        let mut issues = vec![];
+        let password = body.password();
+
+        if let Some(minimum) = self.policy.min_entropy {
+            let entropy = entropy(password);
+            if entropy < minimum {
+                issues.push(Issue::Weak { entropy, minimum });
+            }
+        }
+
+        if self.policy.reuse && !password.is_empty() {
+            self.passwords
+                .entry(self.hasher.hash(password))
+                .or_default()
+                .push(name.into());
+        }
+
+        if let (Some(max_age), Some(changed)) = (self.policy.max_age, changed) {
+            let age = SystemTime::now()
+                .duration_since(changed)
+                .unwrap_or_default();
+            if age > max_age {
+                issues.push(Issue::Old {
+                    days:    age.as_secs() / 86400,
+                    maximum: max_age.as_secs() / 86400,
+                });
+            }
+        }
+
+        if self.policy.fields {
+            for (field, keys) in [("user", &USER_KEYS), ("url", &URL_KEYS)] {
+                let set = keys
+                    .iter()
+                    .any(|key| body.get_all(key).any(|value| !value.trim().is_empty()));
+                if !set {
+                    issues.push(Issue::MissingField { field });
+                }
+            }
+        }
+
+        if !issues.is_empty() {
+            self.issues.entry(name.into()).or_default().extend(issues);
+        }
+    }
+
+    /// Report a secret with the given name that could not be checked.
+    pub fn unreadable(&mut self, name: &str, err: &anyhow::Error) {
+        self.secrets += 1;
+        self.issues
+            .entry(name.into())
+            .or_default()
+            .push(Issue::Unreadable {
+                error: format!("{err:#}"),
+            });
+    }
+
+    /// Finish the audit, and get the report.
+    #[must_use]
+    pub fn finish(mut self) -> Report {
+        for names in self.passwords.values().filter(|names| names.len() > 1) {
+            for name in names {
+                let secrets = names
+                    .iter()
+                    .filter(|other| *other != name)
+                    .cloned()
+                    .collect();
+                self.issues
+                    .entry(name.clone())
+                    .or_default()
+                    .push(Issue::Reused { secrets });
+            }
+        }
+
+        Report {
+            secrets:  self.secrets,
+            findings: self
+                .issues
+                .into_iter()
+                .map(|(name, issues)| Finding { name, issues })
+                .collect(),
+        }
+    }
+}
+
+impl Report {
+    /// Serialize this report as pretty JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Weak { entropy, minimum } => write!(
+                f,
+                "weak password: {entropy:.0} bits of entropy, minimum is {minimum:.0}"
+            ),
+            Self::Reused { secrets } =>
+                write!(f, "reused password: also in {}", secrets.join(", ")),
+            Self::Old { days, maximum } => write!(
+                f,
+                "old password: unchanged for {days} days, maximum is {maximum}"
+            ),
+            Self::MissingField { field } => write!(f, "missing field: {field}"),
+            Self::Unreadable { error } => write!(f, "unreadable: {error}"),
+        }
+    }
+}
+
+/// Keyed password hasher, to detect reuse without keeping plaintexts.
+///
+/// Each hasher uses a random key, hashes are only comparable within the same
+/// hasher and are meaningless outside this process.
+pub struct ReuseHasher {
+    /// Random hash key.
+    key: RandomState,
+}
+
+impl ReuseHasher {
+    /// Construct a new hasher with a random key.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            key: RandomState::new(),
+        }
+    }
+
+    /// Hash the given password.
+    #[must_use]
+    pub fn hash(&self, password: &str) -> u64 {
+        self.key.hash_one(password)
+    }
+}
+
+impl Default for ReuseHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Estimate the entropy of the given password in bits.
+///
+/// Finds the cheapest way to build the password from patterns and single
+/// characters. Patterns are common words, also reversed, capitalized or with
+/// common character substitutions, keyboard rows, alphabetic and numeric
+/// sequences, repeated characters and years. Single characters cost the bits
+/// of the character classes used in the password.
+#[must_use]
+pub fn entropy(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    let char_bits = log2(pool_size(&chars));
+    if chars.len() > MAX_PATTERN_SEARCH_LEN {
+        return char_bits * float(chars.len());
+    }
+
+    let lower: Vec<char> = chars.iter().map(|c| lowercase(*c)).collect();
+    let unleet: Vec<char> = lower.iter().map(|c| unleet(*c)).collect();
+
+    // Cheapest bits to build each prefix of the password
+    let mut best = vec![f64::INFINITY; chars.len() + 1];
+    best[0] = 0.0;
+    for end in 1..=chars.len() {
+        best[end] = best[end - 1] + char_bits;
+        for start in 0..end.saturating_sub(MIN_PATTERN_LEN - 1) {
+            let range = start..end;
+            let bits = pattern_bits(
+                &chars[range.clone()],
+                &lower[range.clone()],
+                &unleet[range],
+                char_bits,
+            );
+            if let Some(bits) = bits {
+                best[end] = best[end].min(best[start] + bits);
+            }
+        }
+    }
+
+    best[chars.len()]
+}
+
+/// Get the bits of the cheapest pattern matching the given password segment,
+/// if any.
+///
+/// The segment is given as is, in lowercase, and in lowercase with common
+/// substitutions undone.
+fn pattern_bits(chars: &[char], lower: &[char], unleet: &[char], char_bits: f64) -> Option<f64> {
+    let len = chars.len();
+    let lower_str: String = lower.iter().collect();
+    let mut bits: Vec<f64> = vec![];
+
+    // Repeated character
+    if lower.iter().all(|c| *c == lower[0]) {
+        bits.push(char_bits + log2(len));
+    }
+
+    // Sequence, such as abc or 987
+    let steps: Vec<i64> = lower
+        .windows(2)
+        .map(|w| i64::from(u32::from(w[1])) - i64::from(u32::from(w[0])))
+        .collect();
+    if steps.iter().all(|step| *step == steps[0]) && steps[0].abs() == 1 {
+        let alphabet = if chars[0].is_ascii_digit() { 10 } else { 26 };
+        let descending = if steps[0] < 0 { 1.0 } else { 0.0 };
+        bits.push(log2(alphabet) + log2(len) + descending);
+    }
+
+    // Keyboard row
+    for row in &KEYBOARD_ROWS {
+        let reversed: String = row.chars().rev().collect();
+        if row.contains(&lower_str) {
+            bits.push(log2(row.len()) + log2(len));
+        } else if reversed.contains(&lower_str) {
+            bits.push(log2(row.len()) + log2(len) + 1.0);
+        }
+    }
+
+    // Year
+    if len == 4 {
+        if let Ok(year) = lower_str.parse::<u32>() {
+            if (FIRST_YEAR..=LAST_YEAR).contains(&year) {
+                bits.push(f64::from(LAST_YEAR - FIRST_YEAR + 1).log2());
+            }
+        }
+    }
+
+    // Common word, possibly reversed, capitalized or with substitutions
+    let word: String = unleet.iter().collect();
+    let reversed: String = unleet.iter().rev().collect();
+    let rank = COMMON_WORDS
+        .iter()
+        .position(|common| *common == word)
+        .map(|rank| (rank, 0.0))
+        .or_else(|| {
+            COMMON_WORDS
+                .iter()
+                .position(|common| *common == reversed)
+                .map(|rank| (rank, 1.0))
+        });
+    if let Some((rank, reversed_bits)) = rank {
+        let leet_bits = if unleet == lower { 0.0 } else { 1.0 };
+        bits.push(log2(rank + 2) + case_bits(chars) + leet_bits + reversed_bits);
+    }
+
+    bits.into_iter().reduce(f64::min)
+}
+
+/// Get the bits for the capitalization of a word.
+fn case_bits(chars: &[char]) -> f64 {
+    let upper = chars.iter().filter(|c| c.is_uppercase()).count();
+    let first_upper = chars.first().is_some_and(|c| c.is_uppercase());
+    if upper == 0 {
+        0.0
+    } else if upper == chars.len() || (upper == 1 && first_upper) {
+        1.0
+    } else {
+        float(chars.len())
+    }
+}
+
+/// Get the number of possible characters for the character classes used in
+/// the given password.
+fn pool_size(chars: &[char]) -> usize {
+    let mut pool: usize = CHAR_CLASSES
+        .iter()
+        .filter(|(class, _)| chars.iter().any(class))
+        .map(|(_, size)| size)
+        .sum();
+    if chars
+        .iter()
+        .any(|c| !c.is_ascii_alphanumeric() && !c.is_ascii_punctuation())
+    {
+        pool += 100;
+    }
+    pool.max(1)
+}
+
+/// Get the lowercase variant of a character.
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Undo common character substitutions, such as `4` for `a`.
+const fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '8' => 'b',
+        '(' => 'c',
+        '3' => 'e',
+        '6' | '9' => 'g',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' | '+' => 't',
+        '2' => 'z',
+        c => c,
+    }
+}
+
+/// Get the given number as float.
+fn float(n: usize) -> f64 {
+    f64::from(u32::try_from(n).unwrap_or(u32::MAX))
+}
+
+/// Get the base 2 logarithm of the given number.
+fn log2(n: usize) -> f64 {
+    float(n).log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Plaintext;
+
+    #[test]
+    fn entropy_of_patterns() {
+        assert!(entropy("") < 1.0);
+        assert!(entropy("password") < 5.0);
+        assert!(entropy("P4ssw0rd") < 10.0);
+        assert!(entropy("drowssap") < 10.0);
+        assert!(entropy("qwertyuiop") < 10.0);
+        assert!(entropy("abcdefgh") < 10.0);
+        assert!(entropy("aaaaaaaaaaaa") < 10.0);
+        assert!(entropy("summer1987") < 25.0);
+        assert!(entropy("Tr0ub4dor&3") > 30.0);
+        assert!(entropy("xK9#mQ2$vL7!pR4@") > 80.0);
+        assert!(entropy("summer1987") < entropy("smr8x1q7ez"));
+    }
+
+    #[test]
+    fn auditor_reports_issues() {
+        let body =
+            |text: &str| SecretBody::parse(&Plaintext::from(text.as_bytes().to_vec())).unwrap();
+        let mut auditor = Auditor::new(Policy {
+            min_entropy: Some(50.0),
+            max_age:     Some(Duration::from_secs(86400)),
+            reuse:       true,
+            fields:      true,
+        });
+        let old = SystemTime::now() - Duration::from_secs(3 * 86400);
+        auditor.check("a", &body("password\nuser: alice\n"), Some(old));
+        auditor.check("b", &body("password\nurl: example.com\n"), None);
+        auditor.check(
+            "c",
+            &body("xK9#mQ2$vL7!pR4@\nuser: carol\nurl: example.org\n"),
+            Some(SystemTime::now()),
+        );
+        auditor.unreadable("d", &anyhow::anyhow!("failed"));
+
+        let report = auditor.finish();
+        assert_eq!(report.secrets, 4);
+        let names: Vec<_> = report.findings.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "d"]);
+
+        let issues: Vec<String> = report.findings[0]
+            .issues
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(issues[0].starts_with("weak password"));
+        assert_eq!(
+            issues[1],
+            "old password: unchanged for 3 days, maximum is 1"
+        );
+        assert_eq!(issues[2], "missing field: url");
+        assert_eq!(issues[3], "reused password: also in b");
+        assert!(matches!(
+            report.findings[1].issues[1],
+            Issue::MissingField { field: "user" }
+        ));
+    }
+}
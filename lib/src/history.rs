@@ -0,0 +1,231 @@
+//! Secret history from the password store git repository.
+//!
+//! All operations are read-only, use [`Sync`](crate::sync::Sync) to commit
+//! changes.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{
+    git::{self, GitLogEntry},
+    store::strip_secret_suffix,
+    Ciphertext, Store,
+};
+
+/// History helper for given store.
+pub struct History<'a> {
+    /// The store.
+    store: &'a Store,
+}
+
+/// A revision of a secret, a commit that changed it.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    /// Commit hash.
+    pub commit: String,
+
+    /// Commit author name.
+    pub author: String,
+
+    /// Commit author time.
+    pub time: SystemTime,
+
+    /// Commit message summary.
+    pub summary: String,
+
+    /// Secret path in this revision, relative to the store root.
+    pub path: PathBuf,
+
+    /// Whether the secret was removed in this revision.
+    pub removed: bool,
+}
+
+impl<'a> History<'a> {
+    /// Construct new history helper for given store.
+    #[must_use]
+    pub const fn new(store: &'a Store) -> Self {
+        Self { store }
+    }
+
+    /// Get the repository path.
+    fn path(&self) -> &Path {
+        &self.store.root
+    }
+
+    /// List revisions of the secret at the given path, newest first.
+    ///
+    /// The path is relative to the store root. Renames are followed, removed
+    /// secrets are listed as well.
+    pub fn log(&self, path: &Path) -> Result<Vec<Revision>> {
+        ensure_init(self.store)?;
+        Ok(git::git_log_follow(self.path(), path)
+            .map_err(Err::Log)?
+            .into_iter()
+            .map(Revision::from)
+            .collect())
+    }
+
+    /// Get the time each secret was last changed, by path relative to the
+    /// store root.
+    ///
+    /// Paths not changed in any commit are not included.
+    pub fn last_changed(&self) -> Result<HashMap<PathBuf, SystemTime>> {
+        ensure_init(self.store)?;
+        Ok(git::git_last_changed(self.path())
+            .map_err(Err::Log)?
+            .into_iter()
+            .map(|(path, time)| (path.into(), UNIX_EPOCH + Duration::from_secs(time)))
+            .collect())
+    }
+
+    /// Resolve the given revision to a full commit hash.
+    pub fn resolve(&self, rev: &str) -> Result<String> {
+        ensure_init(self.store)?;
+        git::git_rev_parse_commit(self.path(), rev)
+            .map_err(Err::Resolve)?
+            .ok_or_else(|| Err::UnknownRevision(rev.into()).into())
+    }
+
+    /// Find the revision of the secret at the given path as it was in commit
+    /// `rev`.
+    ///
+    /// This is the newest revision of the secret that is `rev` itself or one
+    /// of its ancestors.
+    pub fn revision_at(&self, path: &Path, rev: &str) -> Result<Revision> {
+        let commit = self.resolve(rev)?;
+        let log = self.log(path)?;
+
+        let mut found = log.iter().find(|revision| revision.commit == commit);
+        if found.is_none() {
+            for revision in &log {
+                if git::git_is_ancestor(self.path(), &revision.commit, &commit)
+                    .map_err(Err::Resolve)?
+                {
+                    found = Some(revision);
+                    break;
+                }
+            }
+        }
+
+        found
+            .filter(|revision| !revision.removed)
+            .cloned()
+            .ok_or_else(|| Err::NotInRevision(rev.into()).into())
+    }
+
+    /// Read the ciphertext of the secret at the given path as it was in
+    /// commit `rev`.
+    pub fn read(&self, path: &Path, rev: &str) -> Result<Ciphertext> {
+        let revision = self.revision_at(path, rev)?;
+        self.read_revision(&revision)
+    }
+
+    /// Read the ciphertext of the secret in the given revision.
+    pub fn read_revision(&self, revision: &Revision) -> Result<Ciphertext> {
+        if revision.removed {
+            return Err(Err::NotInRevision(revision.commit.clone()).into());
+        }
+
+        // Git paths always use forward slashes
+        let path = revision
+            .path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        Ok(git::git_show_blob(self.path(), &revision.commit, &path)
+            .map_err(Err::Read)?
+            .into())
+    }
+}
+
+impl Revision {
+    /// Get the abbreviated commit hash.
+    #[must_use]
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+
+    /// Get the secret name in this revision, its path without secret suffix.
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.path
+            .to_str()
+            .map_or("?", strip_secret_suffix)
+            .to_owned()
+    }
+}
+
+impl From<GitLogEntry> for Revision {
+    fn from(entry: GitLogEntry) -> Self {
+        Self {
+            commit:  entry.commit,
+            author:  entry.author,
+            time:    UNIX_EPOCH + Duration::from_secs(entry.time),
+            summary: entry.summary,
+            path:    entry.path.into(),
+            removed: entry.status == 'D',
+        }
+    }
+}
+
+/// Ensure the store has a git repository to read history from.
+fn ensure_init(store: &Store) -> Result<()> {
+    if store.sync().is_init() {
+        Ok(())
+    } else {
+        Err(Err::NoGit.into())
+    }
+}
+
+/// A history error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("password store does not use git, it has no history")]
+    NoGit,
+
+    #[error("failed to list secret history")]
+    Log(#[source] anyhow::Error),
+
+    #[error("failed to resolve revision")]
+    Resolve(#[source] anyhow::Error),
+
+    #[error("unknown revision '{}'", _0)]
+    UnknownRevision(String),
+
+    #[error("secret does not exist in revision '{}'", _0)]
+    NotInRevision(String),
+
+    #[error("failed to read secret from git")]
+    Read(#[source] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revision_from_log_entry() {
+        let revision = Revision::from(GitLogEntry {
+            commit:  "0123456789abcdef0123456789abcdef01234567".into(),
+            author:  "alice".into(),
+            time:    1_600_000_000,
+            summary: "Remove secret social/github".into(),
+            status:  'D',
+            path:    "social/github.gpg".into(),
+        });
+        assert_eq!(revision.short_commit(), "0123456");
+        assert_eq!(revision.name(), "social/github");
+        assert_eq!(
+            revision.time,
+            UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+        );
+        assert!(revision.removed);
+    }
+}
@@ -0,0 +1,413 @@
+//! Encrypted search index.
+//!
+//! Searching secret contents requires decrypting every secret, which is slow
+//! for large stores. The search index is an opt-in file, `.prs-index` in the
+//! store root, that is encrypted for the store recipients. It holds the name,
+//! field names, non-sensitive field values and URLs of each secret, so these
+//! can be queried by decrypting a single file.
+//!
+//! Each entry records the modification time and size of the secret file it was
+//! built from. Entries of changed secrets are stale, and can be updated
+//! incrementally by only decrypting those secrets again, see [`Index::stale`].
+//!
+//! The index is local to this machine and is not synced. It may hold fields of
+//! secrets in directories with other recipients, it must therefore never be
+//! shared.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    body::SecretBody,
+    crypto::{prelude::*, Context},
+    Plaintext, Recipients, Secret, Store,
+};
+
+/// Search index file in the store root.
+pub const INDEX_FILE: &str = ".prs-index";
+
+/// Version of the index format, indexes of other versions must be rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+/// Field keys of which the value is sensitive and is not indexed.
+///
+/// A field is sensitive if its lowercase key contains any of these.
+const SENSITIVE_KEYS: [&str; 9] = [
+    "pass", "pin", "secret", "token", "key", "otp", "cvc", "cvv", "private",
+];
+
+/// Field keys of which the value is a URL, even without scheme.
+pub const URL_KEYS: [&str; 4] = ["url", "uri", "website", "site"];
+
+/// URL schemes recognized in field values.
+const URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+/// Search index of a store.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Index {
+    /// Index format version.
+    version: u32,
+
+    /// Entries by secret name.
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+/// Search index entry of a single secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Secret path, relative to the store root.
+    pub path: PathBuf,
+
+    /// Stamp of the secret file this entry was built from.
+    stamp: Stamp,
+
+    /// Whether the secret could not be decrypted or parsed, it has no fields.
+    #[serde(default)]
+    pub unreadable: bool,
+
+    /// Fields in the secret body.
+    #[serde(default)]
+    pub fields: Vec<IndexField>,
+
+    /// URLs found in the secret body.
+    #[serde(default)]
+    pub urls: Vec<String>,
+}
+
+/// Indexed field of a secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexField {
+    /// Field key.
+    pub key: String,
+
+    /// Field value, not set for sensitive fields.
+    pub value: Option<String>,
+}
+
+/// Stamp of a secret file, to detect changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    /// Modification time in nanoseconds since the Unix epoch.
+    modified: u128,
+
+    /// File size in bytes.
+    len: u64,
+}
+
+/// Secrets that changed since the index was updated.
+#[derive(Debug, Default)]
+pub struct Stale {
+    /// Secrets that are new or changed, and must be indexed again.
+    pub changed: Vec<Secret>,
+
+    /// Names of secrets that no longer exist.
+    pub removed: Vec<String>,
+}
+
+impl Index {
+    /// Construct a new empty index.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Get the index file path for the given store.
+    #[must_use]
+    pub fn path(store: &Store) -> PathBuf {
+        store.root.join(INDEX_FILE)
+    }
+
+    /// Check whether the search index is enabled for the given store.
+    ///
+    /// The index is enabled if the index file exists.
+    #[must_use]
+    pub fn is_enabled(store: &Store) -> bool {
+        Self::path(store).is_file()
+    }
+
+    /// Load and decrypt the index of the given store.
+    ///
+    /// Errors if the index has an unsupported format version, it must be
+    /// rebuilt.
+    pub fn load(store: &Store, context: &mut Context) -> Result<Self> {
+        let plaintext = context
+            .decrypt_file(&Self::path(store))
+            .map_err(Err::Read)?;
+        let index: Self = serde_json::from_slice(plaintext.unsecure_ref()).map_err(Err::Parse)?;
+        if index.version != INDEX_VERSION {
+            return Err(Err::Version(index.version).into());
+        }
+        Ok(index)
+    }
+
+    /// Encrypt the index for the store recipients and save it.
+    ///
+    /// The file is replaced atomically.
+    pub fn save(&self, store: &Store, context: &mut Context) -> Result<()> {
+        let recipients = Recipients::load(store).map_err(Err::Write)?;
+        let plaintext =
+            Plaintext::from(serde_json::to_vec(self).map_err(|err| Err::Write(err.into()))?);
+        context
+            .encrypt_file(&recipients, plaintext, &Self::path(store))
+            .map_err(|err| Err::Write(err).into())
+    }
+
+    /// Remove the index file of the given store, disabling the index.
+    pub fn remove(store: &Store) -> Result<()> {
+        match fs::remove_file(Self::path(store)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(Err::Remove(err).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Find secrets in the store that changed since they were indexed.
+    #[must_use]
+    pub fn stale(&self, store: &Store) -> Stale {
+        let mut stale = Stale::default();
+        let mut names = Vec::new();
+        for secret in store.secret_iter() {
+            let current = match self.entries.get(&secret.name) {
+                Some(entry) => stamp(&secret.path).is_some_and(|stamp| stamp == entry.stamp),
+                None => false,
+            };
+            names.push(secret.name.clone());
+            if !current {
+                stale.changed.push(secret);
+            }
+        }
+
+        names.sort_unstable();
+        stale.removed = self
+            .entries
+            .keys()
+            .filter(|name| names.binary_search(name).is_err())
+            .cloned()
+            .collect();
+        stale
+    }
+
+    /// Index the given secret with its plaintext, replacing any existing
+    /// entry.
+    ///
+    /// Use `None` as plaintext if the secret cannot be decrypted, to not retry
+    /// until it changes.
+    pub fn insert(&mut self, store: &Store, secret: &Secret, plaintext: Option<&Plaintext>) {
+        let path = secret
+            .relative_path(&store.root)
+            .map_or_else(|_| secret.path.clone(), Path::to_path_buf);
+        let stamp = stamp(&secret.path).unwrap_or(Stamp {
+            modified: 0,
+            len:      0,
+        });
+        let body = plaintext.and_then(|plaintext| SecretBody::parse(plaintext).ok());
+
+        let mut entry = IndexEntry {
+            path,
+            stamp,
+            unreadable: body.is_none(),
+            fields: vec![],
+            urls: vec![],
+        };
+        if let Some(body) = body {
+            for field in body.fields() {
+                let sensitive = is_sensitive(field.key());
+                entry.fields.push(IndexField {
+                    key:   field.key().into(),
+                    value: (!sensitive).then(|| field.value().into()),
+                });
+                if !sensitive {
+                    entry
+                        .urls
+                        .extend(find_urls(field.key(), field.value()).map(Into::into));
+                }
+            }
+            entry.urls.dedup();
+        }
+
+        self.entries.insert(secret.name.clone(), entry);
+    }
+
+    /// Remove the entry of the secret with the given name.
+    pub fn forget(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+
+    /// Get the entry of the secret with the given name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&IndexEntry> {
+        self.entries.get(name)
+    }
+
+    /// Iterate over all entries by secret name, sorted by name.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &IndexEntry)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Get the number of indexed secrets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the index has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find secrets matching the given query in the store.
+    ///
+    /// Matches the query case-insensitively against the secret name, field
+    /// keys, indexed field values and URLs.
+    #[must_use]
+    pub fn search(&self, store: &Store, query: &str) -> Vec<Secret> {
+        let query = query.to_lowercase();
+        self.entries()
+            .filter(|(name, entry)| name.to_lowercase().contains(&query) || entry.matches(&query))
+            .map(|(_, entry)| Secret::from(store, store.root.join(&entry.path)))
+            .collect()
+    }
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IndexEntry {
+    /// Get the indexed value of the first field with the given key,
+    /// case-insensitively.
+    #[must_use]
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.key.eq_ignore_ascii_case(key.trim()))
+            .and_then(|field| field.value.as_deref())
+    }
+
+    /// Check whether the given lowercase query matches any field key, indexed
+    /// value or URL.
+    fn matches(&self, query: &str) -> bool {
+        self.fields.iter().any(|field| {
+            field.key.to_lowercase().contains(query)
+                || field
+                    .value
+                    .as_ref()
+                    .is_some_and(|value| value.to_lowercase().contains(query))
+        }) || self
+            .urls
+            .iter()
+            .any(|url| url.to_lowercase().contains(query))
+    }
+}
+
+/// Get the stamp of the given secret file, following symlinks.
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(path).ok()?;
+    Some(Stamp {
+        modified: meta
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos(),
+        len:      meta.len(),
+    })
+}
+
+/// Check whether the value of the field with the given key is sensitive.
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEYS
+        .iter()
+        .any(|sensitive| key.contains(sensitive))
+}
+
+/// Find URLs in a field value.
+///
+/// The whole value is a URL for known URL keys if it is a single word,
+/// otherwise words with a known URL scheme are URLs.
+fn find_urls<'a>(key: &str, value: &'a str) -> impl Iterator<Item = &'a str> {
+    let value = value.trim();
+    let is_url = URL_KEYS.iter().any(|url| key.eq_ignore_ascii_case(url)) && !value.is_empty();
+    value.split_whitespace().filter(move |word| {
+        (is_url && *word == value)
+            || URL_SCHEMES
+                .iter()
+                .any(|scheme| word.len() > scheme.len() && word.to_lowercase().starts_with(scheme))
+    })
+}
+
+/// Search index error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to read search index")]
+    Read(#[source] anyhow::Error),
+
+    #[error("failed to parse search index")]
+    Parse(#[source] serde_json::Error),
+
+    #[error("unsupported search index version {}, rebuild it", _0)]
+    Version(u32),
+
+    #[error("failed to write search index")]
+    Write(#[source] anyhow::Error),
+
+    #[error("failed to remove search index")]
+    Remove(#[source] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_skips_sensitive_values() {
+        let store = Store {
+            root: "/store".into(),
+        };
+        let secret = Secret::from(&store, "/store/web/example.gpg".into());
+        let plaintext = Plaintext::from(
+            "hunter2\nUser: alice\nURL: login.example.com/path\nPIN: 1234\nNotes: see https://example.org/help\n"
+                .as_bytes()
+                .to_vec(),
+        );
+
+        let mut index = Index::new();
+        index.insert(&store, &secret, Some(&plaintext));
+        let entry = index.get("web/example").unwrap();
+        assert_eq!(entry.path, PathBuf::from("web/example.gpg"));
+        assert!(!entry.unreadable);
+        assert_eq!(entry.field("user"), Some("alice"));
+        assert_eq!(entry.field("pin"), None);
+        assert!(entry.fields.iter().any(|field| field.key == "PIN"));
+        assert_eq!(entry.urls, vec![
+            "login.example.com/path",
+            "https://example.org/help"
+        ]);
+
+        assert_eq!(index.search(&store, "ALICE").len(), 1);
+        assert_eq!(index.search(&store, "example.org").len(), 1);
+        assert!(index.search(&store, "1234").is_empty());
+        assert!(index.search(&store, "hunter2").is_empty());
+
+        index.insert(&store, &secret, None);
+        assert!(index.get("web/example").unwrap().unreadable);
+        index.forget("web/example");
+        assert!(index.is_empty());
+    }
+}
@@ -117,23 +117,36 @@
     )
 )]
 #![feature(derive_default_enum)]
+#[cfg(all(feature = "agent", unix))]
+pub mod agent;
+pub mod body;
 pub mod crypto;
+pub mod find;
 pub(crate) mod git;
+pub mod health;
+pub mod history;
+pub mod index;
 #[cfg(feature = "otp")]
 pub mod otp;
+#[cfg(feature = "pwned")]
+pub mod pwned;
 pub mod store;
 pub mod sync;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub mod systemd_bin;
+pub mod template;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub mod tomb;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) mod tomb_bin;
+pub mod transaction;
+pub mod trash;
 pub mod types;
 pub mod util;
 mod vendor;
 
 // Re-exports
+pub use body::SecretBody;
 pub use crypto::{recipients::Recipients, Key};
 pub use store::{Secret, Store};
 pub use types::{Ciphertext, Plaintext};
@@ -190,6 +203,7 @@ pub static STORE_UMASK: Lazy<u32> = Lazy::new(|| {
 /// Default proto config.
 // TODO: remove when multiple protocols are supported.
 const CONFIG: Config = Config {
-    proto:   Proto::Gpg,
-    gpg_tty: false,
+    proto:        Proto::Gpg,
+    gpg_tty:      false,
+    age_identity: None,
 };
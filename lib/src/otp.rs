@@ -1,6 +1,11 @@
 //! One-time-password library
 
-use crate::{crypto::IsContext, store::Store, types::Plaintext, OTP_DEFUALT_FILE};
+use crate::{
+    crypto::{util, Config, IsContext},
+    store::Store,
+    types::Plaintext,
+    OTP_DEFUALT_FILE,
+};
 use anyhow::Result;
 use colored::Colorize;
 use data_encoding::{DecodeError, BASE32_NOPAD};
@@ -314,7 +319,7 @@ impl OtpFile {
     pub fn new(store: &Store) -> Result<Self> {
         let otp_file = store.root.join(OTP_DEFUALT_FILE);
         if otp_file.exists() {
-            let plaintext = crate::crypto::context(&crate::CONFIG)?
+            let plaintext = crate::crypto::context(&Config::from(util::detect_proto(&otp_file)))?
                 .decrypt_file(&otp_file)
                 .map_err(OtpError::Decrypt)?;
 
@@ -366,7 +371,7 @@ impl OtpFile {
         let otp_file = store.root.join(OTP_DEFUALT_FILE);
         let recipients = store.recipients()?;
 
-        crate::crypto::context(&crate::CONFIG)?
+        crate::crypto::context(&Config::from(store.proto()))?
             .encrypt_file(
                 &recipients,
                 Plaintext::from(serde_json::to_string_pretty(&self.0)?),
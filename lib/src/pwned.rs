@@ -0,0 +1,387 @@
+//! Check passwords against Have I Been Pwned password hashes.
+//!
+//! Passwords are hashed with SHA-1 and looked up in one of these sources:
+//!
+//! - a local hash list, ordered by hash, with a `HASH:COUNT` line for each
+//!   hash, which is binary searched
+//! - a local directory of range files, one for each 5 character hash prefix
+//!   named `PREFIX.txt` or `PREFIX`, with a `SUFFIX:COUNT` line for each hash
+//! - a k-anonymity range API, such as the Have I Been Pwned API, queried with
+//!   only the first 5 characters of each hash
+//!
+//! Hashes are compared case-insensitively, a missing count counts as once.
+
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, HashMap},
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Result;
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+
+/// curl binary, used to query range APIs.
+pub const CURL_BIN: &str = "curl";
+
+/// Have I Been Pwned range API URL.
+pub const API_URL: &str = "https://api.pwnedpasswords.com";
+
+/// Length of a hex encoded SHA-1 hash.
+const HASH_LEN: usize = 40;
+
+/// Length of the hash prefix of a range.
+const PREFIX_LEN: usize = 5;
+
+/// Source of pwned password hashes.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// Hash list file, ordered by hash.
+    HashList(PathBuf),
+
+    /// Directory of range files.
+    RangeDir(PathBuf),
+
+    /// Range API base URL, ranges are queried at `URL/range/PREFIX`.
+    Api(String),
+}
+
+/// Checker for pwned passwords.
+///
+/// Ranges read from range files or the API are cached, so each range is only
+/// fetched once.
+pub struct Checker {
+    /// Source of hashes.
+    source: Source,
+
+    /// Cached ranges, with counts by hash suffix, by hash prefix.
+    ranges: HashMap<String, HashMap<String, u64>>,
+}
+
+impl Source {
+    /// Local dataset at the given path.
+    ///
+    /// A directory is used as directory of range files, a file as hash list.
+    #[must_use]
+    pub fn dataset(path: PathBuf) -> Self {
+        if path.is_dir() {
+            Self::RangeDir(path)
+        } else {
+            Self::HashList(path)
+        }
+    }
+}
+
+impl Checker {
+    /// Construct a new checker using the given source.
+    #[must_use]
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            ranges: HashMap::new(),
+        }
+    }
+
+    /// Check how many times the given password was seen in breaches.
+    ///
+    /// Returns 0 if the password is not pwned.
+    pub fn check(&mut self, password: &str) -> Result<u64> {
+        self.check_hash(&hash(password))
+    }
+
+    /// Check how many times the password with the given SHA-1 hash was seen in
+    /// breaches.
+    ///
+    /// The hash must be hex encoded. Returns 0 if the password is not pwned.
+    pub fn check_hash(&mut self, hash: &str) -> Result<u64> {
+        let hash = hash.to_ascii_uppercase();
+        if hash.len() != HASH_LEN || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Err::InvalidHash.into());
+        }
+        let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+
+        let range = match (&self.source, self.ranges.entry(prefix.into())) {
+            (Source::HashList(path), _) => return search_hash_list(path, &hash),
+            (_, Entry::Occupied(range)) => range.into_mut(),
+            (Source::RangeDir(dir), Entry::Vacant(entry)) =>
+                entry.insert(read_range_file(dir, prefix)?),
+            (Source::Api(url), Entry::Vacant(entry)) => entry.insert(query_range_api(url, prefix)?),
+        };
+
+        Ok(range.get(suffix).copied().unwrap_or(0))
+    }
+}
+
+/// Get the uppercase hex encoded SHA-1 hash of the given password.
+#[must_use]
+pub fn hash(password: &str) -> String {
+    Sha1::digest(password.as_bytes()).iter().fold(
+        String::with_capacity(HASH_LEN),
+        |mut hash, byte| {
+            let _ = write!(hash, "{byte:02X}");
+            hash
+        },
+    )
+}
+
+/// Binary search the hash list file at the given path for the given uppercase
+/// hash, and return its count.
+fn search_hash_list(path: &Path, hash: &str) -> Result<u64> {
+    let mut file = BufReader::new(File::open(path).map_err(Err::Read)?);
+    let mut line = vec![];
+
+    // Search offsets at which a line may start that is not compared yet
+    let (mut low, mut high) = (0, file.get_ref().metadata().map_err(Err::Read)?.len());
+    while low < high {
+        // Find the first line starting at or after the middle
+        let middle = low + (high - low) / 2;
+        let mut start = middle;
+        if middle > 0 {
+            file.seek(SeekFrom::Start(middle - 1)).map_err(Err::Read)?;
+            line.clear();
+            start += read_line(&mut file, &mut line)? - 1;
+        } else {
+            file.seek(SeekFrom::Start(0)).map_err(Err::Read)?;
+        }
+        if start >= high {
+            high = middle;
+            continue;
+        }
+
+        line.clear();
+        let end = start + read_line(&mut file, &mut line)?;
+        let entry = String::from_utf8_lossy(&line);
+
+        // Invalid lines, such as empty trailing lines, are sorted last
+        let Some((line_hash, count)) = parse_line(&entry) else {
+            high = middle;
+            continue;
+        };
+
+        match line_hash.to_ascii_uppercase().as_str().cmp(hash) {
+            Ordering::Equal => return Ok(count),
+            Ordering::Less => low = end,
+            Ordering::Greater => high = middle,
+        }
+    }
+
+    Ok(0)
+}
+
+/// Read a line including line terminator into the given buffer, and return the
+/// number of bytes read.
+fn read_line(file: &mut BufReader<File>, line: &mut Vec<u8>) -> Result<u64> {
+    let read = file.read_until(b'\n', line).map_err(Err::Read)?;
+    Ok(read as u64)
+}
+
+/// Read the range file for the given prefix from the given directory.
+fn read_range_file(dir: &Path, prefix: &str) -> Result<HashMap<String, u64>> {
+    let path = [format!("{prefix}.txt"), prefix.into()]
+        .iter()
+        .flat_map(|name| [name.clone(), name.to_ascii_lowercase()])
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| Err::MissingRange(prefix.into()))?;
+
+    let range = std::fs::read_to_string(path).map_err(Err::Read)?;
+    Ok(parse_range(&range))
+}
+
+/// Query the range for the given prefix from the range API at the given URL.
+///
+/// Asks for padding, so the response size does not reveal the range.
+fn query_range_api(url: &str, prefix: &str) -> Result<HashMap<String, u64>> {
+    let curl = which::which(CURL_BIN).map_err(Err::Unavailable)?;
+    let url = format!("{}/range/{prefix}", url.trim_end_matches('/'));
+    let output = Command::new(curl)
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--header", "Add-Padding: true"])
+        .args(["--user-agent", concat!("prs/", env!("CARGO_PKG_VERSION"))])
+        .arg(&url)
+        .output()
+        .map_err(|err| Err::Api(url.clone(), err.into()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(Err::Api(url, anyhow::anyhow!(stderr)).into());
+    }
+
+    Ok(parse_range(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse a range with `SUFFIX:COUNT` lines, skipping invalid lines and padding
+/// with a zero count.
+fn parse_range(range: &str) -> HashMap<String, u64> {
+    range
+        .lines()
+        .filter_map(parse_line)
+        .filter(|(suffix, count)| suffix.len() == HASH_LEN - PREFIX_LEN && *count > 0)
+        .map(|(suffix, count)| (suffix.to_ascii_uppercase(), count))
+        .collect()
+}
+
+/// Parse a `HASH:COUNT` or `HASH` line.
+fn parse_line(line: &str) -> Option<(&str, u64)> {
+    let line = line.trim();
+    let (hash, count) = match line.split_once(':') {
+        Some((hash, count)) => (hash, count.trim().parse().ok()?),
+        None => (line, 1),
+    };
+    (!hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some((hash, count))
+}
+
+/// A pwned password check error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("invalid SHA-1 password hash")]
+    InvalidHash,
+
+    #[error("failed to read pwned password dataset")]
+    Read(#[source] io::Error),
+
+    #[error("pwned password dataset has no range file for hash prefix '{0}'")]
+    MissingRange(String),
+
+    #[error("failed to find curl binary to query pwned password API")]
+    Unavailable(#[source] which::Error),
+
+    #[error("failed to query pwned password API at '{0}'")]
+    Api(String, #[source] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// Serve a single HTTP request on a local port with the given status and
+    /// body.
+    ///
+    /// Returns the base URL, and a handle to join for the received request.
+    fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len(),
+            )
+            .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn hash_password() {
+        assert_eq!(hash("password"), "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8");
+        assert_eq!(hash(""), "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709");
+    }
+
+    #[test]
+    fn search_hash_list_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let mut hashes: Vec<String> = (0..500).map(|i| hash(&format!("pw{i}"))).collect();
+        hashes.sort();
+        let list: String = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| format!("{hash}:{}\r\n", i + 1))
+            .collect();
+        fs::write(dir.join("list.txt"), list).unwrap();
+
+        let mut checker = Checker::new(Source::dataset(dir.join("list.txt")));
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_eq!(checker.check_hash(hash).unwrap(), i as u64 + 1);
+            assert_eq!(
+                checker.check_hash(&hash.to_lowercase()).unwrap(),
+                i as u64 + 1
+            );
+        }
+        assert_eq!(checker.check("not pwned").unwrap(), 0);
+        assert_eq!(checker.check_hash(&"0".repeat(HASH_LEN)).unwrap(), 0);
+        assert_eq!(checker.check_hash(&"F".repeat(HASH_LEN)).unwrap(), 0);
+        assert!(checker.check_hash("abc").is_err());
+
+        fs::write(dir.join("empty.txt"), "").unwrap();
+        let mut checker = Checker::new(Source::dataset(dir.join("empty.txt")));
+        assert_eq!(checker.check("password").unwrap(), 0);
+    }
+
+    #[test]
+    fn read_range_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::write(
+            dir.join("5BAA6.txt"),
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD8:42\n0000000000000000000000000000000000A:0\n",
+        )
+        .unwrap();
+
+        let mut checker = Checker::new(Source::dataset(dir.into()));
+        assert_eq!(checker.check("password").unwrap(), 42);
+        assert_eq!(
+            checker
+                .check_hash("5BAA60000000000000000000000000000000000A")
+                .unwrap(),
+            0
+        );
+        assert!(checker.check("not pwned").is_err());
+    }
+
+    #[test]
+    fn query_range_from_api() {
+        if which::which(CURL_BIN).is_err() {
+            return;
+        }
+
+        let (url, server) = serve_once(
+            "200 OK",
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD8:42\r\n0000000000000000000000000000000000A:0\r\n",
+        );
+        let mut checker = Checker::new(Source::Api(format!("{url}/")));
+        assert_eq!(checker.check("password").unwrap(), 42);
+        assert_eq!(
+            checker
+                .check_hash("5BAA60000000000000000000000000000000000A")
+                .unwrap(),
+            0
+        );
+
+        // Only the hash prefix is sent, the range is cached
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /range/5BAA6 HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nAdd-Padding: true\r\n"));
+        assert!(!request.contains("1E4C9B93F3F0682250B6CF8331B7EE68FD8"));
+
+        let (url, server) = serve_once("404 Not Found", "");
+        let err = query_range_api(&url, "5BAA6").unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Err::Api(..))));
+        server.join().unwrap();
+    }
+}
@@ -16,8 +16,13 @@ use walkdir::{DirEntry, WalkDir};
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 use crate::tomb::Tomb;
 use crate::{
-    crypto::{self, prelude::*},
+    crypto::{self, prelude::*, Config, Proto},
+    find,
+    history::History,
+    index::Index,
     sync::Sync,
+    transaction::Transaction,
+    trash::Trash,
     vendor::shellexpand,
     Recipients,
 };
@@ -25,6 +30,12 @@ use crate::{
 /// Password store secret file suffix.
 pub const SECRET_SUFFIX: &str = ".gpg";
 
+/// Password store secret file suffix for secrets encrypted with age.
+pub const SECRET_SUFFIX_AGE: &str = ".age";
+
+/// All recognized password store secret file suffixes.
+const SECRET_SUFFIXES: [&str; 2] = [SECRET_SUFFIX, SECRET_SUFFIX_AGE];
+
 /// Prevent multiple compilations of the same `Regex`.
 /// Used for checking for 'sneaky' paths
 static SNEAKY_RE: OnceCell<Regex> = OnceCell::new();
@@ -77,6 +88,41 @@ impl Store {
         Recipients::load(self)
     }
 
+    /// Get the recipient keys for the given path in this store.
+    ///
+    /// Like pass, this uses the recipients of the nearest directory that has
+    /// its own recipients, walking up to the store root.
+    pub fn recipients_for<P: AsRef<Path>>(&self, path: P) -> Result<Recipients> {
+        Recipients::load_for(self, path.as_ref())
+    }
+
+    /// Get the directory holding the recipients for the given path in this
+    /// store.
+    #[must_use]
+    pub fn recipients_dir<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        crypto::store::store_recipients_dir(self, path.as_ref())
+    }
+
+    /// Get the crypto protocol used by this store.
+    ///
+    /// This is the protocol new secrets in the store root are encrypted with.
+    /// It is age if the store has an age recipients file, and GPG otherwise.
+    /// Existing secrets may use a different protocol, see [`Secret::proto`].
+    #[must_use]
+    pub fn proto(&self) -> Proto {
+        crypto::store::store_proto(self)
+    }
+
+    /// Get the crypto protocol used for the given path in this store.
+    ///
+    /// For an existing secret this is the protocol it is encrypted with. For
+    /// other paths this is the protocol new secrets are encrypted with in the
+    /// nearest directory that has its own recipients.
+    #[must_use]
+    pub fn proto_for<P: AsRef<Path>>(&self, path: P) -> Proto {
+        crypto::store::store_proto_for(self, path.as_ref())
+    }
+
     /// Get a sync helper for this store.
     #[must_use]
     pub const fn sync(&self) -> Sync {
@@ -90,6 +136,24 @@ impl Store {
         Tomb::new(self, quiet, verbose, force)
     }
 
+    /// Get a history helper for this store.
+    #[must_use]
+    pub const fn history(&self) -> History<'_> {
+        History::new(self)
+    }
+
+    /// Get a trash helper for this store.
+    #[must_use]
+    pub const fn trash(&self) -> Trash<'_> {
+        Trash::new(self)
+    }
+
+    /// Begin a transaction of file changes in this store.
+    #[must_use]
+    pub const fn transaction(&self) -> Transaction<'_> {
+        Transaction::new(self)
+    }
+
     /// Create secret iterator for this store.
     #[must_use]
     pub fn secret_iter(&self) -> SecretIter {
@@ -115,10 +179,12 @@ impl Store {
         let path = self.root.as_path().join(path);
         let path = path.to_str()?;
 
-        // Try path with secret file suffix
-        let with_suffix = PathBuf::from(format!("{}{}", path, SECRET_SUFFIX));
-        if with_suffix.is_file() {
-            return Some(Secret::from(self, with_suffix));
+        // Try path with secret file suffixes
+        for suffix in SECRET_SUFFIXES {
+            let with_suffix = PathBuf::from(format!("{}{}", path, suffix));
+            if with_suffix.is_file() {
+                return Some(Secret::from(self, with_suffix));
+            }
         }
 
         // Try path without secret file suffix
@@ -134,18 +200,35 @@ impl Store {
     ///
     /// If secret is found at exact query path, `FindSecret::Found` is returned.
     /// Otherwise any number of closely matching secrets is returned as
-    /// `FindSecret::Many`.
+    /// `FindSecret::Many`, best matches first. See [`find::Query::parse`] for
+    /// the query format.
     #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
     pub fn find(&self, query: Option<String>) -> FindSecret {
+        self.find_indexed(None, query.as_deref())
+    }
+
+    /// Try to find matching secrets for given query, using the given search
+    /// index for URL queries.
+    ///
+    /// See [`Store::find`].
+    #[must_use]
+    pub fn find_indexed(&self, index: Option<&Index>, query: Option<&str>) -> FindSecret {
         // Try to find exact secret match
-        if let Some(query) = &query {
+        if let Some(query) = query {
             if let Some(secret) = self.find_at(query) {
                 return FindSecret::Exact(secret);
             }
         }
 
         // Find all closely matching
-        FindSecret::Many(self.secrets(query))
+        let query = find::Query::parse(query);
+        FindSecret::Many(
+            find::find(self, index, &query)
+                .into_iter()
+                .map(|found| found.secret)
+                .collect(),
+        )
     }
 
     /// Check for 'sneaky' paths found within the password store
@@ -213,11 +296,12 @@ impl Store {
             path.push(name_hint.ok_or_else(|| Err::TargetDirWithoutNamehint(path.clone()))?);
         }
 
-        // Add secret extension if non existent
-        let ext: OsString = SECRET_SUFFIX.trim_start_matches('.').into();
+        // Add secret extension for protocol at path if non existent
+        let suffix = self.proto_for(&path).secret_suffix();
+        let ext: OsString = suffix.trim_start_matches('.').into();
         if path.extension() != Some(&ext) {
             let mut tmp = path.as_os_str().to_owned();
-            tmp.push(SECRET_SUFFIX);
+            tmp.push(suffix);
             path = PathBuf::from(tmp);
         }
 
@@ -265,11 +349,20 @@ impl Secret {
         let name: String = relative_path(root, &path)
             .ok()
             .and_then(Path::to_str)
-            .map_or("?", |f| f.trim_end_matches(SECRET_SUFFIX))
+            .map_or("?", strip_secret_suffix)
             .to_string();
         Self { name, path }
     }
 
+    /// Get the crypto protocol this secret is encrypted with.
+    ///
+    /// This is detected from the file suffix, or from the file header if the
+    /// suffix is unknown.
+    #[must_use]
+    pub fn proto(&self) -> Proto {
+        crypto::util::detect_proto(&self.path)
+    }
+
     /// Get relative path to this secret, root must be given.
     pub fn relative_path<'a>(
         &'a self,
@@ -295,6 +388,14 @@ impl Secret {
     }
 }
 
+/// Strip any known secret file suffix from the given file name.
+pub(crate) fn strip_secret_suffix(name: &str) -> &str {
+    SECRET_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+}
+
 /// Get relative path in given root.
 pub fn relative_path<'a>(
     root: &'a Path,
@@ -374,7 +475,7 @@ impl Iterator for SecretIter {
 }
 
 /// Check if given WalkDir DirEntry is hidden sub-directory.
-fn is_hidden_subdir(entry: &DirEntry) -> bool {
+pub(crate) fn is_hidden_subdir(entry: &DirEntry) -> bool {
     entry.depth() > 0
         && entry
             .file_name()
@@ -388,7 +489,7 @@ fn is_secret_file(entry: &DirEntry) -> bool {
         && entry
             .file_name()
             .to_str()
-            .map_or(false, |s| s.ends_with(SECRET_SUFFIX))
+            .map_or(false, |s| SECRET_SUFFIXES.iter().any(|suffix| s.ends_with(suffix)))
 }
 
 /// Check if given WalkDir DirEntry passes the configuration.
@@ -424,12 +525,11 @@ fn filter_by_config(entry: &DirEntry, config: &SecretIterConfig) -> bool {
 /// Returns true if there is no secret.
 #[must_use]
 pub fn can_decrypt(store: &Store) -> bool {
-    // Try all proto's here once we support more
     store
         .secret_iter()
         .next()
         .map_or(false, |secret| {
-            crypto::context(&crate::CONFIG)
+            crypto::context(&Config::from(secret.proto()))
                 .map(|mut context| context.can_decrypt_file(&secret.path).unwrap_or(true))
                 .unwrap_or(false)
         })
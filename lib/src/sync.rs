@@ -3,8 +3,10 @@
 use std::{path::Path, time::Duration};
 
 use anyhow::Result;
+use thiserror::Error;
 
 use crate::{
+    crypto::{self, prelude::*, util as crypto_util, Proto},
     git::{self, RepositoryState},
     Store,
 };
@@ -18,6 +20,9 @@ pub const STORE_GIT_DIR: &str = ".git/";
 /// be optimized away if not needed.
 pub const GIT_PULL_OUTDATED: Duration = Duration::from_secs(30);
 
+/// Git config key to enable verified sync with.
+pub const GIT_CONFIG_VERIFY_SYNC: &str = "prs.verifySync";
+
 /// Sync helper for given store.
 pub struct Sync<'a> {
     /// The store.
@@ -122,10 +127,7 @@ impl<'a> Sync<'a> {
             return Ok(());
         }
 
-        // Commit changes if dirty
-        if is_dirty(self.path())? {
-            self.commit_all(msg, false)?;
-        }
+        self.commit(msg)?;
 
         // Do not push  if no remote or not out of sync
         if !self.has_remote()? || !safe_need_to_push(self.path()) {
@@ -162,6 +164,19 @@ impl<'a> Sync<'a> {
         Ok(())
     }
 
+    /// Commit all changes in the store, if sync is initialized and it is dirty.
+    ///
+    /// Changes are unstaged again if committing fails. This does not push.
+    pub(crate) fn commit<M: AsRef<str>>(&self, msg: M) -> Result<()> {
+        if !self.is_init() || !is_dirty(self.path())? {
+            return Ok(());
+        }
+
+        self.commit_all(msg, false).inspect_err(|_| {
+            let _ = git::git_reset(self.path());
+        })
+    }
+
     /// Initialize sync.
     pub fn init(&self) -> Result<()> {
         git::git_init(self.path())?;
@@ -207,6 +222,23 @@ impl<'a> Sync<'a> {
         self.add_remote_url(remote, url)
     }
 
+    /// Check whether verified sync is enabled for this store.
+    ///
+    /// In verified sync mode, all commits pulled from the remote must be signed
+    /// by a GPG recipient of the store, and our own commits are signed with our
+    /// recipient key. Enable it with the `prs.verifySync` git config option.
+    pub fn is_verified(&self) -> Result<bool> {
+        if !self.is_init() {
+            return Ok(false);
+        }
+        Ok(git::git_config_bool(self.path(), GIT_CONFIG_VERIFY_SYNC)?.unwrap_or(false))
+    }
+
+    /// Set a git config value in the local repository config of this store.
+    pub fn set_config(&self, key: &str, value: &str) -> Result<()> {
+        git::git_config_set(self.path(), key, value)
+    }
+
     /// Check whether this store has a remote configured.
     pub fn has_remote(&self) -> Result<bool> {
         if !self.is_init() {
@@ -216,8 +248,45 @@ impl<'a> Sync<'a> {
     }
 
     /// Pull changes from remote.
+    ///
+    /// In verified sync mode, see [`Sync::is_verified`], new commits are only
+    /// merged if all of them are signed by a store recipient.
     fn pull(&self) -> Result<()> {
-        git::git_pull(self.path())
+        if !self.is_verified()? {
+            return git::git_pull(self.path());
+        }
+
+        // Verified sync is meaningless without recipients to verify against
+        let recipients = self.recipient_fingerprints()?;
+        if recipients.is_empty() {
+            return Err(Err::NoGpgRecipients.into());
+        }
+
+        // Fetch and resolve upstream commit, nothing to pull without upstream
+        let repo = self.path();
+        git::git_fetch(repo, None)?;
+        let upstream = match git::git_branch_upstream(repo, "HEAD")? {
+            Some(upstream) => git::git_ref_hash(repo, upstream)?,
+            None => return Ok(()),
+        };
+
+        // Verify new commits against recipients we currently trust
+        let commits = git::git_commit_signers(repo, &format!("HEAD..{upstream}"))?;
+        if commits.is_empty() {
+            return Ok(());
+        }
+        let unverified = unverified_commits(commits, &recipients);
+        if !unverified.is_empty() {
+            return Err(Err::UnverifiedCommits(unverified).into());
+        }
+
+        // Merge the exact commit we verified, only sign if we create a merge commit
+        let sign_key = if git::git_is_ancestor(repo, "HEAD", &upstream)? {
+            None
+        } else {
+            Some(self.signing_key()?)
+        };
+        git::git_merge(repo, &upstream, sign_key.as_deref())
     }
 
     /// Push changes to remote.
@@ -226,10 +295,47 @@ impl<'a> Sync<'a> {
     }
 
     /// Add all changes and commit them.
+    ///
+    /// In verified sync mode, see [`Sync::is_verified`], the commit is signed
+    /// with our recipient key.
     fn commit_all<M: AsRef<str>>(&self, msg: M, commit_empty: bool) -> Result<()> {
         let path = self.path();
+        let sign_key = if self.is_verified()? {
+            Some(self.signing_key()?)
+        } else {
+            None
+        };
         git::git_add_all(path)?;
-        git::git_commit(path, msg.as_ref(), commit_empty)
+        git::git_commit(path, msg.as_ref(), commit_empty, sign_key.as_deref())
+    }
+
+    /// Get the fingerprints of all GPG recipients in the store.
+    fn recipient_fingerprints(&self) -> Result<Vec<String>> {
+        Ok(crypto::store::store_load_all_keys(self.store)?
+            .into_iter()
+            .filter(|key| key.proto() == Proto::Gpg)
+            .map(|key| key.fingerprint(false))
+            .collect())
+    }
+
+    /// Get the fingerprint of our key to sign commits with.
+    ///
+    /// This is the first GPG recipient of the store we have the secret key for.
+    fn signing_key(&self) -> Result<String> {
+        let recipients = self.recipient_fingerprints()?;
+        if recipients.is_empty() {
+            return Err(Err::NoGpgRecipients.into());
+        }
+        crypto::context(&crate::CONFIG)?
+            .keys_private()?
+            .into_iter()
+            .map(|key| key.fingerprint(false))
+            .find(|fp| {
+                recipients
+                    .iter()
+                    .any(|recipient| crypto_util::fingerprints_equal(fp, recipient))
+            })
+            .ok_or_else(|| Err::NoSigningKey.into())
     }
 }
 
@@ -261,6 +367,26 @@ impl Readyness {
     }
 }
 
+/// Get the commits not signed by any of the given recipient fingerprints.
+///
+/// Takes commits with their signer as listed by [`git::git_commit_signers`].
+fn unverified_commits(
+    commits: Vec<(String, Option<String>)>,
+    recipients: &[String],
+) -> Vec<String> {
+    commits
+        .into_iter()
+        .filter(|(_, signer)| {
+            !signer.as_ref().is_some_and(|signer| {
+                recipients
+                    .iter()
+                    .any(|fp| crypto_util::fingerprints_equal(fp, signer))
+            })
+        })
+        .map(|(commit, _)| commit)
+        .collect()
+}
+
 /// Check if repository is dirty.
 ///
 /// Repository is dirty if it has any uncommitted changed.
@@ -304,3 +430,62 @@ fn need_to_push(repo: &Path) -> Result<bool> {
     // Compare local and remote branch hashes
     Ok(git::git_ref_hash(repo, branch)? != git::git_ref_hash(repo, upstream)?)
 }
+
+/// Sync error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error(
+        "failed to pull changes, commits are not signed by a store recipient: {}",
+        _0.join(", ")
+    )]
+    UnverifiedCommits(Vec<String>),
+
+    #[error("failed to verify sync, password store has no GPG recipients")]
+    NoGpgRecipients,
+
+    #[error("failed to sign commit, no secret key for any store recipient")]
+    NoSigningKey,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a commit list from commit and signer pairs.
+    fn commits(commits: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+        commits
+            .iter()
+            .map(|(commit, signer)| ((*commit).into(), signer.map(Into::into)))
+            .collect()
+    }
+
+    #[test]
+    fn unverified_commits_filters_recipient_signers() {
+        let recipients = vec!["AAAA1111".into(), "BBBB2222".into()];
+        let list = commits(&[
+            ("c1", Some("AAAA1111")),
+            ("c2", Some("bbbb2222")),
+            ("c3", Some("CCCC3333")),
+            ("c4", None),
+            ("c5", Some(" aaaa1111 ")),
+        ]);
+        assert_eq!(unverified_commits(list, &recipients), vec!["c3", "c4"]);
+    }
+
+    #[test]
+    fn unverified_commits_without_recipients() {
+        let list = commits(&[("c1", Some("AAAA1111")), ("c2", Some("")), ("c3", None)]);
+        assert_eq!(unverified_commits(list, &[]), vec!["c1", "c2", "c3"]);
+
+        // An empty signer never matches, not even an empty recipient
+        let list = commits(&[("c1", Some(""))]);
+        assert_eq!(unverified_commits(list, &["".into()]), vec!["c1"]);
+    }
+
+    #[test]
+    fn unverified_commits_all_verified() {
+        let list = commits(&[("c1", Some("AAAA1111"))]);
+        assert!(unverified_commits(list, &["AAAA1111".into()]).is_empty());
+        assert!(unverified_commits(vec![], &[]).is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+//! Secret templates.
+//!
+//! Templates describe the fields of common secret types, such as logins or
+//! database credentials. They are stored as YAML files in the `.templates`
+//! directory in the store root, and are not encrypted:
+//!
+//! ```yaml
+//! description: Database credentials
+//! password:
+//!   generate: password
+//!   length: 32
+//! fields:
+//!   - key: user
+//!     required: true
+//!   - key: host
+//!     default: localhost
+//!   - key: api.token
+//!     generate: passphrase
+//! ```
+//!
+//! A secret declares its template in the `template` property.
+
+use std::{fs, io, path::PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{body::SecretBody, Plaintext, Store};
+
+/// Directory in the store root holding templates.
+pub const TEMPLATES_DIR: &str = ".templates";
+
+/// Property a secret declares its template in.
+pub const TEMPLATE_PROPERTY: &str = "template";
+
+/// File extensions template files may have.
+const TEMPLATE_EXTENSIONS: [&str; 2] = ["yml", "yaml"];
+
+/// Secret template.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    /// Template name, the file name without extension.
+    #[serde(skip)]
+    pub name: String,
+
+    /// Template description.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// The password line.
+    #[serde(default)]
+    pub password: TemplatePassword,
+
+    /// Fields in the secret body, in order.
+    #[serde(default)]
+    pub fields: Vec<TemplateField>,
+}
+
+/// Password line of a secret template.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplatePassword {
+    /// Whether the password must not be empty.
+    #[serde(default)]
+    pub required: bool,
+
+    /// How to generate the password.
+    #[serde(default)]
+    pub generate: Option<Generator>,
+
+    /// Length of the generated password, in characters or words.
+    #[serde(default)]
+    pub length: Option<u16>,
+}
+
+/// Field of a secret template.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateField {
+    /// Property key, nested keys are separated by `.`.
+    pub key: String,
+
+    /// Whether the field must be set and not be empty.
+    #[serde(default)]
+    pub required: bool,
+
+    /// Default value.
+    #[serde(default)]
+    pub default: Option<String>,
+
+    /// How to generate the value.
+    #[serde(default)]
+    pub generate: Option<Generator>,
+
+    /// Length of the generated value, in characters or words.
+    #[serde(default)]
+    pub length: Option<u16>,
+}
+
+/// Generator for template values.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Generator {
+    /// Random password of characters.
+    Password,
+
+    /// Passphrase of random words.
+    Passphrase,
+}
+
+impl Template {
+    /// Load the template with the given name from the store.
+    pub fn load(store: &Store, name: &str) -> Result<Self> {
+        let path = template_path(store, name).ok_or_else(|| Err::Unknown(name.into()))?;
+        let data = fs::read_to_string(&path).map_err(Err::Read)?;
+        Self::parse(name, &data)
+    }
+
+    /// Parse a template with the given name from YAML.
+    pub fn parse(name: &str, data: &str) -> Result<Self> {
+        let mut template: Self =
+            serde_yaml::from_str(data).map_err(|err| Err::Parse(name.into(), err))?;
+        template.name = name.into();
+        Ok(template)
+    }
+
+    /// Add the template fields to the given secret body.
+    ///
+    /// Declares the template in the secret, and adds fields that are not in
+    /// the secret yet with their default value, or empty. Nothing is
+    /// generated.
+    pub fn apply(&self, body: &mut SecretBody) -> Result<()> {
+        if body.property(TEMPLATE_PROPERTY).is_none() {
+            body.set_property(TEMPLATE_PROPERTY, &self.name)?;
+        }
+        for field in &self.fields {
+            if body.property(&field.key).is_none() {
+                body.set_property(&field.key, field.default.as_deref().unwrap_or_default())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate values for empty fields with a generator.
+    ///
+    /// Values are generated with `generate`, given the generator and length.
+    /// The password is not generated, see [`Template::password`].
+    pub fn generate_fields<F>(&self, body: &mut SecretBody, mut generate: F) -> Result<()>
+    where
+        F: FnMut(Generator, Option<u16>) -> Plaintext,
+    {
+        for field in &self.fields {
+            let Some(generator) = field.generate else {
+                continue;
+            };
+            if body
+                .property(&field.key)
+                .is_none_or(|value| value.is_empty())
+            {
+                let value = generate(generator, field.length);
+                body.set_property(&field.key, value.unsecure_to_str()?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find required fields that are missing or empty in the given secret
+    /// body.
+    ///
+    /// A missing password is reported as `password`.
+    #[must_use]
+    pub fn missing(&self, body: &SecretBody) -> Vec<&str> {
+        let mut missing = vec![];
+        if self.password.required && body.password().trim().is_empty() {
+            missing.push("password");
+        }
+        missing.extend(
+            self.fields
+                .iter()
+                .filter(|field| field.required)
+                .filter(|field| {
+                    body.property(&field.key)
+                        .is_none_or(|value| value.is_empty())
+                })
+                .map(|field| field.key.as_str()),
+        );
+        missing
+    }
+}
+
+/// Get the template directory of the given store.
+#[must_use]
+pub fn templates_dir(store: &Store) -> PathBuf {
+    store.root.join(TEMPLATES_DIR)
+}
+
+/// List the names of all templates in the given store, sorted.
+pub fn list(store: &Store) -> Result<Vec<String>> {
+    let dir = templates_dir(store);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut names = vec![];
+    for entry in fs::read_dir(dir).map_err(Err::Read)? {
+        let path = entry.map_err(Err::Read)?.path();
+        let is_template = path.is_file()
+            && path
+                .extension()
+                .is_none_or(|extension| TEMPLATE_EXTENSIONS.iter().any(|e| extension == *e));
+        if let Some(name) = path.file_stem().filter(|_| is_template) {
+            let name = name.to_string_lossy().into_owned();
+            if !name.starts_with('.') {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Find the file of the template with the given name.
+fn template_path(store: &Store, name: &str) -> Option<PathBuf> {
+    // Template names must not escape the template directory
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return None;
+    }
+    let dir = templates_dir(store);
+    std::iter::once(dir.join(name))
+        .chain(
+            TEMPLATE_EXTENSIONS
+                .iter()
+                .map(|extension| dir.join(format!("{name}.{extension}"))),
+        )
+        .find(|path| path.is_file())
+}
+
+/// A template error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("template '{}' does not exist", _0)]
+    Unknown(String),
+
+    #[error("failed to read template")]
+    Read(#[source] io::Error),
+
+    #[error("failed to parse template '{}'", _0)]
+    Parse(String, #[source] serde_yaml::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "\
+description: Database credentials
+password:
+  required: true
+  generate: password
+fields:
+  - key: user
+    required: true
+  - key: host
+    default: localhost
+  - key: api.token
+    generate: passphrase
+";
+
+    fn template() -> Template {
+        Template::parse("database", TEMPLATE).unwrap()
+    }
+
+    fn body(text: &str) -> SecretBody {
+        SecretBody::parse(&Plaintext::from(text)).unwrap()
+    }
+
+    fn render(body: &SecretBody) -> String {
+        body.to_plaintext().unsecure_to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn template_parse() {
+        let template = template();
+        assert_eq!(template.name, "database");
+        assert_eq!(template.password.generate, Some(Generator::Password));
+        assert_eq!(template.fields.len(), 3);
+        assert_eq!(template.fields[1].default.as_deref(), Some("localhost"));
+
+        assert!(Template::parse("empty", "{}").unwrap().fields.is_empty());
+        assert!(Template::parse("unknown", "color: red").is_err());
+        assert!(Template::parse("generator", "password:\n  generate: pin").is_err());
+    }
+
+    #[test]
+    fn template_apply() {
+        let mut secret = body("secret\nhost: db.example.com\n");
+        template().apply(&mut secret).unwrap();
+        assert_eq!(
+            secret
+                .property(TEMPLATE_PROPERTY)
+                .unwrap()
+                .unsecure_to_str()
+                .unwrap(),
+            "database",
+        );
+        assert_eq!(
+            secret.property("host").unwrap().unsecure_to_str().unwrap(),
+            "db.example.com"
+        );
+        assert!(secret.property("user").unwrap().is_empty());
+        assert!(secret.property("api.token").unwrap().is_empty());
+
+        // Applying twice must not change anything
+        let rendered = render(&secret);
+        template().apply(&mut secret).unwrap();
+        assert_eq!(render(&secret), rendered);
+    }
+
+    #[test]
+    fn template_generate_fields() {
+        let mut secret = body("secret\n");
+        let template = template();
+        template.apply(&mut secret).unwrap();
+        template
+            .generate_fields(&mut secret, |generator, _| {
+                assert_eq!(generator, Generator::Passphrase);
+                Plaintext::from("correct horse")
+            })
+            .unwrap();
+        assert_eq!(secret.password(), "secret");
+        assert_eq!(
+            secret
+                .property("api.token")
+                .unwrap()
+                .unsecure_to_str()
+                .unwrap(),
+            "correct horse",
+        );
+
+        // Existing values are kept
+        template
+            .generate_fields(&mut secret, |_, _| Plaintext::from("battery staple"))
+            .unwrap();
+        assert_eq!(
+            secret
+                .property("api.token")
+                .unwrap()
+                .unsecure_to_str()
+                .unwrap(),
+            "correct horse",
+        );
+    }
+
+    #[test]
+    fn template_missing() {
+        let template = template();
+        assert_eq!(template.missing(&body("")), ["password", "user"]);
+        assert_eq!(template.missing(&body("secret\nuser:\n")), ["user"]);
+        assert!(template.missing(&body("secret\nuser: alice\n")).is_empty());
+    }
+}
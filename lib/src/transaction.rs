@@ -0,0 +1,457 @@
+//! Transactional changes to multiple files in a password store.
+//!
+//! Some operations change many files at once, such as moving a secret along
+//! with its aliases or re-encrypting secrets for new recipients. A transaction
+//! applies each change atomically right away, so later steps observe earlier
+//! ones, and keeps a backup of every file it replaces or removes. If any step
+//! fails, all changes are rolled back, leaving the store as it was.
+//!
+//! Backups are kept in `.git/prs-transaction` if the store uses git, and in
+//! `.prs-transaction` in the store root otherwise. They are removed when the
+//! transaction is committed or rolled back.
+
+use std::{
+    fs, io, mem,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{
+    crypto::{prelude::*, Context},
+    sync::Sync,
+    util::fs::{sync_parent, write_atomic},
+    Ciphertext, Plaintext, Recipients, Store,
+};
+
+/// Transaction backup directory in the store git directory.
+const GIT_BACKUP_DIR: &str = ".git/prs-transaction";
+
+/// Transaction backup directory in the store root, if the store has no git
+/// directory.
+pub const BACKUP_DIR: &str = ".prs-transaction";
+
+/// Counter to make backup directory names unique within this process.
+static BACKUP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A transaction of file changes in a store.
+///
+/// Changes are applied immediately. Use [`Transaction::commit`] to keep them,
+/// or [`Transaction::rollback`] to undo them. A transaction that is dropped
+/// without being committed is rolled back.
+pub struct Transaction<'a> {
+    /// The store.
+    store: &'a Store,
+
+    /// Directory holding backups of replaced and removed files, created when
+    /// first used.
+    backup_dir: Option<PathBuf>,
+
+    /// Number of backups made.
+    backups: usize,
+
+    /// Steps to undo applied changes, in order they were applied.
+    journal: Vec<Undo>,
+
+    /// Whether the transaction is committed or rolled back.
+    done: bool,
+}
+
+/// A step to undo an applied change.
+#[derive(Debug)]
+enum Undo {
+    /// Restore a file from its backup.
+    Restore { path: PathBuf, backup: PathBuf },
+
+    /// Remove a newly created file.
+    Remove(PathBuf),
+
+    /// Rename a moved file back.
+    Rename { from: PathBuf, to: PathBuf },
+
+    /// Remove a newly created directory.
+    Dir(PathBuf),
+}
+
+impl<'a> Transaction<'a> {
+    /// Begin a new transaction in the given store.
+    #[must_use]
+    pub const fn new(store: &'a Store) -> Self {
+        Self {
+            store,
+            backup_dir: None,
+            backups: 0,
+            journal: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Get the store this transaction changes.
+    #[must_use]
+    pub const fn store(&self) -> &'a Store {
+        self.store
+    }
+
+    /// Atomically write the given contents to a file.
+    ///
+    /// Missing parent directories are created. If the path is a symlink, the
+    /// file it points to is replaced.
+    pub fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+        let path = match path.symlink_metadata() {
+            Ok(meta) if meta.file_type().is_symlink() =>
+                fs::canonicalize(path).map_err(|err| Err::Write(path.into(), err))?,
+            _ => path.to_path_buf(),
+        };
+        self.create_dir_all(path.parent().ok_or(Err::NoParent)?)?;
+
+        let backup = if path.exists() {
+            let backup = self.backup_path()?;
+            link_or_copy(&path, &backup).map_err(Err::Backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        write_atomic(&path, contents).map_err(|err| Err::Write(path.clone(), err))?;
+        self.journal.push(match backup {
+            Some(backup) => Undo::Restore { path, backup },
+            None => Undo::Remove(path),
+        });
+        Ok(())
+    }
+
+    /// Atomically write ciphertext to a file.
+    pub fn write_ciphertext(&mut self, path: &Path, ciphertext: &Ciphertext) -> Result<()> {
+        self.write(path, ciphertext.unsecure_ref())
+    }
+
+    /// Encrypt plaintext for recipients and atomically write it to a file.
+    pub fn encrypt(
+        &mut self,
+        context: &mut Context,
+        recipients: &Recipients,
+        plaintext: Plaintext,
+        path: &Path,
+    ) -> Result<()> {
+        let ciphertext = context.encrypt(recipients, plaintext)?;
+        self.write_ciphertext(path, &ciphertext)
+    }
+
+    /// Remove a file or symlink.
+    pub fn remove(&mut self, path: &Path) -> Result<()> {
+        let backup = self.backup_path()?;
+        move_file(path, &backup).map_err(|err| Err::Remove(path.into(), err))?;
+        self.journal.push(Undo::Restore {
+            path: path.into(),
+            backup,
+        });
+        Ok(())
+    }
+
+    /// Rename a file or symlink, replacing any existing file at the target.
+    ///
+    /// Missing parent directories of the target are created.
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+        self.create_dir_all(to.parent().ok_or(Err::NoParent)?)?;
+        if to.symlink_metadata().is_ok() {
+            self.remove(to)?;
+        }
+
+        fs::rename(from, to).map_err(|err| Err::Rename(from.into(), to.into(), err))?;
+        sync_parent(to).map_err(|err| Err::Rename(from.into(), to.into(), err))?;
+        self.journal.push(Undo::Rename {
+            from: from.into(),
+            to:   to.into(),
+        });
+        Ok(())
+    }
+
+    /// Create a symlink at the given path, replacing any existing file.
+    ///
+    /// Missing parent directories are created.
+    #[cfg(unix)]
+    pub fn symlink(&mut self, target: &Path, path: &Path) -> Result<()> {
+        self.create_dir_all(path.parent().ok_or(Err::NoParent)?)?;
+        if path.symlink_metadata().is_ok() {
+            self.remove(path)?;
+        }
+
+        std::os::unix::fs::symlink(target, path).map_err(|err| Err::Write(path.into(), err))?;
+        self.journal.push(Undo::Remove(path.into()));
+        Ok(())
+    }
+
+    /// Create a directory and all missing parents.
+    pub fn create_dir_all(&mut self, path: &Path) -> Result<()> {
+        if path.is_dir() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        fs::create_dir(path).map_err(|err| Err::CreateDir(path.into(), err))?;
+        self.journal.push(Undo::Dir(path.into()));
+        Ok(())
+    }
+
+    /// Commit the transaction, keeping all changes.
+    pub fn commit(mut self) -> Result<()> {
+        self.done = true;
+        self.journal.clear();
+        self.remove_backups()
+    }
+
+    /// Commit the transaction and commit its changes to the store git
+    /// repository, see [`Sync::finalize`].
+    ///
+    /// If committing to git fails, all changes are rolled back. Pushing
+    /// happens after the transaction is committed, a failed push keeps the
+    /// changes.
+    pub fn commit_sync<M: AsRef<str>>(mut self, sync: &Sync, msg: M) -> Result<()> {
+        if let Err(err) = sync.commit(msg.as_ref()) {
+            self.rollback()?;
+            return Err(err);
+        }
+
+        self.commit()?;
+        sync.finalize(msg)
+    }
+
+    /// Roll back the transaction, undoing all changes in reverse order.
+    ///
+    /// Continues undoing other changes if one fails. Backups are kept if
+    /// anything could not be undone.
+    pub fn rollback(&mut self) -> Result<()> {
+        self.done = true;
+
+        let mut failed = None;
+        for undo in mem::take(&mut self.journal).into_iter().rev() {
+            if let Err(err) = undo.apply() {
+                failed.get_or_insert(err);
+            }
+        }
+
+        match failed {
+            None => self.remove_backups(),
+            Some(err) =>
+                Err(Err::Rollback(self.backup_dir.clone().unwrap_or_default(), err).into()),
+        }
+    }
+
+    /// Get a path to back up a file to, creating the backup directory.
+    fn backup_path(&mut self) -> Result<PathBuf> {
+        let dir = if let Some(dir) = &self.backup_dir {
+            dir.clone()
+        } else {
+            let base = if self.store.root.join(".git").is_dir() {
+                self.store.root.join(GIT_BACKUP_DIR)
+            } else {
+                self.store.root.join(BACKUP_DIR)
+            };
+            let counter = BACKUP_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let dir = base.join(format!("{}-{counter}", process::id()));
+            fs::create_dir_all(&dir).map_err(Err::Backup)?;
+            self.backup_dir.insert(dir).clone()
+        };
+
+        self.backups += 1;
+        Ok(dir.join(self.backups.to_string()))
+    }
+
+    /// Remove the backup directory, and its parent if it is empty.
+    fn remove_backups(&mut self) -> Result<()> {
+        if let Some(dir) = self.backup_dir.take() {
+            fs::remove_dir_all(&dir).map_err(Err::Backup)?;
+            if let Some(base) = dir.parent() {
+                let _ = fs::remove_dir(base);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Transaction<'_> {
+    /// Roll back if not committed or rolled back explicitly.
+    ///
+    /// Errors cannot be returned here, so a failed rollback is reported on
+    /// stderr with the directory backups are kept in.
+    fn drop(&mut self) {
+        if !self.done {
+            if let Err(err) = self.rollback() {
+                eprintln!("error: {err:#}");
+            }
+        }
+    }
+}
+
+impl Undo {
+    /// Apply this undo step.
+    ///
+    /// Tolerates files that are already gone.
+    fn apply(self) -> io::Result<()> {
+        match self {
+            Self::Restore { path, backup } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                move_file(&backup, &path)?;
+                sync_parent(&path)
+            },
+            Self::Remove(path) => ignore_not_found(fs::remove_file(path)),
+            Self::Rename { from, to } => {
+                if let Some(parent) = from.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&to, &from)?;
+                sync_parent(&from)
+            },
+            Self::Dir(path) => match fs::remove_dir(&path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound && path_is_empty_dir(&path) =>
+                    Err(err),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Hard link a file to a new path, copy it if linking is not possible.
+fn link_or_copy(from: &Path, to: &Path) -> io::Result<()> {
+    fs::hard_link(from, to).or_else(|_| fs::copy(from, to).map(|_| ()))
+}
+
+/// Move a file or symlink to a new path, copy it if renaming is not possible.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.symlink_metadata()?.file_type().is_symlink() {
+        return fs::rename(from, to);
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+/// Treat a not found error as success.
+fn ignore_not_found(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Check whether the given path is an empty directory.
+fn path_is_empty_dir(path: &Path) -> bool {
+    path.read_dir()
+        .is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// Transaction error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to write file: {}", _0.display())]
+    Write(PathBuf, #[source] io::Error),
+
+    #[error("failed to remove file: {}", _0.display())]
+    Remove(PathBuf, #[source] io::Error),
+
+    #[error("failed to rename file: {} -> {}", _0.display(), _1.display())]
+    Rename(PathBuf, PathBuf, #[source] io::Error),
+
+    #[error("failed to create directory: {}", _0.display())]
+    CreateDir(PathBuf, #[source] io::Error),
+
+    #[error("failed to write file, unknown parent directory")]
+    NoParent,
+
+    #[error("failed to back up file for transaction")]
+    Backup(#[source] io::Error),
+
+    #[error("failed to roll back changes, backups kept in: {}", _0.display())]
+    Rollback(PathBuf, #[source] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Create an empty store in a new temporary directory.
+    ///
+    /// The directory is removed when the returned guard is dropped.
+    fn temp_store() -> (TempDir, Store) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store {
+            root: dir.path().into(),
+        };
+        (dir, store)
+    }
+
+    #[test]
+    fn rollback_restores_store() {
+        let (_dir, store) = temp_store();
+        let root = &store.root;
+        fs::write(root.join("a.gpg"), "a").unwrap();
+        fs::write(root.join("b.gpg"), "b").unwrap();
+
+        let mut tx = store.transaction();
+        tx.write(&root.join("a.gpg"), b"changed").unwrap();
+        tx.write(&root.join("dir/new.gpg"), b"new").unwrap();
+        tx.rename(&root.join("b.gpg"), &root.join("a.gpg")).unwrap();
+        tx.remove(&root.join("dir/new.gpg")).unwrap();
+        assert_eq!(fs::read_to_string(root.join("a.gpg")).unwrap(), "b");
+        drop(tx);
+
+        assert_eq!(fs::read_to_string(root.join("a.gpg")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(root.join("b.gpg")).unwrap(), "b");
+        assert!(!root.join("dir").exists());
+        assert!(!root.join(BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn rollback_failure_keeps_backups() {
+        let (_dir, store) = temp_store();
+        let root = &store.root;
+        fs::write(root.join("a.gpg"), "a").unwrap();
+
+        let mut tx = store.transaction();
+        tx.write(&root.join("a.gpg"), b"changed").unwrap();
+        tx.write(&root.join("b.gpg"), b"new").unwrap();
+        let backup_dir = tx.backup_dir.clone().unwrap();
+        fs::remove_file(backup_dir.join("1")).unwrap();
+
+        // Other changes are still undone, the error names the backups
+        let err = tx.rollback().unwrap_err();
+        match err.downcast_ref() {
+            Some(Err::Rollback(dir, _)) => assert_eq!(dir, &backup_dir),
+            _ => panic!("expected rollback error"),
+        }
+        assert!(!root.join("b.gpg").exists());
+        assert!(backup_dir.is_dir());
+        drop(tx);
+    }
+
+    #[test]
+    fn commit_keeps_changes() {
+        let (_dir, store) = temp_store();
+        let root = &store.root;
+        fs::write(root.join("a.gpg"), "a").unwrap();
+
+        let mut tx = store.transaction();
+        tx.write(&root.join("a.gpg"), b"changed").unwrap();
+        tx.rename(&root.join("a.gpg"), &root.join("dir/b.gpg"))
+            .unwrap();
+        tx.commit().unwrap();
+
+        assert!(!root.join("a.gpg").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("dir/b.gpg")).unwrap(),
+            "changed"
+        );
+        assert!(!root.join(BACKUP_DIR).exists());
+    }
+}
@@ -0,0 +1,518 @@
+//! Trash for removed secrets.
+//!
+//! Removed secrets are moved into the hidden `.trash` directory in the store
+//! root, which the secret iterator skips. Each trashed secret has an info file
+//! recording its original path and removal time, so it can be restored later.
+//! Aliases are not moved, their symlink target is recorded instead.
+
+use std::{
+    fmt::Write,
+    fs, io,
+    path::{Component, Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{
+    store::{relative_path, strip_secret_suffix},
+    Secret, Store,
+};
+
+/// Trash directory in the store root.
+pub const TRASH_DIR: &str = ".trash";
+
+/// Directory in the trash holding trashed secret files.
+const FILES_DIR: &str = "files";
+
+/// Directory in the trash holding info files.
+const INFO_DIR: &str = "info";
+
+/// Info file suffix.
+const INFO_SUFFIX: &str = ".trashinfo";
+
+/// Info file key for the original path, relative to the store root.
+const INFO_PATH: &str = "Path";
+
+/// Info file key for the removal time, in seconds since the Unix epoch.
+const INFO_DATE: &str = "DeletionDate";
+
+/// Info file key for the symlink target of an alias.
+const INFO_TARGET: &str = "Target";
+
+/// Trash helper for given store.
+pub struct Trash<'a> {
+    /// The store.
+    store: &'a Store,
+}
+
+/// A secret in the trash.
+#[derive(Debug, Clone)]
+pub struct TrashEntry {
+    /// Unique ID of the entry in the trash.
+    pub id: String,
+
+    /// Original secret name.
+    pub name: String,
+
+    /// Original secret path, relative to the store root.
+    pub path: PathBuf,
+
+    /// Time the secret was removed at.
+    pub removed: SystemTime,
+
+    /// Symlink target if the secret was an alias.
+    pub target: Option<PathBuf>,
+}
+
+impl<'a> Trash<'a> {
+    /// Construct new trash helper for given store.
+    #[must_use]
+    pub const fn new(store: &'a Store) -> Self {
+        Self { store }
+    }
+
+    /// Get the trash directory.
+    #[must_use]
+    pub fn dir(&self) -> PathBuf {
+        self.store.root.join(TRASH_DIR)
+    }
+
+    /// Move the given secret into the trash.
+    ///
+    /// If the secret is an alias, the symlink is removed and its target is
+    /// recorded.
+    pub fn add(&self, secret: &Secret) -> Result<TrashEntry> {
+        let path = relative_path(&self.store.root, &secret.path)
+            .map_err(|_| Err::UnknownRoot)?
+            .to_path_buf();
+        let target = if fs::symlink_metadata(&secret.path)
+            .map_err(Err::Move)?
+            .file_type()
+            .is_symlink()
+        {
+            Some(fs::read_link(&secret.path).map_err(Err::Move)?)
+        } else {
+            None
+        };
+
+        fs::create_dir_all(self.dir().join(FILES_DIR)).map_err(Err::CreateDir)?;
+        fs::create_dir_all(self.dir().join(INFO_DIR)).map_err(Err::CreateDir)?;
+
+        let mut entry = TrashEntry {
+            id: String::new(),
+            name: secret.name.clone(),
+            path,
+            removed: SystemTime::now(),
+            target,
+        };
+        entry.id = self.free_id(&entry);
+
+        // Write info first, a secret must never be in the trash without it
+        fs::write(self.info_path(&entry.id), entry.to_info()).map_err(Err::WriteInfo)?;
+        let moved = if entry.is_alias() {
+            fs::remove_file(&secret.path)
+        } else {
+            fs::rename(&secret.path, self.file_path(&entry))
+        };
+        if let Err(err) = moved {
+            let _ = fs::remove_file(self.info_path(&entry.id));
+            return Err(Err::Move(err).into());
+        }
+
+        Ok(entry)
+    }
+
+    /// List all secrets in the trash, oldest removal first.
+    pub fn list(&self) -> Result<Vec<TrashEntry>> {
+        let dir = self.dir().join(INFO_DIR);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut entries = vec![];
+        for file in fs::read_dir(dir).map_err(Err::ReadInfo)? {
+            let path = file.map_err(Err::ReadInfo)?.path();
+            let id = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(INFO_SUFFIX))
+            {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            let info = fs::read_to_string(&path).map_err(Err::ReadInfo)?;
+            entries.push(TrashEntry::from_info(id, &info)?);
+        }
+
+        entries.sort_by(|a, b| a.removed.cmp(&b.removed).then_with(|| a.id.cmp(&b.id)));
+        Ok(entries)
+    }
+
+    /// Find secrets in the trash by ID or original name, newest removal first.
+    ///
+    /// An exact ID match is preferred over matches on the name.
+    pub fn find(&self, query: &str) -> Result<Vec<TrashEntry>> {
+        let query = strip_secret_suffix(query.trim_matches('/'));
+        let mut entries = self.list()?;
+        entries.reverse();
+
+        if let Some(entry) = entries.iter().find(|entry| entry.id == query) {
+            return Ok(vec![entry.clone()]);
+        }
+        entries.retain(|entry| entry.name == query);
+        Ok(entries)
+    }
+
+    /// Restore the given secret from the trash to its original path.
+    ///
+    /// Fails if a file already exists at the original path. An alias is
+    /// recreated as symlink, its target must exist.
+    pub fn restore(&self, entry: &TrashEntry) -> Result<Secret> {
+        entry.check_paths()?;
+        let path = self.store.root.join(&entry.path);
+        if fs::symlink_metadata(&path).is_ok() {
+            return Err(Err::Exists(entry.name.clone()).into());
+        }
+        if let Some(target) = entry.alias_target() {
+            if !self.store.root.join(target).exists() {
+                return Err(Err::NoAliasTarget(entry.name.clone()).into());
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(Err::CreateDir)?;
+        }
+        entry
+            .target
+            .as_ref()
+            .map_or_else(
+                || fs::rename(self.file_path(entry), &path),
+                |target| symlink(target, &path),
+            )
+            .map_err(Err::Restore)?;
+        fs::remove_file(self.info_path(&entry.id)).map_err(Err::RemoveInfo)?;
+        self.remove_empty();
+
+        Ok(Secret::from(self.store, path))
+    }
+
+    /// Permanently remove the given secret from the trash.
+    pub fn remove(&self, entry: &TrashEntry) -> Result<()> {
+        if !entry.is_alias() {
+            match fs::remove_file(self.file_path(entry)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound =>
+                    return Err(Err::Remove(err).into()),
+                _ => {},
+            }
+        }
+        fs::remove_file(self.info_path(&entry.id)).map_err(Err::RemoveInfo)?;
+        self.remove_empty();
+        Ok(())
+    }
+
+    /// Remove the trash directories if they are empty.
+    fn remove_empty(&self) {
+        let dir = self.dir();
+        let _ = fs::remove_dir(dir.join(FILES_DIR));
+        let _ = fs::remove_dir(dir.join(INFO_DIR));
+        let _ = fs::remove_dir(dir);
+    }
+
+    /// Get the path of the info file for the given entry ID.
+    fn info_path(&self, id: &str) -> PathBuf {
+        self.dir().join(INFO_DIR).join(format!("{id}{INFO_SUFFIX}"))
+    }
+
+    /// Get the path of the trashed secret file for the given entry.
+    ///
+    /// The file keeps the extension of the original secret.
+    fn file_path(&self, entry: &TrashEntry) -> PathBuf {
+        let mut file = self.dir().join(FILES_DIR).join(&entry.id).into_os_string();
+        if let Some(extension) = entry.path.extension() {
+            file.push(".");
+            file.push(extension);
+        }
+        file.into()
+    }
+
+    /// Find an unused ID for the given entry.
+    ///
+    /// The ID is based on its file name and removal time, such as
+    /// `github-1600000000`, so it never equals a secret name.
+    fn free_id(&self, entry: &TrashEntry) -> String {
+        let name = entry
+            .name
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty() && !name.starts_with('.'))
+            .unwrap_or("secret");
+        let name = format!("{}-{}", name, entry.removed_secs());
+        let mut candidate = entry.clone();
+        candidate.id.clone_from(&name);
+        let mut i = 1;
+        while self.info_path(&candidate.id).exists() || self.file_path(&candidate).exists() {
+            i += 1;
+            candidate.id = format!("{name}.{i}");
+        }
+        candidate.id
+    }
+}
+
+impl TrashEntry {
+    /// Check whether this secret was an alias.
+    #[must_use]
+    pub const fn is_alias(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Get the path of the alias target, relative to the store root.
+    ///
+    /// Returns `None` if this secret was not an alias, or if its target is
+    /// outside the store.
+    #[must_use]
+    pub fn alias_target(&self) -> Option<PathBuf> {
+        let target = self.target.as_ref()?;
+        normalize(
+            &self
+                .path
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(target),
+        )
+    }
+
+    /// Check that the original path and alias target stay within the store.
+    ///
+    /// Info files may be tampered with, restoring must never write outside the
+    /// store root.
+    fn check_paths(&self) -> Result<(), Err> {
+        let path_safe = self.path.components().next().is_some()
+            && self
+                .path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        let target_safe = self.target.is_none() || self.alias_target().is_some();
+        if path_safe && target_safe {
+            Ok(())
+        } else {
+            Err(Err::UnsafePath(self.id.clone()))
+        }
+    }
+
+    /// Get the time elapsed since removal.
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        self.removed.elapsed().unwrap_or_default()
+    }
+
+    /// Get the removal time in seconds since the Unix epoch.
+    fn removed_secs(&self) -> u64 {
+        self.removed
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    /// Render the info file for this entry.
+    fn to_info(&self) -> String {
+        let mut info = format!(
+            "{INFO_PATH}={}\n{INFO_DATE}={}\n",
+            self.path.display(),
+            self.removed_secs()
+        );
+        if let Some(target) = &self.target {
+            let _ = writeln!(info, "{INFO_TARGET}={}", target.display());
+        }
+        info
+    }
+
+    /// Parse an info file for the entry with the given ID.
+    fn from_info(id: String, info: &str) -> Result<Self> {
+        let (mut path, mut removed, mut target) = (None, UNIX_EPOCH, None);
+        for (key, value) in info.lines().filter_map(|line| line.split_once('=')) {
+            match key.trim() {
+                INFO_PATH => path = Some(PathBuf::from(value)),
+                INFO_DATE => {
+                    let secs = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| Err::InvalidInfo(id.clone()))?;
+                    removed = UNIX_EPOCH + Duration::from_secs(secs);
+                },
+                INFO_TARGET => target = Some(PathBuf::from(value)),
+                _ => {},
+            }
+        }
+
+        let path = path.ok_or_else(|| Err::InvalidInfo(id.clone()))?;
+        let name = strip_secret_suffix(&path.to_string_lossy()).to_string();
+        let entry = Self {
+            id,
+            name,
+            path,
+            removed,
+            target,
+        };
+        entry.check_paths()?;
+        Ok(entry)
+    }
+}
+
+/// Lexically normalize the given relative path, resolving `.` and `..`.
+///
+/// Returns `None` if the path is not relative, or if it leaves its root
+/// through `..`.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir =>
+                if !normalized.pop() {
+                    return None;
+                },
+            Component::Normal(component) => normalized.push(component),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+/// Create a symlink at `dst` which points to `src`.
+fn symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(src, dst)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::symlink_file(src, dst)
+    }
+}
+
+/// A trash error.
+#[derive(Debug, Error)]
+pub enum Err {
+    #[error("failed to determine secret path relative to store root")]
+    UnknownRoot,
+
+    #[error("failed to create trash directory")]
+    CreateDir(#[source] io::Error),
+
+    #[error("failed to write trash info file")]
+    WriteInfo(#[source] io::Error),
+
+    #[error("failed to read trash info file")]
+    ReadInfo(#[source] io::Error),
+
+    #[error("invalid trash info file for '{}'", _0)]
+    InvalidInfo(String),
+
+    #[error("trash info file for '{}' has path outside password store", _0)]
+    UnsafePath(String),
+
+    #[error("failed to remove trash info file")]
+    RemoveInfo(#[source] io::Error),
+
+    #[error("failed to move secret into trash")]
+    Move(#[source] io::Error),
+
+    #[error("failed to restore secret from trash")]
+    Restore(#[source] io::Error),
+
+    #[error("failed to remove secret from trash")]
+    Remove(#[source] io::Error),
+
+    #[error("cannot restore secret, '{}' already exists", _0)]
+    Exists(String),
+
+    #[error("cannot restore alias '{}', its target does not exist", _0)]
+    NoAliasTarget(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_round_trip() {
+        let entry = TrashEntry {
+            id:      "github-1600000000".into(),
+            name:    "social/github".into(),
+            path:    "social/github.gpg".into(),
+            removed: UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+            target:  Some("../work/github.gpg".into()),
+        };
+        let parsed = TrashEntry::from_info(entry.id.clone(), &entry.to_info()).unwrap();
+        assert_eq!(parsed.id, "github-1600000000");
+        assert_eq!(parsed.name, "social/github");
+        assert_eq!(parsed.path, entry.path);
+        assert_eq!(parsed.removed, entry.removed);
+        assert_eq!(parsed.target, entry.target);
+        assert_eq!(parsed.alias_target(), Some("work/github.gpg".into()));
+
+        assert!(TrashEntry::from_info("x".into(), "DeletionDate=1\n").is_err());
+        assert!(TrashEntry::from_info("x".into(), "Path=x.gpg\nDeletionDate=now\n").is_err());
+    }
+
+    #[test]
+    fn normalize_path() {
+        assert_eq!(
+            normalize(Path::new("a/./b/../c.gpg")),
+            Some(PathBuf::from("a/c.gpg"))
+        );
+        assert_eq!(normalize(Path::new("a/../../c.gpg")), None);
+        assert_eq!(normalize(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn reject_paths_outside_store() {
+        let info = |path: &str, target: Option<&str>| {
+            let mut info = format!("Path={path}\nDeletionDate=1\n");
+            if let Some(target) = target {
+                info.push_str(&format!("Target={target}\n"));
+            }
+            TrashEntry::from_info("x".into(), &info)
+        };
+
+        assert!(info("a/b.gpg", None).is_ok());
+        assert!(info("a/b.gpg", Some("../c.gpg")).is_ok());
+        assert!(info("a/b.gpg", Some("./c/../d.gpg")).is_ok());
+
+        for path in [
+            "",
+            "/etc/passwd",
+            "../outside.gpg",
+            "a/../../b.gpg",
+            "./a.gpg",
+        ] {
+            assert!(info(path, None).is_err(), "path {path:?}");
+        }
+        for target in ["/etc/passwd", "../../outside.gpg", "../a/../../b.gpg"] {
+            assert!(info("a/b.gpg", Some(target)).is_err(), "target {target:?}");
+        }
+
+        // Restoring checks paths again, entries may not come from info files
+        let store = Store {
+            root: std::env::temp_dir().join("prs-trash-unused"),
+        };
+        let entry = TrashEntry {
+            id:      "x".into(),
+            name:    "x".into(),
+            path:    "../x.gpg".into(),
+            removed: UNIX_EPOCH,
+            target:  None,
+        };
+        let err = Trash::new(&store).restore(&entry).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Err::UnsafePath(_))));
+        let entry = TrashEntry {
+            path: "a/x.gpg".into(),
+            target: Some("/etc/passwd".into()),
+            ..entry
+        };
+        let err = Trash::new(&store).restore(&entry).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Err::UnsafePath(_))));
+    }
+}
@@ -5,8 +5,10 @@ use secstr::SecVec;
 use thiserror::Error;
 use zeroize::Zeroize;
 
+use crate::body::SecretBody;
+
 /// Delimiter for properties.
-const PROPERTY_DELIMITER: char = ':';
+pub(crate) const PROPERTY_DELIMITER: char = ':';
 
 /// Newline character(s) on this platform.
 #[cfg(not(windows))]
@@ -124,22 +126,14 @@ impl Plaintext {
     /// Returns line with the given property. The property prefix is removed, and only the trimmed
     /// value is returned. Returns an error if the property does not exist.
     ///
+    /// If a YAML document follows a `---` line, nested properties may be selected with a dotted
+    /// path. See [`SecretBody::property`].
+    ///
     /// This will never return the first line being the password.
     pub fn property(&self, property: &str) -> Result<Self> {
-        let property = property.trim().to_uppercase();
-        self.unsecure_to_str()
-            .map_err(Err::Utf8)?
-            .lines()
-            .skip(1)
-            .find_map(|line| {
-                let mut parts = line.splitn(2, PROPERTY_DELIMITER);
-                if parts.next().unwrap().trim().to_uppercase() == property {
-                    Some(parts.next().map_or("", str::trim).into())
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| Err::Property(property.to_lowercase()).into())
+        SecretBody::parse(self)?
+            .property(property)
+            .ok_or_else(|| Err::Property(property.trim().to_lowercase()).into())
     }
 
     // TODO: use
@@ -164,10 +158,14 @@ impl Plaintext {
     ///
     /// Optionally adds platform newline.
     pub fn append(&mut self, other: &Self, newline: bool) {
-        let mut data = self.unsecure_ref().to_vec();
-        if newline {
-            data.extend_from_slice(NEWLINE.as_bytes());
-        }
+        let newline = if newline { NEWLINE } else { "" };
+
+        // Allocate once, growing would leave unzeroed copies behind
+        let mut data = Vec::with_capacity(
+            self.unsecure_ref().len() + newline.len() + other.unsecure_ref().len(),
+        );
+        data.extend_from_slice(self.unsecure_ref());
+        data.extend_from_slice(newline.as_bytes());
         data.extend_from_slice(other.unsecure_ref());
         self.0 = data.into();
     }
@@ -1,6 +1,12 @@
-use std::path::{Path, PathBuf};
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 use std::process::{Command, Stdio};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use anyhow::Result;
 #[cfg(all(feature = "tomb", target_os = "linux"))]
@@ -46,6 +52,66 @@ pub fn append_file_name(path: &Path, suffix: &str) -> Result<PathBuf> {
     )))
 }
 
+/// Counter to make temporary file names unique within this process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Atomically write the given contents to a file.
+///
+/// The contents are written to a temporary file in the same directory, which
+/// is synced to disk and then renamed to replace the target. A crash or error
+/// never leaves a partially written file. If the path is a symlink, the file
+/// it points to is replaced.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    // Replace symlink target, not the symlink itself
+    let path = match path.symlink_metadata() {
+        Ok(meta) if meta.file_type().is_symlink() => fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+
+    let temp = temp_path(&path)?;
+    if let Err(err) = write_synced(&temp, contents).and_then(|()| fs::rename(&temp, &path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(err);
+    }
+    sync_parent(&path)
+}
+
+/// Build a unique temporary file path next to the given path.
+pub(crate) fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, Err::UnknownName))?
+        .to_string_lossy();
+    Ok(path.with_file_name(format!(".{}.prs-tmp-{}-{}", name, process::id(), counter)))
+}
+
+/// Write contents to a new file with store permissions, sync it to disk.
+fn write_synced(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o666 - (0o666 & *crate::STORE_UMASK));
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Sync the directory containing the given path to disk, to persist renames.
+pub(crate) fn sync_parent(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
 /// Chown a path to the current process' with `sudo`.
 #[cfg(all(feature = "tomb", target_os = "linux"))]
 pub(crate) fn sudo_chown(path: &Path, uid: u32, gid: u32, recursive: bool) -> Result<()> {